@@ -0,0 +1,97 @@
+//! Walk throughput benchmark: particles stuck per second with and without
+//! distance-field jumps, and serial vs parallel walking.
+//! Run with `cargo bench --bench walk`.
+
+use dla_sim_tui::settings::NeighborhoodType;
+use dla_sim_tui::{DlaSimulation, SeedPattern, SimulationSettings};
+use std::time::Instant;
+
+const RUNS: u64 = 3;
+
+struct Case {
+    name: &'static str,
+    size: usize,
+    particles: usize,
+    settings: SimulationSettings,
+}
+
+fn cases() -> Vec<Case> {
+    // Jumps only apply to continuous walks
+    let continuous = SimulationSettings {
+        lattice_walk: false,
+        ..SimulationSettings::default()
+    };
+    let moore_continuous = SimulationSettings {
+        neighborhood: NeighborhoodType::Moore,
+        lattice_walk: false,
+        walk_step_size: 1.5,
+        ..SimulationSettings::default()
+    };
+    vec![
+        Case { name: "4-nbr 300x300", size: 300, particles: 4000, settings: continuous.clone() },
+        Case { name: "4-nbr 600x600", size: 600, particles: 12000, settings: continuous },
+        Case { name: "moore 600x600", size: 600, particles: 12000, settings: moore_continuous },
+    ]
+}
+
+/// Returns (particles stuck, seconds) summed over RUNS seeded runs.
+/// `threads` of 1 uses the serial stepper.
+fn measure(case: &Case, jumps: bool, threads: usize) -> (usize, f64) {
+    let mut stuck = 0;
+    let mut secs = 0.0;
+    for seed in 0..RUNS {
+        let mut sim = DlaSimulation::new(case.size, case.size);
+        sim.settings = case.settings.clone();
+        sim.settings.distance_jumps = jumps;
+        sim.settings.max_walk_iterations = 50000;
+        sim.num_particles = case.particles;
+        sim.rng_seed = Some(seed);
+        sim.reset_with_seed(SeedPattern::Point);
+
+        let start_stuck = sim.particles_stuck;
+        let start = Instant::now();
+        if threads > 1 {
+            while sim.step_parallel(threads) > 0 {}
+        } else {
+            while sim.step() {}
+        }
+        secs += start.elapsed().as_secs_f64();
+        stuck += sim.particles_stuck - start_stuck;
+    }
+    (stuck, secs)
+}
+
+fn main() {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    println!("{:<18} {:>14} {:>14} {:>9}", "case", "off (p/s)", "jumps (p/s)", "speedup");
+    for case in cases() {
+        let (n_off, t_off) = measure(&case, false, 1);
+        let (n_on, t_on) = measure(&case, true, 1);
+        let rate_off = n_off as f64 / t_off;
+        let rate_on = n_on as f64 / t_on;
+        println!(
+            "{:<18} {:>14.0} {:>14.0} {:>8.1}x",
+            case.name,
+            rate_off,
+            rate_on,
+            rate_on / rate_off
+        );
+    }
+
+    println!();
+    println!("{:<18} {:>14} {:>14} {:>9}", "case", "serial (p/s)", format!("{} thr (p/s)", cores), "speedup");
+    for case in cases() {
+        let (n_serial, t_serial) = measure(&case, true, 1);
+        let (n_par, t_par) = measure(&case, true, cores);
+        let rate_serial = n_serial as f64 / t_serial;
+        let rate_par = n_par as f64 / t_par;
+        println!(
+            "{:<18} {:>14.0} {:>14.0} {:>8.1}x",
+            case.name,
+            rate_serial,
+            rate_par,
+            rate_par / rate_serial
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Fractal analysis of a grown cluster: radius of gyration, mass-radius
+//! scaling N(r) and the two-point correlation dimension, plus per-run time series.
+//!
+//! Every estimate treats the stuck particles as points (`DlaSimulation::discs`),
+//! so lattice and off-lattice runs are measured the same way. Wall deposits
+//! grown under the Stick boundary are left out of the simulation-level
+//! estimates. Estimates that don't have enough data yet are reported as 0.0,
+//! like `DlaSimulation::calculate_fractal_dimension`.
+
+use crate::simulation::{Disc, DlaSimulation};
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// Fewest particles before any dimension is estimated
+pub const MIN_ANALYSIS_PARTICLES: usize = 50;
+/// Samples kept per run by `AnalysisTracker` (and the default for `time_series`)
+pub const SERIES_SAMPLES: usize = 64;
+/// Log-spaced radii (or prefix sizes) used for each scaling fit
+const FIT_POINTS: usize = 10;
+/// Smallest radius used in the scaling fits, in cells
+const MIN_FIT_RADIUS: f32 = 2.0;
+/// Reference particles sampled for the correlation function
+const CORRELATION_REFERENCES: usize = 256;
+/// Rg(N) is fitted over the last 1/RG_SPAN_FACTOR..1 of the run's history
+const RG_SPAN_FACTOR: usize = 32;
+
+/// Straight-line fit of log y against log x
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PowerLawFit {
+    /// Slope in log-log space
+    pub exponent: f32,
+    /// Fit quality (1.0 = perfect power law)
+    pub r_squared: f32,
+}
+
+/// Fit y = c * x^exponent by least squares in log-log space.
+/// Non-positive points are skipped; returns None with fewer than 3 usable points.
+pub fn fit_power_law(xs: &[f32], ys: &[f32]) -> Option<PowerLawFit> {
+    let (lx, ly): (Vec<f64>, Vec<f64>) = xs
+        .iter()
+        .zip(ys)
+        .filter(|(x, y)| **x > 0.0 && **y > 0.0)
+        .map(|(x, y)| ((*x as f64).ln(), (*y as f64).ln()))
+        .unzip();
+    if lx.len() < 3 {
+        return None;
+    }
+
+    let n = lx.len() as f64;
+    let mean_x = lx.iter().sum::<f64>() / n;
+    let mean_y = ly.iter().sum::<f64>() / n;
+    let sxx: f64 = lx.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = lx.iter().zip(&ly).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let syy: f64 = ly.iter().map(|y| (y - mean_y).powi(2)).sum();
+    if sxx < 1e-12 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let r_squared = if syy < 1e-12 { 1.0 } else { (sxy * sxy) / (sxx * syy) };
+    Some(PowerLawFit {
+        exponent: slope as f32,
+        r_squared: r_squared as f32,
+    })
+}
+
+/// `count` values spaced evenly in log space between lo and hi (inclusive)
+fn log_space(lo: f32, hi: f32, count: usize) -> Vec<f32> {
+    let ratio = (hi / lo).powf(1.0 / (count - 1) as f32);
+    (0..count).map(|i| lo * ratio.powi(i as i32)).collect()
+}
+
+/// Root-mean-square distance of the particles from their centre of mass
+pub fn radius_of_gyration(discs: &[Disc]) -> f32 {
+    if discs.is_empty() {
+        return 0.0;
+    }
+    let n = discs.len() as f64;
+    let (sx, sy, sq) = discs.iter().fold((0.0, 0.0, 0.0), |(sx, sy, sq), d| {
+        let (x, y) = (d.x as f64, d.y as f64);
+        (sx + x, sy + y, sq + x * x + y * y)
+    });
+    let (mx, my) = (sx / n, sy / n);
+    (sq / n - mx * mx - my * my).max(0.0).sqrt() as f32
+}
+
+/// Dimension from how the radius of gyration grew as particles were added:
+/// N ~ Rg^D over the later part of the run. Needs the discs in stick order.
+pub fn rg_dimension(discs: &[Disc]) -> Option<PowerLawFit> {
+    let n = discs.len();
+    let first = (n / RG_SPAN_FACTOR).max(MIN_ANALYSIS_PARTICLES / 2);
+    if n < MIN_ANALYSIS_PARTICLES || first * 2 > n {
+        return None;
+    }
+
+    // Rg of every prefix we need, from running sums
+    let sizes: Vec<usize> = log_space(first as f32, n as f32, FIT_POINTS)
+        .into_iter()
+        .map(|s| (s.round() as usize).clamp(1, n))
+        .collect();
+    let (mut sx, mut sy, mut sq) = (0.0f64, 0.0f64, 0.0f64);
+    let mut next = 0;
+    let mut radii = Vec::with_capacity(sizes.len());
+    for (i, d) in discs.iter().enumerate() {
+        let (x, y) = (d.x as f64, d.y as f64);
+        sx += x;
+        sy += y;
+        sq += x * x + y * y;
+        while next < sizes.len() && sizes[next] == i + 1 {
+            let m = (i + 1) as f64;
+            let rg_sq = sq / m - (sx / m).powi(2) - (sy / m).powi(2);
+            radii.push(rg_sq.max(0.0).sqrt() as f32);
+            next += 1;
+        }
+    }
+
+    let counts: Vec<f32> = sizes.iter().map(|&s| s as f32).collect();
+    fit_power_law(&radii, &counts)
+}
+
+/// Mass-radius scaling: number of particles within each radius of `origin`.
+/// Radii run from MIN_FIT_RADIUS to half the cluster extent, where growth has
+/// stopped screening the interior.
+pub fn mass_radius(discs: &[Disc], origin: (f32, f32)) -> Vec<(f32, usize)> {
+    let mut dists: Vec<f32> = discs
+        .iter()
+        .map(|d| ((d.x - origin.0).powi(2) + (d.y - origin.1).powi(2)).sqrt())
+        .collect();
+    dists.sort_by(f32::total_cmp);
+    let extent = dists.last().copied().unwrap_or(0.0);
+    let max_r = extent / 2.0;
+    if max_r < MIN_FIT_RADIUS * 2.0 {
+        return Vec::new();
+    }
+
+    log_space(MIN_FIT_RADIUS, max_r, FIT_POINTS)
+        .into_iter()
+        .map(|r| (r, dists.partition_point(|&d| d <= r)))
+        .collect()
+}
+
+/// Dimension from the mass-radius relation N(r) ~ r^D
+pub fn mass_radius_dimension(discs: &[Disc], origin: (f32, f32)) -> Option<PowerLawFit> {
+    if discs.len() < MIN_ANALYSIS_PARTICLES {
+        return None;
+    }
+    let (radii, counts): (Vec<f32>, Vec<f32>) = mass_radius(discs, origin)
+        .into_iter()
+        .map(|(r, n)| (r, n as f32))
+        .unzip();
+    fit_power_law(&radii, &counts)
+}
+
+/// Two-point correlation: average number of other particles within each radius
+/// of a particle, sampled over up to CORRELATION_REFERENCES evenly spread
+/// reference particles. Radii run from MIN_FIT_RADIUS to the radius of gyration.
+pub fn correlation(discs: &[Disc]) -> Vec<(f32, f32)> {
+    let max_r = radius_of_gyration(discs);
+    if max_r < MIN_FIT_RADIUS * 2.0 {
+        return Vec::new();
+    }
+    let radii = log_space(MIN_FIT_RADIUS, max_r, FIT_POINTS);
+    let max_sq = max_r * max_r;
+
+    let stride = (discs.len() / CORRELATION_REFERENCES).max(1);
+    let mut refs = 0usize;
+    let mut hist = vec![0usize; radii.len()];
+    for reference in discs.iter().step_by(stride) {
+        refs += 1;
+        for d in discs {
+            let d_sq = (d.x - reference.x).powi(2) + (d.y - reference.y).powi(2);
+            if d_sq > 0.0 && d_sq <= max_sq {
+                let dist = d_sq.sqrt();
+                let bin = radii.partition_point(|&r| r < dist);
+                if bin < hist.len() {
+                    hist[bin] += 1;
+                }
+            }
+        }
+    }
+
+    let mut total = 0;
+    radii
+        .into_iter()
+        .zip(hist)
+        .map(|(r, count)| {
+            total += count;
+            (r, total as f32 / refs as f32)
+        })
+        .collect()
+}
+
+/// Dimension from the correlation function C(r) ~ r^D
+pub fn correlation_dimension(discs: &[Disc]) -> Option<PowerLawFit> {
+    if discs.len() < MIN_ANALYSIS_PARTICLES {
+        return None;
+    }
+    let (radii, c): (Vec<f32>, Vec<f32>) = correlation(discs).into_iter().unzip();
+    fit_power_law(&radii, &c)
+}
+
+/// All estimates for a cluster at one point in its growth
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct AnalysisSample {
+    /// Particles in the analyzed cluster (seed included, unless it was imported)
+    pub particles: usize,
+    pub radius_of_gyration: f32,
+    /// D from N ~ Rg^D over the run so far
+    pub rg_dimension: f32,
+    /// D from N(r) ~ r^D around the grid centre
+    pub mass_radius_dimension: f32,
+    /// D from the two-point correlation function
+    pub correlation_dimension: f32,
+}
+
+/// Analyze a cluster given its discs in stick order, measuring N(r) around `origin`
+pub fn analyze(discs: &[Disc], origin: (f32, f32)) -> AnalysisSample {
+    let exponent = |fit: Option<PowerLawFit>| fit.map_or(0.0, |f| f.exponent);
+    AnalysisSample {
+        particles: discs.len(),
+        radius_of_gyration: radius_of_gyration(discs),
+        rg_dimension: exponent(rg_dimension(discs)),
+        mass_radius_dimension: exponent(mass_radius_dimension(discs, origin)),
+        correlation_dimension: exponent(correlation_dimension(discs)),
+    }
+}
+
+/// The seed-grown cluster in stick order, without wall deposits or the
+/// particles of an imported seed (a text or image seed would swamp the fits)
+pub fn cluster_discs(sim: &DlaSimulation) -> Cow<'_, [Disc]> {
+    let discs = sim.discs();
+    let skip = |d: &Disc| d.data.deposit || sim.is_imported_seed(&d.data);
+    if discs.iter().any(skip) {
+        Cow::Owned(discs.iter().filter(|d| !skip(d)).copied().collect())
+    } else {
+        Cow::Borrowed(discs)
+    }
+}
+
+/// Analyze the simulation's current seed-grown cluster
+pub fn analyze_simulation(sim: &DlaSimulation) -> AnalysisSample {
+    analyze_grown(sim, &cluster_discs(sim))
+}
+
+/// `analyze` for particles grown by `sim`. The Rg and mass-radius fits assume
+/// growth out from a point, so they are left at 0 around an imported seed.
+fn analyze_grown(sim: &DlaSimulation, discs: &[Disc]) -> AnalysisSample {
+    let mut sample = analyze(discs, grid_center(sim));
+    if sim.seed_particles() > 0 {
+        sample.rg_dimension = 0.0;
+        sample.mass_radius_dimension = 0.0;
+    }
+    sample
+}
+
+/// Replay a run's growth from its stick order: `samples` evenly spaced
+/// snapshots from MIN_ANALYSIS_PARTICLES up to the current cluster size
+pub fn time_series(sim: &DlaSimulation, samples: usize) -> Vec<AnalysisSample> {
+    let discs = cluster_discs(sim);
+    if discs.len() < MIN_ANALYSIS_PARTICLES || samples == 0 {
+        return Vec::new();
+    }
+    let span = discs.len() - MIN_ANALYSIS_PARTICLES;
+    let mut series: Vec<AnalysisSample> = (1..=samples)
+        .map(|k| MIN_ANALYSIS_PARTICLES + span * k / samples)
+        .map(|n| analyze_grown(sim, &discs[..n]))
+        .collect();
+    series.dedup_by_key(|s| s.particles);
+    series
+}
+
+fn grid_center(sim: &DlaSimulation) -> (f32, f32) {
+    (sim.grid_width as f32 / 2.0, sim.grid_height as f32 / 2.0)
+}
+
+/// Collects an `AnalysisSample` every few particles while a run grows.
+/// Starts over whenever the simulation is reset or replaced.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisTracker {
+    samples: Vec<AnalysisSample>,
+    run_seed: u64,
+    /// Particles stuck (deposits included) when the last sample was taken
+    sampled_at: usize,
+}
+
+impl AnalysisTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a sample if the cluster has grown enough since the last one.
+    /// Returns true if a sample was added.
+    pub fn update(&mut self, sim: &DlaSimulation) -> bool {
+        let stuck = sim.particles_stuck;
+        let new_run = sim.active_seed() != self.run_seed
+            || (!self.samples.is_empty() && stuck < self.sampled_at);
+        if new_run {
+            self.samples.clear();
+            self.run_seed = sim.active_seed();
+        }
+
+        let interval = (sim.num_particles / SERIES_SAMPLES).max(1);
+        let due = if self.samples.is_empty() {
+            stuck.saturating_sub(sim.seed_particles()) >= MIN_ANALYSIS_PARTICLES
+        } else {
+            stuck >= self.sampled_at + interval
+        };
+        if due {
+            self.samples.push(analyze_simulation(sim));
+            self.sampled_at = stuck;
+        }
+        due
+    }
+
+    /// Samples of the current run, oldest first
+    pub fn samples(&self) -> &[AnalysisSample] {
+        &self.samples
+    }
+
+    /// Most recent sample, if any
+    pub fn latest(&self) -> Option<&AnalysisSample> {
+        self.samples.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{ParticleData, SeedPattern};
+
+    fn disc(x: f32, y: f32) -> Disc {
+        Disc {
+            x,
+            y,
+            data: ParticleData::default(),
+        }
+    }
+
+    #[test]
+    fn test_filled_square_is_two_dimensional() {
+        let mut discs = Vec::new();
+        for y in 0..120 {
+            for x in 0..120 {
+                discs.push(disc(x as f32 + 0.5, y as f32 + 0.5));
+            }
+        }
+        let mass = mass_radius_dimension(&discs, (60.0, 60.0)).unwrap();
+        assert!((mass.exponent - 2.0).abs() < 0.1, "mass-radius D = {}", mass.exponent);
+        let corr = correlation_dimension(&discs).unwrap();
+        assert!((corr.exponent - 2.0).abs() < 0.15, "correlation D = {}", corr.exponent);
+    }
+
+    #[test]
+    fn test_dla_cluster_estimates_agree() {
+        let mut sim = DlaSimulation::new(300, 300);
+        sim.rng_seed = Some(4);
+        sim.num_particles = 3000;
+        sim.settings.off_lattice = true;
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+
+        let sample = analyze_simulation(&sim);
+        for d in [sample.rg_dimension, sample.mass_radius_dimension, sample.correlation_dimension] {
+            assert!((1.45..1.95).contains(&d), "D = {}", d);
+        }
+
+        let series = time_series(&sim, 8);
+        assert_eq!(series.len(), 8);
+        assert_eq!(series.last().unwrap().particles, sim.particles_stuck);
+        assert!(series.windows(2).all(|w| w[0].radius_of_gyration <= w[1].radius_of_gyration));
+    }
+}
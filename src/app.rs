@@ -1,10 +1,15 @@
 use crate::braille;
-use crate::color::{ColorLut, ColorScheme};
-use crate::config::AppConfig;
-use crate::presets::{Preset, PresetManager};
-use crate::recorder::Recorder;
-use crate::simulation::{DlaSimulation, SeedPattern};
-use crate::theme::{Theme, ThemeId};
+use dla_sim_tui::analysis::AnalysisTracker;
+use dla_sim_tui::color::{ColorLut, ColorScheme};
+use dla_sim_tui::config::AppConfig;
+use dla_sim_tui::custom_seed::CustomSeed;
+use dla_sim_tui::presets::{Preset, PresetManager};
+use dla_sim_tui::recorder::Recorder;
+use dla_sim_tui::simulation::{DlaSimulation, PaintTool, SeedPattern};
+use dla_sim_tui::snapshot;
+use dla_sim_tui::state::SimulationState;
+use dla_sim_tui::theme::{Theme, ThemeId};
+use ratatui::style::Color;
 use std::path::Path;
 
 // Parameter adjustment increments
@@ -20,10 +25,22 @@ const MULTI_CONTACT_INCREMENT: i32 = 1;
 const STICKY_GRADIENT_INCREMENT: f32 = 0.1;
 const SPAWN_OFFSET_INCREMENT: f32 = 5.0;
 const ESCAPE_MULT_INCREMENT: f32 = 0.5;
+const DBM_ETA_INCREMENT: f32 = 0.25;
+const MOBILITY_INCREMENT: f32 = 0.1;
 const MIN_RADIUS_INCREMENT: f32 = 10.0;
 const MAX_ITERATIONS_INCREMENT: i32 = 1000;
 const HIGHLIGHT_INCREMENT: i32 = 5;
 
+/// Brush radius range in grid cells for paint mode
+const MIN_BRUSH_SIZE: usize = 1;
+const MAX_BRUSH_SIZE: usize = 12;
+
+/// Timeline scrub steps per full run for the coarse keys
+const TIMELINE_COARSE_STEPS: usize = 100;
+
+/// Default filename offered by the state save/load popups
+const DEFAULT_STATE_FILE: &str = "dla-state.json";
+
 /// Navigate within a popup list with wrap-around
 /// direction: 1 for down, -1 for up
 fn nav_popup_index(idx: &mut usize, len: usize, direction: i32) {
@@ -101,6 +118,26 @@ impl TextInputPopup {
     }
 }
 
+/// Whether the state popup saves or loads a simulation state file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateAction {
+    Save,
+    Load,
+}
+
+/// Scrub position while browsing a paused run's growth history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeline {
+    /// Only particles up to this age are shown
+    pub cursor: usize,
+    /// Age of the newest particle
+    pub end: usize,
+    /// particles_stuck when `end` was taken, to notice edits to the run
+    stuck: usize,
+    /// Whether the run was paused before the timeline opened
+    was_paused: bool,
+}
+
 /// View mode for the UI layout
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum ViewMode {
@@ -136,17 +173,22 @@ pub enum Focus {
     Age,            // color by age toggle
     Boundary,
     ColorScheme,
+    DbmEta,         // dielectric breakdown exponent
     Direction,
     EscapeMult,
     Force,
+    GrowthModel,    // growth rule (DLA, Eden, ballistic, reaction-limited)
     Highlight,
     Invert,
+    Jumps,          // toggle distance-field jumps on/off
     LatticeWalk,    // toggle lattice walk on/off
     MaxIterations,
     MinRadius,
+    Mobility,       // cluster-cluster mobility exponent
     Mode,
     MultiContact,
     Neighborhood,
+    OffLattice,     // toggle off-lattice disc mode on/off
     Particles,
     RadialBias,
     Seed,
@@ -155,6 +197,7 @@ pub enum Focus {
     SpawnOffset,
     Speed,
     Stickiness,
+    StickyEdges,    // which walls are sticky under the Stick boundary
     StickyGradient,
     TipSticky,
     WalkStep,
@@ -167,12 +210,17 @@ impl Focus {
     pub fn next(&self) -> Focus {
         match self {
             Focus::None | Focus::Controls => Focus::AdaptiveStep,
-            // Movement: adaptive, adapt factor, direction, force, lattice, radial, walk
+            // Movement: adaptive, adapt factor, direction, eta, force, jumps, lattice, mobility, model, off-lattice, radial, walk
             Focus::AdaptiveStep => Focus::AdaptiveFactor,
             Focus::AdaptiveFactor => Focus::Direction,
-            Focus::Direction => Focus::Force,
-            Focus::Force => Focus::LatticeWalk,
-            Focus::LatticeWalk => Focus::RadialBias,
+            Focus::Direction => Focus::DbmEta,
+            Focus::DbmEta => Focus::Force,
+            Focus::Force => Focus::Jumps,
+            Focus::Jumps => Focus::LatticeWalk,
+            Focus::LatticeWalk => Focus::Mobility,
+            Focus::Mobility => Focus::GrowthModel,
+            Focus::GrowthModel => Focus::OffLattice,
+            Focus::OffLattice => Focus::RadialBias,
             Focus::RadialBias => Focus::WalkStep,
             // Sticking: contacts, gradient, neighbors, sticky, side stick, tip stick
             Focus::WalkStep => Focus::MultiContact,
@@ -181,15 +229,16 @@ impl Focus {
             Focus::Neighborhood => Focus::Stickiness,
             Focus::Stickiness => Focus::SideSticky,
             Focus::SideSticky => Focus::TipSticky,
-            // Spawn: bound, escape, max steps, min radius, spawn, spawn off
+            // Spawn: bound, escape, max steps, min radius, spawn, spawn off, walls
             Focus::TipSticky => Focus::Boundary,
             Focus::Boundary => Focus::EscapeMult,
             Focus::EscapeMult => Focus::MaxIterations,
             Focus::MaxIterations => Focus::MinRadius,
             Focus::MinRadius => Focus::Spawn,
             Focus::Spawn => Focus::SpawnOffset,
+            Focus::SpawnOffset => Focus::StickyEdges,
             // Visual: age, color, highlight, invert, mode, particles, seed, speed
-            Focus::SpawnOffset => Focus::Age,
+            Focus::StickyEdges => Focus::Age,
             Focus::Age => Focus::ColorScheme,
             Focus::ColorScheme => Focus::Highlight,
             Focus::Highlight => Focus::Invert,
@@ -205,13 +254,18 @@ impl Focus {
     pub fn prev(&self) -> Focus {
         match self {
             Focus::None | Focus::Controls => Focus::Speed,
-            // Movement: adaptive, adapt factor, direction, force, lattice, radial, walk
+            // Movement: adaptive, adapt factor, direction, eta, force, jumps, lattice, mobility, model, off-lattice, radial, walk
             Focus::AdaptiveStep => Focus::AdaptiveStep, // Stop at boundary
             Focus::AdaptiveFactor => Focus::AdaptiveStep,
             Focus::Direction => Focus::AdaptiveFactor,
-            Focus::Force => Focus::Direction,
-            Focus::LatticeWalk => Focus::Force,
-            Focus::RadialBias => Focus::LatticeWalk,
+            Focus::DbmEta => Focus::Direction,
+            Focus::Force => Focus::DbmEta,
+            Focus::Jumps => Focus::Force,
+            Focus::LatticeWalk => Focus::Jumps,
+            Focus::Mobility => Focus::LatticeWalk,
+            Focus::GrowthModel => Focus::Mobility,
+            Focus::OffLattice => Focus::GrowthModel,
+            Focus::RadialBias => Focus::OffLattice,
             Focus::WalkStep => Focus::RadialBias,
             // Sticking: contacts, gradient, neighbors, sticky, side stick, tip stick
             Focus::MultiContact => Focus::WalkStep,
@@ -220,15 +274,16 @@ impl Focus {
             Focus::Stickiness => Focus::Neighborhood,
             Focus::SideSticky => Focus::Stickiness,
             Focus::TipSticky => Focus::SideSticky,
-            // Spawn: bound, escape, max steps, min radius, spawn, spawn off
+            // Spawn: bound, escape, max steps, min radius, spawn, spawn off, walls
             Focus::Boundary => Focus::TipSticky,
             Focus::EscapeMult => Focus::Boundary,
             Focus::MaxIterations => Focus::EscapeMult,
             Focus::MinRadius => Focus::MaxIterations,
             Focus::Spawn => Focus::MinRadius,
             Focus::SpawnOffset => Focus::Spawn,
+            Focus::StickyEdges => Focus::SpawnOffset,
             // Visual: age, color, highlight, invert, mode, particles, seed, speed
-            Focus::Age => Focus::SpawnOffset,
+            Focus::Age => Focus::StickyEdges,
             Focus::ColorScheme => Focus::Age,
             Focus::Highlight => Focus::ColorScheme,
             Focus::Invert => Focus::Highlight,
@@ -243,46 +298,52 @@ impl Focus {
     pub fn line_index(&self) -> u16 {
         // Line indices account for section headers:
         // 0: -- movement --
-        // 1-7: adaptive, adapt factor, direction, force, lattice, radial, walk
-        // 8: -- sticking --
-        // 9-14: contacts, gradient, neighbors, sticky, side stick, tip stick
-        // 15: -- spawn --
-        // 16-21: bound, escape, max steps, min radius, spawn, spawn off
-        // 22: -- visual --
-        // 23-30: age, color, highlight, invert, mode, particles, seed, speed
+        // 1-12: adaptive, adapt factor, direction, eta, force, jumps, lattice, mobility, model, off-lattice, radial, walk
+        // 13: -- sticking --
+        // 14-19: contacts, gradient, neighbors, sticky, side stick, tip stick
+        // 20: -- spawn --
+        // 21-27: bound, escape, max steps, min radius, spawn, spawn off, walls
+        // 28: -- visual --
+        // 29-36: age, color, highlight, invert, mode, particles, seed, speed
         match self {
             Focus::None | Focus::Controls => 0,
             // Movement (after header at line 0)
             Focus::AdaptiveStep => 1,
             Focus::AdaptiveFactor => 2,
             Focus::Direction => 3,
-            Focus::Force => 4,
-            Focus::LatticeWalk => 5,
-            Focus::RadialBias => 6,
-            Focus::WalkStep => 7,
-            // Sticking (after header at line 8)
-            Focus::MultiContact => 9,
-            Focus::StickyGradient => 10,
-            Focus::Neighborhood => 11,
-            Focus::Stickiness => 12,
-            Focus::SideSticky => 13,
-            Focus::TipSticky => 14,
-            // Spawn (after header at line 15)
-            Focus::Boundary => 16,
-            Focus::EscapeMult => 17,
-            Focus::MaxIterations => 18,
-            Focus::MinRadius => 19,
-            Focus::Spawn => 20,
-            Focus::SpawnOffset => 21,
-            // Visual (after header at line 22)
-            Focus::Age => 23,
-            Focus::ColorScheme => 24,
-            Focus::Highlight => 25,
-            Focus::Invert => 26,
-            Focus::Mode => 27,
-            Focus::Particles => 28,
-            Focus::Seed => 29,
-            Focus::Speed => 30,
+            Focus::DbmEta => 4,
+            Focus::Force => 5,
+            Focus::Jumps => 6,
+            Focus::LatticeWalk => 7,
+            Focus::Mobility => 8,
+            Focus::GrowthModel => 9,
+            Focus::OffLattice => 10,
+            Focus::RadialBias => 11,
+            Focus::WalkStep => 12,
+            // Sticking (after header at line 13)
+            Focus::MultiContact => 14,
+            Focus::StickyGradient => 15,
+            Focus::Neighborhood => 16,
+            Focus::Stickiness => 17,
+            Focus::SideSticky => 18,
+            Focus::TipSticky => 19,
+            // Spawn (after header at line 20)
+            Focus::Boundary => 21,
+            Focus::EscapeMult => 22,
+            Focus::MaxIterations => 23,
+            Focus::MinRadius => 24,
+            Focus::Spawn => 25,
+            Focus::SpawnOffset => 26,
+            Focus::StickyEdges => 27,
+            // Visual (after header at line 28)
+            Focus::Age => 29,
+            Focus::ColorScheme => 30,
+            Focus::Highlight => 31,
+            Focus::Invert => 32,
+            Focus::Mode => 33,
+            Focus::Particles => 34,
+            Focus::Seed => 35,
+            Focus::Speed => 36,
         }
     }
 
@@ -298,15 +359,32 @@ pub struct App {
     pub color_scheme: ColorScheme,
     pub color_lut: ColorLut,
     pub color_by_age: bool,
+    /// Shade the canvas with the dielectric breakdown potential
+    pub show_potential: bool,
     pub focus: Focus,
     pub view_mode: ViewMode,
     pub steps_per_frame: usize,
+    /// Worker threads for particle walking (1 = serial)
+    pub threads: usize,
+    /// Fractal dimension estimates sampled as the cluster grows (States view chart)
+    pub analysis: AnalysisTracker,
     pub show_help: bool,
     pub help_scroll: u16,
     pub controls_scroll: u16,
     pub param_popup: Option<ParamPopup>,
     pub export_popup: Option<TextInputPopup>,
     pub export_result: Option<Result<String, String>>,
+    // Snapshot state
+    pub snapshot_popup: Option<TextInputPopup>,
+    pub snapshot_result: Option<Result<String, String>>,
+    /// Image pixels per simulation cell for PNG snapshots
+    pub snapshot_scale: u32,
+    /// Copies per side in PNG snapshots (tileable with the Wrap boundary)
+    pub snapshot_tiles: u32,
+    // Simulation state save/load
+    pub state_popup: Option<TextInputPopup>,
+    pub state_action: StateAction,
+    pub state_result: Option<Result<String, String>>,
     // Recording state
     pub recorder: Recorder,
     pub recording_popup: Option<TextInputPopup>,
@@ -321,6 +399,20 @@ pub struct App {
     pub preset_popup: Option<PresetPopup>,
     pub preset_save_popup: Option<TextInputPopup>,
     pub preset_result: Option<Result<String, String>>,
+    // Paint mode state
+    /// Mouse drags on the canvas paint with paint_tool (mouse capture is on)
+    pub paint_mode: bool,
+    pub paint_tool: PaintTool,
+    /// Brush radius in grid cells
+    pub brush_size: usize,
+    /// Grid position of the last paint event in the current drag
+    last_paint: Option<(f32, f32)>,
+    /// Saved custom seeds, cycled after the built-in seed patterns
+    pub custom_seeds: Vec<CustomSeed>,
+    pub seed_save_popup: Option<TextInputPopup>,
+    pub paint_result: Option<Result<String, String>>,
+    /// Growth history being scrubbed (the run is paused while it is open)
+    pub timeline: Option<Timeline>,
 }
 
 impl App {
@@ -334,15 +426,25 @@ impl App {
             color_lut: color_scheme.build_lut(),
             color_scheme,
             color_by_age: true,
+            show_potential: false,
             focus: Focus::AdaptiveStep,
             view_mode: ViewMode::Default,
             steps_per_frame: 15,
+            threads: 1,
+            analysis: AnalysisTracker::new(),
             show_help: false,
             help_scroll: 0,
             controls_scroll: 0,
             param_popup: None,
             export_popup: None,
             export_result: None,
+            snapshot_popup: None,
+            snapshot_result: None,
+            snapshot_scale: snapshot::DEFAULT_SNAPSHOT_SCALE,
+            snapshot_tiles: 1,
+            state_popup: None,
+            state_action: StateAction::Save,
+            state_result: None,
             recorder: Recorder::new(),
             recording_popup: None,
             recording_result: None,
@@ -353,12 +455,35 @@ impl App {
             preset_popup: None,
             preset_save_popup: None,
             preset_result: None,
+            paint_mode: false,
+            paint_tool: PaintTool::default(),
+            brush_size: 2,
+            last_paint: None,
+            custom_seeds: CustomSeed::load_all(),
+            seed_save_popup: None,
+            paint_result: None,
+            timeline: None,
         }
     }
 
     /// Run simulation steps for current frame
     pub fn tick(&mut self) {
-        if !self.simulation.paused {
+        self.analysis.update(&self.simulation);
+        self.sync_timeline();
+        if self.simulation.paused {
+            return;
+        }
+        if self.threads > 1 {
+            // Parallel batches are a fixed size, so run whole batches until the frame's budget is met
+            let mut walked = 0;
+            while walked < self.steps_per_frame {
+                let n = self.simulation.step_parallel(self.threads);
+                if n == 0 {
+                    break;
+                }
+                walked += n;
+            }
+        } else {
             for _ in 0..self.steps_per_frame {
                 if !self.simulation.step() {
                     break;
@@ -373,24 +498,27 @@ impl App {
         let dir_f32 = direction as f32;
         match self.focus {
             Focus::None | Focus::Controls => {}
-            Focus::Age | Focus::Invert | Focus::AdaptiveStep | Focus::LatticeWalk => {
+            Focus::Age
+            | Focus::Invert
+            | Focus::AdaptiveStep
+            | Focus::Jumps
+            | Focus::LatticeWalk
+            | Focus::OffLattice => {
                 // Toggle parameters - same action regardless of direction
                 match self.focus {
                     Focus::Age => self.toggle_color_by_age(),
                     Focus::Invert => self.toggle_invert_colors(),
                     Focus::AdaptiveStep => self.simulation.settings.toggle_adaptive_step(),
+                    Focus::Jumps => self.simulation.settings.toggle_distance_jumps(),
                     Focus::LatticeWalk => self.simulation.settings.toggle_lattice_walk(),
+                    Focus::OffLattice => self.simulation.settings.toggle_off_lattice(),
                     _ => {}
                 }
             }
             Focus::Stickiness => self.simulation.adjust_stickiness(dir_f32 * STICKINESS_INCREMENT),
             Focus::Particles => self.simulation.adjust_particles(direction * PARTICLE_INCREMENT),
             Focus::Seed => {
-                let new_pattern = if direction > 0 {
-                    self.simulation.seed_pattern.next()
-                } else {
-                    self.simulation.seed_pattern.prev()
-                };
+                let new_pattern = self.step_seed_pattern(direction);
                 self.simulation.reset_with_seed(new_pattern);
             }
             Focus::ColorScheme => {
@@ -421,8 +549,17 @@ impl App {
             Focus::AdaptiveFactor => self.simulation.settings.adjust_adaptive_step_factor(dir_f32 * ADAPTIVE_FACTOR_INCREMENT),
             Focus::WalkStep => self.adjust_walk_step(dir_f32 * WALK_STEP_INCREMENT),
             Focus::Direction => self.simulation.settings.adjust_walk_bias_angle(dir_f32 * DIRECTION_INCREMENT),
+            Focus::DbmEta => self.simulation.settings.adjust_dbm_eta(dir_f32 * DBM_ETA_INCREMENT),
+            Focus::Mobility => self.simulation.settings.adjust_mobility_exponent(dir_f32 * MOBILITY_INCREMENT),
             Focus::Force => self.simulation.settings.adjust_walk_bias_strength(dir_f32 * FORCE_INCREMENT),
             Focus::RadialBias => self.simulation.settings.adjust_radial_bias(dir_f32 * RADIAL_BIAS_INCREMENT),
+            Focus::GrowthModel => {
+                if direction > 0 {
+                    self.cycle_growth_model();
+                } else {
+                    self.cycle_growth_model_prev();
+                }
+            }
             // Sticking
             Focus::Neighborhood => {
                 if direction > 0 {
@@ -450,6 +587,13 @@ impl App {
                     self.cycle_boundary_prev();
                 }
             }
+            Focus::StickyEdges => {
+                if direction > 0 {
+                    self.cycle_sticky_edges();
+                } else {
+                    self.cycle_sticky_edges_prev();
+                }
+            }
             Focus::SpawnOffset => self.simulation.settings.adjust_spawn_radius_offset(dir_f32 * SPAWN_OFFSET_INCREMENT),
             Focus::EscapeMult => self.simulation.settings.adjust_escape_multiplier(dir_f32 * ESCAPE_MULT_INCREMENT),
             Focus::MinRadius => self.simulation.settings.adjust_min_spawn_radius(dir_f32 * MIN_RADIUS_INCREMENT),
@@ -492,15 +636,47 @@ impl App {
         self.simulation.reset_with_seed(pattern);
     }
 
+    /// Seed pattern one step from the current one, with the saved custom
+    /// seeds between Empty and Point
+    fn step_seed_pattern(&self, direction: i32) -> SeedPattern {
+        let customs = &self.custom_seeds;
+        let custom = |i: usize| SeedPattern::Custom(customs[i].clone());
+        let position = match &self.simulation.seed_pattern {
+            SeedPattern::Custom(seed) => customs.iter().position(|s| s.name == seed.name),
+            _ => None,
+        };
+        if direction > 0 {
+            match (&self.simulation.seed_pattern, position) {
+                (SeedPattern::Empty, _) if !customs.is_empty() => custom(0),
+                (SeedPattern::Custom(_), Some(i)) if i + 1 < customs.len() => custom(i + 1),
+                (pattern, _) => pattern.next(),
+            }
+        } else {
+            match (&self.simulation.seed_pattern, position) {
+                (SeedPattern::Point, _) if !customs.is_empty() => custom(customs.len() - 1),
+                (SeedPattern::Custom(_), Some(i)) if i > 0 => custom(i - 1),
+                (pattern, _) => pattern.prev(),
+            }
+        }
+    }
+
     /// Toggle color-by-age mode
     pub fn toggle_color_by_age(&mut self) {
         self.color_by_age = !self.color_by_age;
     }
 
+    /// Toggle the potential heat-map overlay (shown while the DBM model runs)
+    pub fn toggle_potential_overlay(&mut self) {
+        self.show_potential = !self.show_potential;
+    }
+
     /// Cycle color scheme
     pub fn cycle_color_scheme(&mut self) {
         self.color_scheme = self.color_scheme.next();
         self.color_lut = self.color_scheme.build_lut();
+        if let Color::Rgb(r, g, b) = self.theme.highlight_color {
+            self.recorder.config.highlight_color = (r, g, b);
+        }
     }
 
     /// Cycle through view modes (Default → States → Fullscreen → Default)
@@ -579,6 +755,16 @@ impl App {
         self.simulation.settings.boundary_behavior = self.simulation.settings.boundary_behavior.next();
     }
 
+    /// Cycle through growth models
+    pub fn cycle_growth_model(&mut self) {
+        self.simulation.settings.growth_model = self.simulation.settings.growth_model.next();
+    }
+
+    /// Cycle through sticky wall selections
+    pub fn cycle_sticky_edges(&mut self) {
+        self.simulation.settings.sticky_edges = self.simulation.settings.sticky_edges.next();
+    }
+
     /// Cycle through spawn modes
     pub fn cycle_spawn_mode(&mut self) {
         self.simulation.settings.spawn_mode = self.simulation.settings.spawn_mode.next();
@@ -609,6 +795,16 @@ impl App {
         self.simulation.settings.boundary_behavior = self.simulation.settings.boundary_behavior.prev();
     }
 
+    /// Cycle growth model backward
+    pub fn cycle_growth_model_prev(&mut self) {
+        self.simulation.settings.growth_model = self.simulation.settings.growth_model.prev();
+    }
+
+    /// Cycle sticky walls backward
+    pub fn cycle_sticky_edges_prev(&mut self) {
+        self.simulation.settings.sticky_edges = self.simulation.settings.sticky_edges.prev();
+    }
+
     /// Cycle spawn mode backward
     pub fn cycle_spawn_mode_prev(&mut self) {
         self.simulation.settings.spawn_mode = self.simulation.settings.spawn_mode.prev();
@@ -627,16 +823,21 @@ impl App {
             ('c', Focus::ColorScheme, "Color Scheme"),
             ('d', Focus::Direction, "Direction"),
             ('e', Focus::EscapeMult, "Escape Multiplier"),
+            ('e', Focus::DbmEta, "Eta (DBM)"),
             ('f', Focus::Force, "Force (Bias Strength)"),
             ('g', Focus::StickyGradient, "Gradient (Stickiness)"),
             ('h', Focus::Highlight, "Highlight"),
             ('i', Focus::Invert, "Invert"),
+            ('j', Focus::Jumps, "Jumps (Distance Field)"),
             ('l', Focus::LatticeWalk, "Lattice Walk"),
             ('m', Focus::Mode, "Mode (Color)"),
+            ('m', Focus::GrowthModel, "Model (Growth)"),
             ('m', Focus::MultiContact, "Multi-Contact Min"),
             ('m', Focus::MinRadius, "Min Spawn Radius"),
             ('m', Focus::MaxIterations, "Max Steps"),
+            ('m', Focus::Mobility, "Mobility (DLCA)"),
             ('n', Focus::Neighborhood, "Neighborhood"),
+            ('o', Focus::OffLattice, "Off-Lattice (Discs)"),
             ('o', Focus::SpawnOffset, "Offset (Spawn)"),
             ('p', Focus::Particles, "Particles"),
             ('r', Focus::RadialBias, "Radial Bias"),
@@ -647,6 +848,7 @@ impl App {
             ('s', Focus::Spawn, "Spawn Mode"),
             ('t', Focus::TipSticky, "Tip Stickiness"),
             ('w', Focus::WalkStep, "Walk Step"),
+            ('w', Focus::StickyEdges, "Walls (Sticky Edges)"),
         ];
 
         all_params
@@ -677,16 +879,21 @@ impl App {
             (Focus::ColorScheme, "Color Scheme"),
             (Focus::Direction, "Direction"),
             (Focus::EscapeMult, "Escape Multiplier"),
+            (Focus::DbmEta, "Eta (DBM)"),
             (Focus::Force, "Force (Bias Strength)"),
             (Focus::StickyGradient, "Gradient (Stickiness)"),
             (Focus::Highlight, "Highlight"),
             (Focus::Invert, "Invert"),
+            (Focus::Jumps, "Jumps (Distance Field)"),
             (Focus::LatticeWalk, "Lattice Walk"),
             (Focus::MaxIterations, "Max Steps"),
             (Focus::MinRadius, "Min Spawn Radius"),
+            (Focus::Mobility, "Mobility (DLCA)"),
             (Focus::Mode, "Mode (Color)"),
+            (Focus::GrowthModel, "Model (Growth)"),
             (Focus::MultiContact, "Multi-Contact Min"),
             (Focus::Neighborhood, "Neighborhood"),
+            (Focus::OffLattice, "Off-Lattice (Discs)"),
             (Focus::SpawnOffset, "Offset (Spawn)"),
             (Focus::Particles, "Particles"),
             (Focus::RadialBias, "Radial Bias"),
@@ -697,6 +904,7 @@ impl App {
             (Focus::Stickiness, "Stickiness"),
             (Focus::TipSticky, "Tip Stickiness"),
             (Focus::WalkStep, "Walk Step"),
+            (Focus::StickyEdges, "Walls (Sticky Edges)"),
         ]
     }
 
@@ -779,27 +987,118 @@ impl App {
         self.export_result = None;
     }
 
+    // === Snapshot popup methods ===
+
+    /// Open PNG snapshot popup with default filename
+    pub fn open_snapshot_popup(&mut self) {
+        self.snapshot_popup = Some(TextInputPopup::new(" Save PNG Snapshot ", "dla-snapshot.png"));
+    }
+
+    /// Close snapshot popup without saving
+    pub fn close_snapshot_popup(&mut self) {
+        self.snapshot_popup = None;
+    }
+
+    /// Adjust snapshot pixel scale
+    pub fn adjust_snapshot_scale(&mut self, delta: i32) {
+        self.snapshot_scale = (self.snapshot_scale as i32 + delta)
+            .clamp(1, snapshot::MAX_SNAPSHOT_SCALE as i32) as u32;
+    }
+
+    /// Confirm snapshot and write the PNG
+    pub fn confirm_snapshot(&mut self) {
+        if let Some(popup) = self.snapshot_popup.take() {
+            let result = snapshot::save_snapshot(
+                Path::new(&popup.input),
+                &self.simulation,
+                &self.color_scheme,
+                self.color_by_age,
+                self.theme.background,
+                self.snapshot_scale,
+                self.snapshot_tiles,
+            );
+            self.snapshot_result = Some(
+                result.map(|(w, h)| format!("{} ({}x{})", popup.input, w, h)),
+            );
+        }
+    }
+
+    /// Clear snapshot result (call after displaying it)
+    pub fn clear_snapshot_result(&mut self) {
+        self.snapshot_result = None;
+    }
+
+    // === State save/load popup methods ===
+
+    /// Open popup to save the full simulation state
+    pub fn open_state_save_popup(&mut self) {
+        self.state_action = StateAction::Save;
+        self.state_popup = Some(TextInputPopup::new(" Save State ", DEFAULT_STATE_FILE));
+    }
+
+    /// Open popup to load a saved simulation state
+    pub fn open_state_load_popup(&mut self) {
+        self.state_action = StateAction::Load;
+        self.state_popup = Some(TextInputPopup::new(" Load State ", DEFAULT_STATE_FILE));
+    }
+
+    /// Close state popup without saving or loading
+    pub fn close_state_popup(&mut self) {
+        self.state_popup = None;
+    }
+
+    /// Confirm the state popup, saving or loading depending on the action
+    pub fn confirm_state_popup(&mut self) {
+        if let Some(popup) = self.state_popup.take() {
+            let path = Path::new(&popup.input);
+            let result = match self.state_action {
+                StateAction::Save => self.save_state(path).map(|_| format!("Saved: {}", popup.input)),
+                StateAction::Load => self.load_state(path).map(|_| format!("Loaded: {}", popup.input)),
+            };
+            self.state_result = Some(result);
+        }
+    }
+
+    /// Clear state result (call after displaying it)
+    pub fn clear_state_result(&mut self) {
+        self.state_result = None;
+    }
+
+    /// Save the full simulation state (grid, particle data, RNG position) to a file
+    pub fn save_state(&self, path: &Path) -> Result<(), String> {
+        self.simulation.to_state().save_to_file(path)
+    }
+
+    /// Replace the running simulation with one loaded from a state file
+    pub fn load_state(&mut self, path: &Path) -> Result<(), String> {
+        let state = SimulationState::load_from_file(path)?;
+        self.simulation = DlaSimulation::from_state(state);
+        Ok(())
+    }
+
     /// Create AppConfig from current state
     pub fn to_config(&self) -> AppConfig {
         AppConfig {
             version: 1,
             settings: self.simulation.settings.clone(),
-            seed_pattern: self.simulation.seed_pattern,
+            seed_pattern: self.simulation.seed_pattern.clone(),
             stickiness: self.simulation.stickiness,
             num_particles: self.simulation.num_particles,
             color_scheme: self.color_scheme,
             steps_per_frame: self.steps_per_frame,
             color_by_age: self.color_by_age,
             theme: self.theme_id,
+            rng_seed: self.simulation.rng_seed,
         }
     }
 
     /// Apply AppConfig to current state
     pub fn apply_config(&mut self, config: &AppConfig) {
         self.simulation.settings = config.settings.clone();
-        self.simulation.seed_pattern = config.seed_pattern;
+        self.simulation.seed_pattern = config.seed_pattern.clone();
         self.simulation.stickiness = config.stickiness;
         self.simulation.num_particles = config.num_particles;
+        self.simulation.rng_seed = config.rng_seed;
         self.steps_per_frame = config.steps_per_frame;
         self.color_by_age = config.color_by_age;
         // Apply theme (which sets color_scheme and rebuilds LUT)
@@ -967,7 +1266,8 @@ impl App {
                     self.simulation.settings = preset.settings.clone();
                     self.simulation.stickiness = preset.base_stickiness;
                     self.simulation.num_particles = preset.num_particles;
-                    self.simulation.reset_with_seed(preset.seed_pattern);
+                    self.simulation.rng_seed = preset.rng_seed;
+                    self.simulation.reset_with_seed(preset.seed_pattern.clone());
                     self.preset_result = Some(Ok(format!("Loaded: {}", name)));
                 }
             }
@@ -977,14 +1277,15 @@ impl App {
 
     /// Save current settings as a preset
     pub fn save_preset(&mut self, name: String) {
-        let preset = Preset::new(
+        let mut preset = Preset::new(
             name.clone(),
             "User preset",
             self.simulation.settings.clone(),
-            self.simulation.seed_pattern,
+            self.simulation.seed_pattern.clone(),
             self.simulation.stickiness,
             self.simulation.num_particles,
         );
+        preset.rng_seed = self.simulation.rng_seed;
         match self.preset_manager.save_preset(preset) {
             Ok(()) => self.preset_result = Some(Ok(format!("Saved: {}", name))),
             Err(e) => self.preset_result = Some(Err(e)),
@@ -995,4 +1296,150 @@ impl App {
     pub fn clear_preset_result(&mut self) {
         self.preset_result = None;
     }
+
+    // === Paint mode methods ===
+
+    /// Toggle paint mode (the caller switches mouse capture to match)
+    pub fn toggle_paint_mode(&mut self) {
+        self.paint_mode = !self.paint_mode;
+        self.end_paint_stroke();
+    }
+
+    /// Cycle the paint tool (seed → erase → wall)
+    pub fn cycle_paint_tool(&mut self) {
+        self.paint_tool = self.paint_tool.next();
+    }
+
+    /// Adjust the brush radius
+    pub fn adjust_brush_size(&mut self, delta: i32) {
+        self.brush_size = (self.brush_size as i32 + delta).clamp(MIN_BRUSH_SIZE as i32, MAX_BRUSH_SIZE as i32) as usize;
+    }
+
+    /// Paint at a canvas character (relative to the canvas's inner area).
+    /// Within a drag, the brush is stamped along the line from the previous
+    /// position so fast strokes don't leave gaps.
+    pub fn paint_at(&mut self, column: u16, row: u16, canvas_width: u16, canvas_height: u16, tool: PaintTool) {
+        let (x, y) = braille::canvas_to_grid(&self.simulation, canvas_width, canvas_height, column, row);
+        let from = self.last_paint.unwrap_or((x, y));
+        self.simulation.paint_line(from, (x, y), self.brush_size as f32, tool);
+        self.last_paint = Some((x, y));
+    }
+
+    /// End the current drag
+    pub fn end_paint_stroke(&mut self) {
+        self.last_paint = None;
+        self.simulation.end_paint_stroke();
+    }
+
+    /// Open the popup that names a new custom seed
+    pub fn open_seed_save_popup(&mut self) {
+        self.seed_save_popup = Some(TextInputPopup::new(" Save Custom Seed ", "my-seed"));
+    }
+
+    /// Close the custom seed popup
+    pub fn close_seed_save_popup(&mut self) {
+        self.seed_save_popup = None;
+    }
+
+    /// Confirm the custom seed popup
+    pub fn confirm_seed_save(&mut self) {
+        if let Some(popup) = self.seed_save_popup.take() {
+            if !popup.input.trim().is_empty() {
+                self.save_custom_seed(popup.input.trim().to_string());
+            }
+        }
+    }
+
+    /// Save the painted seed particles and walls as a custom seed, and make
+    /// it the current seed pattern so resets start from the drawing
+    pub fn save_custom_seed(&mut self, name: String) {
+        let seed = CustomSeed::from_simulation(name.clone(), &self.simulation);
+        match seed.save() {
+            Ok(_) => {
+                self.custom_seeds.retain(|s| !s.name.eq_ignore_ascii_case(&name));
+                self.custom_seeds.push(seed.clone());
+                self.custom_seeds.sort_by_key(|s| s.name.to_lowercase());
+                self.simulation.seed_pattern = SeedPattern::Custom(seed);
+                self.paint_result = Some(Ok(format!("Saved seed: {}", name)));
+            }
+            Err(e) => self.paint_result = Some(Err(e)),
+        }
+    }
+
+    /// Clear paint result (call after displaying it)
+    pub fn clear_paint_result(&mut self) {
+        self.paint_result = None;
+    }
+
+    // === Timeline methods ===
+
+    /// Open the growth timeline at the newest particle, pausing the run,
+    /// or close it and restore the pause state
+    pub fn toggle_timeline(&mut self) {
+        match self.timeline.take() {
+            Some(timeline) => self.simulation.paused = timeline.was_paused,
+            None => {
+                let end = self.simulation.latest_age();
+                self.timeline = Some(Timeline {
+                    cursor: end,
+                    end,
+                    stuck: self.simulation.particles_stuck,
+                    was_paused: self.simulation.paused,
+                });
+                self.simulation.paused = true;
+            }
+        }
+    }
+
+    /// Newest age drawn on the canvas (usize::MAX outside the timeline)
+    pub fn visible_age(&self) -> usize {
+        self.timeline.map_or(usize::MAX, |t| t.cursor)
+    }
+
+    /// Move the timeline cursor by `delta` particles
+    pub fn scrub_timeline(&mut self, delta: i64) {
+        if let Some(timeline) = &mut self.timeline {
+            let cursor = (timeline.cursor as i64 + delta).clamp(0, timeline.end as i64);
+            timeline.cursor = cursor as usize;
+        }
+    }
+
+    /// Move the timeline cursor by a hundredth of the run in `direction`
+    pub fn scrub_timeline_coarse(&mut self, direction: i64) {
+        if let Some(timeline) = &self.timeline {
+            let step = (timeline.end / TIMELINE_COARSE_STEPS).max(1) as i64;
+            self.scrub_timeline(direction * step);
+        }
+    }
+
+    /// Jump the timeline cursor to the start (seeds only) or the newest particle
+    pub fn jump_timeline(&mut self, to_end: bool) {
+        if let Some(timeline) = &mut self.timeline {
+            timeline.cursor = if to_end { timeline.end } else { 0 };
+        }
+    }
+
+    /// Branch from the timeline cursor: drop every later particle and resume
+    /// growth from there with the current parameters
+    pub fn branch_timeline(&mut self) {
+        if let Some(timeline) = self.timeline.take() {
+            self.simulation.truncate_to_age(timeline.cursor);
+            self.simulation.paused = false;
+        }
+    }
+
+    /// Keep the timeline in step with the run: resuming closes it, and a
+    /// reset, load or paint stroke moves its end
+    fn sync_timeline(&mut self) {
+        let Some(timeline) = &mut self.timeline else {
+            return;
+        };
+        if !self.simulation.paused {
+            self.timeline = None;
+        } else if timeline.stuck != self.simulation.particles_stuck {
+            timeline.end = self.simulation.latest_age();
+            timeline.cursor = timeline.cursor.min(timeline.end);
+            timeline.stuck = self.simulation.particles_stuck;
+        }
+    }
 }
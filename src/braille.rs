@@ -1,6 +1,8 @@
-use crate::color::{map_from_lut, ColorLut};
-use crate::settings::ColorMode;
-use crate::simulation::DlaSimulation;
+use dla_sim_tui::color::{map_from_lut, ColorLut, ColorScheme};
+use dla_sim_tui::mask::MaskCell;
+use dla_sim_tui::settings::ColorMode;
+use dla_sim_tui::tree::GrowthTree;
+use dla_sim_tui::simulation::DlaSimulation;
 use ratatui::style::Color;
 
 /// Braille character rendering for high-resolution terminal graphics.
@@ -23,6 +25,11 @@ const BRAILLE_DOTS: [[u8; 4]; 2] = [
     [0x08, 0x10, 0x20, 0x80], // Right column (x=1): rows 0,1,2,3
 ];
 
+/// Gradient for the potential heat map
+const POTENTIAL_SCHEME: ColorScheme = ColorScheme::Fire;
+/// Brightness of the heat map, kept low so particles stay readable on top
+const POTENTIAL_DIM: f32 = 0.45;
+
 /// A single rendered Braille cell with position and color
 #[derive(Clone, Copy)]
 pub struct BrailleCell {
@@ -32,10 +39,12 @@ pub struct BrailleCell {
     pub color: Color,
 }
 
-/// Render the simulation grid to Braille characters (uses LUT for fast color lookup)
+/// Render the simulation grid to Braille characters (uses LUT for fast color lookup).
+/// Only particles up to `max_age` are drawn (usize::MAX for all of them).
 #[allow(clippy::too_many_arguments)]
 pub fn render_to_braille(
     simulation: &DlaSimulation,
+    max_age: usize,
     canvas_width: u16,
     canvas_height: u16,
     color_lut: &ColorLut,
@@ -61,7 +70,28 @@ pub fn render_to_braille(
     // Pre-calculate for color mapping
     let inv_num_particles = 1.0 / simulation.num_particles.max(1) as f32;
     let max_radius = simulation.max_radius.max(1.0);
-    let particles_stuck = simulation.particles_stuck;
+    // Recent means recent at the age being shown
+    let particles_stuck = simulation.particles_stuck.min(max_age.saturating_add(1));
+    let species_count = simulation.settings.species_count();
+    let tree = color_mode.uses_tree().then(|| GrowthTree::new(simulation.discs()));
+
+    // Off-lattice discs don't line up with cells, so rasterize them onto the
+    // dot grid (later discs on top, as in recordings)
+    let disc_dots = simulation.settings.off_lattice.then(|| {
+        let mut dots = vec![None; braille_width * braille_height];
+        simulation.for_each_disc_pixel(
+            1.0 / scale_x,
+            1.0 / scale_y,
+            braille_width as u32,
+            braille_height as u32,
+            |bx, by, i, particle| {
+                if particle.age <= max_age {
+                    dots[by as usize * braille_width + bx as usize] = Some(i);
+                }
+            },
+        );
+        dots
+    });
 
     let mut cells = Vec::with_capacity((canvas_width * canvas_height) as usize);
 
@@ -84,7 +114,19 @@ pub fn render_to_braille(
                     let sim_x = (braille_x as f32 * scale_x) as usize;
                     let sim_y = (braille_y as f32 * scale_y) as usize;
 
-                    if let Some(particle) = simulation.get_particle(sim_x, sim_y) {
+                    let (particle, disc) = match &disc_dots {
+                        Some(dots) => {
+                            let disc = dots.get(braille_y * braille_width + braille_x).copied().flatten();
+                            (disc.map(|i| simulation.discs()[i].data), disc)
+                        }
+                        // The cell's copy is its oldest particle, so nothing in a cell
+                        // it hides is old enough either
+                        None => (
+                            simulation.get_particle(sim_x, sim_y).filter(|p| p.age <= max_age),
+                            None,
+                        ),
+                    };
+                    if let Some(particle) = particle {
                         pattern |= dot_pattern;
                         dot_count += 1;
 
@@ -102,6 +144,16 @@ pub fn render_to_braille(
                                 // Map angle (-PI to PI) to 0-1
                                 (particle.direction + std::f32::consts::PI) / std::f32::consts::TAU
                             }
+                            ColorMode::Origin => {
+                                let age = particle.age as f32 * inv_num_particles * 0.5;
+                                if particle.deposit { 0.5 + age } else { age }
+                            }
+                            ColorMode::Cluster => particle.cluster_shade(),
+                            ColorMode::Species => particle.species_shade(species_count),
+                            ColorMode::TreeDepth | ColorMode::Strahler | ColorMode::Descendants => tree
+                                .as_ref()
+                                .zip(disc.or_else(|| simulation.disc_at(sim_x, sim_y)))
+                                .map_or(0.0, |(tree, i)| tree.shade(i, color_mode)),
                         };
                         total_value += value;
                     }
@@ -138,6 +190,112 @@ pub fn render_to_braille(
     cells
 }
 
+/// Background color of every canvas cell for the potential heat map: the mean
+/// potential of the grid cells under each Braille character
+pub fn render_potential(
+    simulation: &DlaSimulation,
+    potential: &[f32],
+    canvas_width: u16,
+    canvas_height: u16,
+) -> Vec<(u16, u16, Color)> {
+    let sim_width = simulation.grid_width;
+    let sim_height = simulation.grid_height;
+    let braille_width = (canvas_width as usize * 2).max(64);
+    let braille_height = (canvas_height as usize * 4).max(64);
+    let scale_x = sim_width as f32 / braille_width as f32;
+    let scale_y = sim_height as f32 / braille_height as f32;
+
+    let mut cells = Vec::with_capacity((canvas_width * canvas_height) as usize);
+    for cy in 0..canvas_height {
+        for cx in 0..canvas_width {
+            let mut total = 0.0;
+            let mut samples = 0;
+            for dx in 0..2 {
+                for dy in 0..4 {
+                    let sim_x = ((cx as usize * 2 + dx) as f32 * scale_x) as usize;
+                    let sim_y = ((cy as usize * 4 + dy) as f32 * scale_y) as usize;
+                    if sim_x < sim_width && sim_y < sim_height {
+                        total += potential[sim_y * sim_width + sim_x];
+                        samples += 1;
+                    }
+                }
+            }
+            if samples > 0 {
+                let (r, g, b) = POTENTIAL_SCHEME.map_rgb(total / samples as f32);
+                let dim = |c: u8| (c as f32 * POTENTIAL_DIM) as u8;
+                cells.push((cx, cy, Color::Rgb(dim(r), dim(g), dim(b))));
+            }
+        }
+    }
+    cells
+}
+
+/// Background color of every canvas cell covered by the domain mask: the
+/// dimmed shade of the most common masked type under each Braille character.
+/// Open cells are left out.
+pub fn render_mask(simulation: &DlaSimulation, canvas_width: u16, canvas_height: u16) -> Vec<(u16, u16, Color)> {
+    if !simulation.has_mask() {
+        return Vec::new();
+    }
+    let sim_width = simulation.grid_width;
+    let sim_height = simulation.grid_height;
+    let braille_width = (canvas_width as usize * 2).max(64);
+    let braille_height = (canvas_height as usize * 4).max(64);
+    let scale_x = sim_width as f32 / braille_width as f32;
+    let scale_y = sim_height as f32 / braille_height as f32;
+
+    let mut cells = Vec::new();
+    for cy in 0..canvas_height {
+        for cx in 0..canvas_width {
+            // Wall, sink and no-stick counts under this character
+            let mut counts = [0u8; 3];
+            for dx in 0..2 {
+                for dy in 0..4 {
+                    let sim_x = ((cx as usize * 2 + dx) as f32 * scale_x) as usize;
+                    let sim_y = ((cy as usize * 4 + dy) as f32 * scale_y) as usize;
+                    if sim_x < sim_width && sim_y < sim_height {
+                        match simulation.mask_cell(sim_x, sim_y) {
+                            MaskCell::Wall => counts[0] += 1,
+                            MaskCell::Sink => counts[1] += 1,
+                            MaskCell::NoStick => counts[2] += 1,
+                            MaskCell::Open => {}
+                        }
+                    }
+                }
+            }
+            let mut best = MaskCell::Open;
+            let mut best_count = 0;
+            for (kind, &count) in [MaskCell::Wall, MaskCell::Sink, MaskCell::NoStick].iter().zip(&counts) {
+                if count > best_count {
+                    best = *kind;
+                    best_count = count;
+                }
+            }
+            if let Some((r, g, b)) = best.shade() {
+                cells.push((cx, cy, Color::Rgb(r, g, b)));
+            }
+        }
+    }
+    cells
+}
+
+/// Grid position under a canvas character: the inverse of the sub-cell
+/// scaling in `render_to_braille`, taken at the centre of the character's
+/// 2x4 dots (terminals report mouse positions per character)
+pub fn canvas_to_grid(
+    simulation: &DlaSimulation,
+    canvas_width: u16,
+    canvas_height: u16,
+    column: u16,
+    row: u16,
+) -> (f32, f32) {
+    let braille_width = (canvas_width as usize * 2).max(64);
+    let braille_height = (canvas_height as usize * 4).max(64);
+    let scale_x = simulation.grid_width as f32 / braille_width as f32;
+    let scale_y = simulation.grid_height as f32 / braille_height as f32;
+    ((column as f32 * 2.0 + 1.0) * scale_x, (row as f32 * 4.0 + 2.0) * scale_y)
+}
+
 /// Calculate optimal simulation grid size for a given canvas size
 /// Returns (width, height) for the simulation grid
 pub fn calculate_simulation_size(canvas_width: u16, canvas_height: u16) -> (usize, usize) {
@@ -165,6 +323,45 @@ mod tests {
         assert_eq!(all_dots, 0xFF);
     }
 
+    #[test]
+    fn test_canvas_to_grid_inverts_braille_scaling() {
+        let (w, h) = calculate_simulation_size(60, 30);
+        let sim = DlaSimulation::new(w, h);
+        // The centre of a character lands inside the cells its dots sample
+        for (column, row) in [(0, 0), (13, 7), (59, 29)] {
+            let (x, y) = canvas_to_grid(&sim, 60, 30, column, row);
+            assert_eq!(x as usize / 2, column as usize);
+            assert_eq!(y as usize / 4, row as usize);
+        }
+    }
+
+    #[test]
+    fn test_off_lattice_discs_cover_their_dots() {
+        // Two dots per cell, so discs sharing a cell land on different dots
+        let mut sim = DlaSimulation::new(32, 32);
+        sim.settings.off_lattice = true;
+        sim.rng_seed = Some(3);
+        sim.num_particles = 150;
+        sim.reset();
+        while sim.step() {}
+
+        let lut = ColorScheme::Ice.build_lut();
+        let cells = render_to_braille(
+            &sim, usize::MAX, 32, 16, &lut, false, ColorMode::Age, 0, false, 0.0, Color::White, Color::White,
+        );
+        let lit = |bx: usize, by: usize| {
+            cells.iter().any(|c| {
+                c.x as usize == bx / 2
+                    && c.y as usize == by / 4
+                    && (c.char as u32 - BRAILLE_BASE) as u8 & BRAILLE_DOTS[bx % 2][by % 4] != 0
+            })
+        };
+        for disc in sim.discs() {
+            let (bx, by) = ((disc.x * 2.0) as usize, (disc.y * 2.0) as usize);
+            assert!(lit(bx, by), "disc at ({}, {}) not drawn", disc.x, disc.y);
+        }
+    }
+
     #[test]
     fn test_braille_char_generation() {
         // Empty pattern
@@ -1,3 +1,4 @@
+#[cfg(feature = "tui")]
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
@@ -25,9 +26,11 @@ const PLASMA_PHASE_BLUE: f32 = 0.67;
 const PLASMA_MIN_RED: u8 = 50;
 
 /// Pre-computed color lookup table (256 entries for fast gradient access)
+#[cfg(feature = "tui")]
 pub type ColorLut = [Color; 256];
 
 /// Fast color lookup from pre-computed LUT (t should be 0.0-1.0)
+#[cfg(feature = "tui")]
 #[inline]
 pub fn map_from_lut(lut: &ColorLut, t: f32) -> Color {
     let idx = (t.clamp(0.0, 1.0) * 255.0) as usize;
@@ -122,6 +125,7 @@ impl ColorScheme {
     }
 
     /// Map a value from 0.0-1.0 to a terminal color
+    #[cfg(feature = "tui")]
     pub fn map(&self, t: f32) -> Color {
         let (r, g, b) = self.map_rgb(t);
         Color::Rgb(r, g, b)
@@ -152,6 +156,7 @@ impl ColorScheme {
 
     /// Build a 256-entry lookup table for fast color access
     /// Call this once when color scheme changes, then use map_from_lut() for rendering
+    #[cfg(feature = "tui")]
     pub fn build_lut(&self) -> ColorLut {
         let mut lut = [Color::White; 256];
         for (i, color) in lut.iter_mut().enumerate() {
@@ -28,6 +28,9 @@ pub struct AppConfig {
     /// Theme ID (app-level)
     #[serde(default)]
     pub theme: ThemeId,
+    /// Fixed RNG seed for reproducible runs (None = random each reset)
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
 }
 
 impl AppConfig {
@@ -43,7 +46,13 @@ impl AppConfig {
     pub fn load_from_file(path: &Path) -> Result<Self, String> {
         let content =
             fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse config file: {}", e))
+        let config: Self =
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse config file: {}", e))?;
+        config
+            .settings
+            .validate_stick_matrix()
+            .map_err(|e| format!("Invalid config file: {}", e))?;
+        Ok(config)
     }
 }
 
@@ -59,6 +68,7 @@ impl Default for AppConfig {
             steps_per_frame: 15,
             color_by_age: true,
             theme: ThemeId::default(),
+            rng_seed: None,
         }
     }
 }
@@ -66,7 +76,10 @@ impl Default for AppConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::settings::{BoundaryBehavior, ColorMode, NeighborhoodType, SpawnMode};
+    use crate::mask::{DomainMask, MaskCell};
+    use crate::settings::{
+        BoundaryBehavior, ColorMode, GrowthModelType, NeighborhoodType, SpawnMode, Species, StickyEdges,
+    };
     use tempfile::NamedTempFile;
 
     #[test]
@@ -74,6 +87,9 @@ mod tests {
         let config = AppConfig {
             version: 1,
             settings: SimulationSettings {
+                growth_model: GrowthModelType::Dla,
+                dbm_eta: 1.0,
+                mobility_exponent: 0.5,
                 walk_step_size: 3.5,
                 walk_bias_angle: 45.0,
                 walk_bias_strength: 0.25,
@@ -81,20 +97,27 @@ mod tests {
                 adaptive_step: true,
                 adaptive_step_factor: 5.0,
                 lattice_walk: false,
+                distance_jumps: false,
+                off_lattice: false,
                 neighborhood: NeighborhoodType::VonNeumann,
                 multi_contact_min: 2,
                 tip_stickiness: 0.8,
                 side_stickiness: 0.6,
                 stickiness_gradient: 0.2,
+                species: Vec::new(),
+                stick_matrix: Vec::new(),
                 spawn_mode: SpawnMode::Edges,
                 boundary_behavior: BoundaryBehavior::Wrap,
+                sticky_edges: StickyEdges::All,
                 spawn_radius_offset: 15.0,
                 escape_multiplier: 3.0,
                 min_spawn_radius: 30.0,
                 max_walk_iterations: 5000,
+                mask: None,
                 color_mode: ColorMode::Distance,
                 highlight_recent: 10,
                 invert_colors: true,
+                min_brightness: 0.3,
             },
             seed_pattern: SeedPattern::Cross,
             stickiness: 0.7,
@@ -102,7 +125,8 @@ mod tests {
             color_scheme: ColorScheme::Fire,
             steps_per_frame: 10,
             color_by_age: false,
-            theme: ThemeId::Dracula,
+            theme: ThemeId::Violet,
+            rng_seed: None,
         };
 
         // Serialize to JSON
@@ -151,6 +175,9 @@ mod tests {
         let original = AppConfig {
             version: 1,
             settings: SimulationSettings {
+                growth_model: GrowthModelType::Ballistic,
+                dbm_eta: 2.5,
+                mobility_exponent: 1.5,
                 walk_step_size: 4.0,
                 walk_bias_angle: 180.0,
                 walk_bias_strength: 0.4,
@@ -158,20 +185,40 @@ mod tests {
                 adaptive_step: true,
                 adaptive_step_factor: 8.0,
                 lattice_walk: false,
+                distance_jumps: true,
+                off_lattice: true,
                 neighborhood: NeighborhoodType::Extended,
                 multi_contact_min: 3,
                 tip_stickiness: 0.5,
                 side_stickiness: 0.9,
                 stickiness_gradient: -0.3,
+                species: vec![
+                    Species::default(),
+                    Species {
+                        share: 0.25,
+                        spawn_mode: SpawnMode::Left,
+                        bias_angle: 30.0,
+                        bias_strength: 0.2,
+                    },
+                ],
+                stick_matrix: vec![0.1, 1.0, 0.8, 0.3],
                 spawn_mode: SpawnMode::Corners,
                 boundary_behavior: BoundaryBehavior::Bounce,
+                sticky_edges: StickyEdges::All,
                 spawn_radius_offset: 25.0,
                 escape_multiplier: 4.5,
                 min_spawn_radius: 60.0,
                 max_walk_iterations: 20000,
+                mask: Some(DomainMask::from_fn(3, 2, |x, y| match (x + y) % 4 {
+                    0 => MaskCell::Open,
+                    1 => MaskCell::Wall,
+                    2 => MaskCell::Sink,
+                    _ => MaskCell::NoStick,
+                })),
                 color_mode: ColorMode::Density,
                 highlight_recent: 25,
                 invert_colors: true,
+                min_brightness: 0.45,
             },
             seed_pattern: SeedPattern::Starburst,
             stickiness: 0.5,
@@ -180,6 +227,7 @@ mod tests {
             steps_per_frame: 25,
             color_by_age: false,
             theme: ThemeId::Matrix,
+            rng_seed: Some(12345),
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -195,6 +243,8 @@ mod tests {
         assert_eq!(restored.settings.tip_stickiness, 0.5);
         assert_eq!(restored.settings.side_stickiness, 0.9);
         assert_eq!(restored.settings.stickiness_gradient, -0.3);
+        assert_eq!(restored.settings.species, original.settings.species);
+        assert_eq!(restored.settings.stick_matrix, vec![0.1, 1.0, 0.8, 0.3]);
         assert_eq!(restored.settings.spawn_mode, SpawnMode::Corners);
         assert_eq!(
             restored.settings.boundary_behavior,
@@ -204,6 +254,7 @@ mod tests {
         assert_eq!(restored.settings.escape_multiplier, 4.5);
         assert_eq!(restored.settings.min_spawn_radius, 60.0);
         assert_eq!(restored.settings.max_walk_iterations, 20000);
+        assert_eq!(restored.settings.mask, original.settings.mask);
         assert_eq!(restored.settings.color_mode, ColorMode::Density);
         assert_eq!(restored.settings.highlight_recent, 25);
         assert!(restored.settings.invert_colors);
@@ -214,6 +265,30 @@ mod tests {
         assert_eq!(restored.steps_per_frame, 25);
         assert!(!restored.color_by_age);
         assert_eq!(restored.theme, ThemeId::Matrix);
+        assert_eq!(restored.settings.min_brightness, 0.45);
+        assert_eq!(restored.rng_seed, Some(12345));
+    }
+
+    #[test]
+    fn test_config_without_rng_seed_loads() {
+        // Configs written before rng_seed existed must still parse
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        value.as_object_mut().unwrap().remove("rng_seed");
+        let parsed: AppConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.rng_seed, None);
+    }
+
+    #[test]
+    fn test_imported_seed_patterns_roundtrip() {
+        for pattern in [SeedPattern::Text("DLA".to_string()), SeedPattern::Image("logo.png".into())] {
+            let config = AppConfig {
+                seed_pattern: pattern.clone(),
+                ..Default::default()
+            };
+            let json = serde_json::to_string(&config).unwrap();
+            let parsed: AppConfig = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.seed_pattern, pattern);
+        }
     }
 
     #[test]
@@ -225,6 +300,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_stick_matrix_size_must_match_species() {
+        let mut config = AppConfig::default();
+        config.settings.species = vec![Species::default(); 2];
+        config.settings.stick_matrix = vec![0.5; 4];
+        assert!(config.settings.validate_stick_matrix().is_ok());
+
+        config.settings.stick_matrix = vec![0.5; 9];
+        let err = config.settings.validate_stick_matrix().unwrap_err();
+        assert!(err.contains("9 entries") && err.contains("2x2 = 4"), "{}", err);
+
+        let temp_file = NamedTempFile::new().unwrap();
+        config.save_to_file(temp_file.path()).unwrap();
+        let err = AppConfig::load_from_file(temp_file.path()).unwrap_err();
+        assert!(err.contains("9 entries"), "{}", err);
+    }
+
     #[test]
     fn test_missing_config_file() {
         let result = AppConfig::load_from_file(Path::new("/nonexistent/path/config.json"));
@@ -0,0 +1,146 @@
+//! Hand-drawn seeds: seed particles and walls painted on the canvas, saved
+//! under the config directory so they sit beside the built-in seed patterns.
+//!
+//! A custom seed keeps the resolution it was drawn at and is stretched over
+//! the grid (nearest cell) when a run starts from it.
+
+use crate::mask::MaskCell;
+use crate::simulation::DlaSimulation;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Characters used for the cells of a saved drawing
+const SEED_CELL: u8 = b'@';
+const WALL_CELL: u8 = b'#';
+const EMPTY_CELL: u8 = b'.';
+
+/// What a custom seed puts in a grid cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SketchCell {
+    Empty,
+    /// A seed particle
+    Seed,
+    /// A wall in the domain mask
+    Wall,
+}
+
+/// A named drawing of seed particles and walls, one string per row
+/// (`@` seed, `#` wall, `.` empty)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomSeed {
+    pub name: String,
+    rows: Vec<String>,
+}
+
+impl CustomSeed {
+    /// Capture the seed particles and walls of a simulation. Grown particles
+    /// are left out, so a drawing made over a running cluster keeps only what
+    /// was painted (and the original seed).
+    pub fn from_simulation(name: impl Into<String>, simulation: &DlaSimulation) -> Self {
+        let rows = (0..simulation.grid_height)
+            .map(|y| {
+                (0..simulation.grid_width)
+                    .map(|x| {
+                        let is_seed = simulation
+                            .get_particle(x, y)
+                            .is_some_and(|p| p.age == 0 && p.neighbor_count == 0 && !p.deposit);
+                        if is_seed {
+                            SEED_CELL as char
+                        } else if simulation.mask_cell(x, y) == MaskCell::Wall {
+                            WALL_CELL as char
+                        } else {
+                            EMPTY_CELL as char
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { name: name.into(), rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(String::len).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the drawing has at least one seed particle
+    pub fn has_seeds(&self) -> bool {
+        self.rows.iter().any(|row| row.bytes().any(|b| b == SEED_CELL))
+    }
+
+    /// Cell at (x, y) of the drawing; anything unrecognised or missing is empty
+    pub fn get(&self, x: usize, y: usize) -> SketchCell {
+        match self.rows.get(y).and_then(|row| row.as_bytes().get(x)) {
+            Some(&SEED_CELL) => SketchCell::Seed,
+            Some(&WALL_CELL) => SketchCell::Wall,
+            _ => SketchCell::Empty,
+        }
+    }
+
+    /// The drawing stretched over a grid of the given size, row-major, taking
+    /// the drawing cell under the centre of each grid cell
+    pub fn resample(&self, width: usize, height: usize) -> Vec<SketchCell> {
+        let (sw, sh) = (self.width().max(1), self.height().max(1));
+        let mut cells = Vec::with_capacity(width * height);
+        for gy in 0..height {
+            let y = ((gy as f32 + 0.5) * sh as f32 / height as f32) as usize;
+            for gx in 0..width {
+                let x = ((gx as f32 + 0.5) * sw as f32 / width as f32) as usize;
+                cells.push(self.get(x, y));
+            }
+        }
+        cells
+    }
+
+    /// Get the custom seeds directory path
+    fn seeds_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("dla-sim-tui").join("seeds"))
+    }
+
+    /// Load every saved custom seed, sorted by name
+    pub fn load_all() -> Vec<CustomSeed> {
+        let mut seeds = Vec::new();
+        if let Some(dir) = Self::seeds_dir() {
+            if let Ok(entries) = fs::read_dir(&dir) {
+                for entry in entries.flatten() {
+                    if entry.path().extension().is_some_and(|e| e == "json") {
+                        if let Ok(content) = fs::read_to_string(entry.path()) {
+                            if let Ok(seed) = serde_json::from_str::<CustomSeed>(&content) {
+                                seeds.push(seed);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        seeds.sort_by_key(|s| s.name.to_lowercase());
+        seeds
+    }
+
+    /// Find a saved custom seed by name
+    pub fn find(name: &str) -> Option<CustomSeed> {
+        Self::load_all().into_iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Save to disk, replacing any custom seed with the same file name
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let dir = Self::seeds_dir().ok_or("Could not determine config directory")?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create seeds directory: {}", e))?;
+
+        // Sanitize filename
+        let filename = self
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect::<String>();
+        let path = dir.join(format!("{}.json", filename));
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize seed: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write seed file: {}", e))?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,341 @@
+//! Headless batch mode: run a simulation to completion without a terminal
+//! and write the results to disk.
+
+use dla_sim_tui::analysis::{self, AnalysisSample};
+use dla_sim_tui::config::AppConfig;
+use dla_sim_tui::simulation::DlaSimulation;
+use dla_sim_tui::snapshot;
+use dla_sim_tui::state::SimulationState;
+use dla_sim_tui::tree::{GrowthTree, TreeStats};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Options controlling a headless run
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    /// Grid width in cells
+    pub width: usize,
+    /// Grid height in cells
+    pub height: usize,
+    /// Maximum number of walker steps before giving up (0 = unlimited)
+    pub max_steps: usize,
+    /// Worker threads for particle walking (1 = serial)
+    pub threads: usize,
+    /// Where to write the stats JSON, if anywhere
+    pub stats_path: Option<String>,
+    /// Where to write the grid dump CSV, if anywhere
+    pub grid_path: Option<String>,
+    /// Where to write the fractal analysis time series CSV, if anywhere
+    pub analysis_path: Option<String>,
+    /// Where to write a PNG snapshot, if anywhere
+    pub snapshot_path: Option<String>,
+    /// Image pixels per simulation cell for the snapshot
+    pub snapshot_scale: u32,
+    /// Copies per side in the snapshot (Wrap runs tile seamlessly)
+    pub snapshot_tiles: u32,
+    /// Where to save the full simulation state when the run ends, if anywhere
+    pub save_state_path: Option<String>,
+}
+
+/// Summary of a finished run, written as the stats JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct RunStats {
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub rng_seed: u64,
+    pub num_particles: usize,
+    pub particles_stuck: usize,
+    /// Particles grown from sticky walls rather than the seed
+    pub deposits: usize,
+    pub completed: bool,
+    pub walker_steps: usize,
+    pub threads: usize,
+    pub max_radius: f32,
+    pub fractal_dimension: f32,
+    pub fractal_r_squared: f32,
+    pub radius_of_gyration: f32,
+    pub rg_dimension: f32,
+    pub mass_radius_dimension: f32,
+    pub correlation_dimension: f32,
+    /// Shape of the growth tree (parent links between particles)
+    pub tree: TreeStats,
+    pub elapsed_secs: f64,
+}
+
+impl RunStats {
+    pub fn from_simulation(
+        sim: &DlaSimulation,
+        walker_steps: usize,
+        threads: usize,
+        elapsed_secs: f64,
+    ) -> Self {
+        let (fractal_dimension, fractal_r_squared) = sim.calculate_fractal_dimension();
+        let analysis = analysis::analyze_simulation(sim);
+        Self {
+            grid_width: sim.grid_width,
+            grid_height: sim.grid_height,
+            rng_seed: sim.active_seed(),
+            num_particles: sim.num_particles,
+            particles_stuck: sim.particles_stuck,
+            deposits: sim.discs().iter().filter(|d| d.data.deposit).count(),
+            completed: sim.is_complete(),
+            walker_steps,
+            threads,
+            max_radius: sim.max_radius,
+            fractal_dimension,
+            fractal_r_squared,
+            radius_of_gyration: analysis.radius_of_gyration,
+            rg_dimension: analysis.rg_dimension,
+            mass_radius_dimension: analysis.mass_radius_dimension,
+            correlation_dimension: analysis.correlation_dimension,
+            tree: GrowthTree::new(sim.discs()).stats(),
+            elapsed_secs,
+        }
+    }
+}
+
+/// Build a simulation of the given size from a resolved config.
+/// The particle count is capped to what the grid can hold.
+pub fn simulation_from_config(config: &AppConfig, width: usize, height: usize) -> DlaSimulation {
+    let mut sim = DlaSimulation::new(width.max(1), height.max(1));
+    sim.settings = config.settings.clone();
+    sim.stickiness = config.stickiness;
+    sim.rng_seed = config.rng_seed;
+    sim.num_particles = config.num_particles.min(sim.max_particles());
+    sim.reset_with_seed(config.seed_pattern.clone());
+    sim
+}
+
+/// Step the simulation until it completes or `max_steps` is reached.
+/// With more than one thread, walkers are advanced in parallel batches.
+/// With `report_progress`, progress goes to stderr every 10%. Returns the number of steps taken.
+pub fn run_to_completion(
+    sim: &mut DlaSimulation,
+    max_steps: usize,
+    threads: usize,
+    report_progress: bool,
+) -> usize {
+    let mut steps = 0;
+    let mut last_reported = 0;
+    while !sim.is_complete() && (max_steps == 0 || steps < max_steps) {
+        if threads > 1 {
+            let walked = sim.step_parallel(threads);
+            if walked == 0 {
+                break;
+            }
+            steps += walked;
+        } else {
+            if !sim.step() {
+                break;
+            }
+            steps += 1;
+        }
+
+        let percent = (sim.progress() * 100.0) as usize;
+        if report_progress && percent >= last_reported + 10 {
+            last_reported = percent - percent % 10;
+            eprintln!(
+                "Progress: {:>3}% ({}/{} particles)",
+                last_reported, sim.particles_stuck, sim.particle_budget()
+            );
+        }
+    }
+    steps
+}
+
+/// Run a full headless simulation and write the requested outputs.
+/// A resumed state replaces the config-built simulation, grid size included.
+pub fn run(
+    config: &AppConfig,
+    options: &HeadlessOptions,
+    resume: Option<SimulationState>,
+) -> Result<RunStats, String> {
+    let mut sim = match resume {
+        Some(state) => {
+            let sim = DlaSimulation::from_state(state);
+            eprintln!("Resuming at {}/{} particles", sim.particles_stuck, sim.particle_budget());
+            sim
+        }
+        None => simulation_from_config(config, options.width, options.height),
+    };
+    sim.paused = false;
+    if sim.seed_particles() > sim.num_particles / 2 {
+        eprintln!(
+            "Warning: the imported seed is {} particles against {} to grow; the seed will dominate the cluster (raise --particles)",
+            sim.seed_particles(),
+            sim.num_particles
+        );
+    }
+    eprintln!(
+        "Running {}x{} grid, {} particles, seed {}, {} thread(s)",
+        sim.grid_width,
+        sim.grid_height,
+        sim.num_particles,
+        sim.active_seed(),
+        options.threads
+    );
+
+    let start = Instant::now();
+    let steps = run_to_completion(&mut sim, options.max_steps, options.threads, true);
+    let stats = RunStats::from_simulation(&sim, steps, options.threads, start.elapsed().as_secs_f64());
+
+    if stats.completed {
+        eprintln!(
+            "Completed {} particles in {:.2}s (D_f = {:.3})",
+            stats.particles_stuck, stats.elapsed_secs, stats.fractal_dimension
+        );
+    } else {
+        eprintln!(
+            "Stopped after {} steps with {}/{} particles",
+            steps, stats.particles_stuck, stats.num_particles
+        );
+    }
+
+    if let Some(path) = &options.grid_path {
+        write_grid_dump(&sim, Path::new(path))?;
+        eprintln!("Wrote grid dump to {}", path);
+    }
+    if let Some(path) = &options.analysis_path {
+        let series = analysis::time_series(&sim, analysis::SERIES_SAMPLES);
+        write_analysis_series(&series, Path::new(path))?;
+        eprintln!("Wrote {} analysis samples to {}", series.len(), path);
+    }
+    if let Some(path) = &options.snapshot_path {
+        let theme = config.theme.theme();
+        let (w, h) = snapshot::save_snapshot(
+            Path::new(path),
+            &sim,
+            &theme.color_scheme,
+            config.color_by_age,
+            theme.background,
+            options.snapshot_scale,
+            options.snapshot_tiles,
+        )?;
+        eprintln!("Wrote {}x{} snapshot to {}", w, h, path);
+    }
+    if let Some(path) = &options.save_state_path {
+        sim.to_state().save_to_file(Path::new(path))?;
+        eprintln!("Saved state to {}", path);
+    }
+    if let Some(path) = &options.stats_path {
+        write_stats(&stats, Path::new(path))?;
+        eprintln!("Wrote stats to {}", path);
+    }
+
+    Ok(stats)
+}
+
+/// Write every stuck particle as a CSV row.
+/// Off-lattice runs write the exact disc centres instead of grid cells.
+pub fn write_grid_dump(sim: &DlaSimulation, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create grid dump: {}", e))?;
+    let mut out = BufWriter::new(file);
+    let write_err = |e: std::io::Error| format!("Failed to write grid dump: {}", e);
+
+    writeln!(out, "x,y,age,distance,direction,neighbor_count,deposit").map_err(write_err)?;
+    if sim.settings.off_lattice {
+        for disc in sim.discs() {
+            let p = disc.data;
+            writeln!(
+                out,
+                "{:.3},{:.3},{},{:.3},{:.4},{},{}",
+                disc.x, disc.y, p.age, p.distance, p.direction, p.neighbor_count, p.deposit as u8
+            )
+            .map_err(write_err)?;
+        }
+        return out.flush().map_err(write_err);
+    }
+    for y in 0..sim.grid_height {
+        for x in 0..sim.grid_width {
+            if let Some(p) = sim.get_particle(x, y) {
+                writeln!(
+                    out,
+                    "{},{},{},{:.3},{:.4},{},{}",
+                    x, y, p.age, p.distance, p.direction, p.neighbor_count, p.deposit as u8
+                )
+                .map_err(write_err)?;
+            }
+        }
+    }
+    out.flush().map_err(write_err)
+}
+
+/// Write the analysis time series as CSV, one row per sample
+pub fn write_analysis_series(series: &[AnalysisSample], path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create analysis file: {}", e))?;
+    let mut out = BufWriter::new(file);
+    let write_err = |e: std::io::Error| format!("Failed to write analysis file: {}", e);
+
+    writeln!(
+        out,
+        "particles,radius_of_gyration,rg_dimension,mass_radius_dimension,correlation_dimension"
+    )
+    .map_err(write_err)?;
+    for s in series {
+        writeln!(
+            out,
+            "{},{:.3},{:.4},{:.4},{:.4}",
+            s.particles,
+            s.radius_of_gyration,
+            s.rg_dimension,
+            s.mass_radius_dimension,
+            s.correlation_dimension
+        )
+        .map_err(write_err)?;
+    }
+    out.flush().map_err(write_err)
+}
+
+/// Write run statistics as pretty-printed JSON
+pub fn write_stats(stats: &RunStats, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(stats)
+        .map_err(|e| format!("Failed to serialize stats: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write stats: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_run_writes_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let stats_path = dir.path().join("stats.json");
+        let grid_path = dir.path().join("grid.csv");
+
+        let config = AppConfig {
+            num_particles: 200,
+            rng_seed: Some(7),
+            ..Default::default()
+        };
+        let options = HeadlessOptions {
+            width: 80,
+            height: 80,
+            max_steps: 0,
+            threads: 1,
+            stats_path: Some(stats_path.to_string_lossy().into_owned()),
+            grid_path: Some(grid_path.to_string_lossy().into_owned()),
+            analysis_path: None,
+            snapshot_path: None,
+            snapshot_scale: 1,
+            snapshot_tiles: 1,
+            save_state_path: None,
+        };
+
+        let stats = run(&config, &options, None).unwrap();
+        assert!(stats.completed);
+        assert_eq!(stats.rng_seed, 7);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&stats_path).unwrap()).unwrap();
+        assert_eq!(json["particles_stuck"], stats.particles_stuck);
+
+        let csv = std::fs::read_to_string(&grid_path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("x,y,age,distance,direction,neighbor_count,deposit"));
+        assert!(lines.count() >= stats.particles_stuck);
+    }
+}
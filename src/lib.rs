@@ -0,0 +1,53 @@
+//! Diffusion-limited aggregation engine behind the `dla-sim-tui` terminal app.
+//!
+//! The simulation core ([`DlaSimulation`], [`SimulationSettings`], seed
+//! patterns, presets, config and state files), the fractal [`analysis`] and
+//! the growth [`tree`] have no terminal dependencies.
+//! Optional cargo features add the rest:
+//!
+//! - `render`: PNG snapshots ([`snapshot`]), video/GIF recording ([`recorder`])
+//!   and offline growth replays ([`replay`])
+//! - `tui`: ratatui colour types ([`color::ColorLut`], [`theme::Theme`])
+//! - `cli`: command-line parsing used by the binary
+//!
+//! All three are on by default; embedders that only need the engine can use
+//! `default-features = false`.
+//!
+//! ```
+//! use dla_sim_tui::{DlaSimulation, SeedPattern};
+//!
+//! let mut sim = DlaSimulation::new(120, 120);
+//! sim.num_particles = 500;
+//! sim.rng_seed = Some(42);
+//! sim.reset_with_seed(SeedPattern::Point);
+//! while sim.step() {}
+//! assert!(sim.is_complete());
+//! ```
+
+pub mod analysis;
+pub mod color;
+pub mod config;
+pub mod custom_seed;
+pub mod mask;
+pub mod presets;
+#[cfg(feature = "render")]
+pub mod recorder;
+#[cfg(feature = "render")]
+pub mod replay;
+pub mod settings;
+pub mod simulation;
+#[cfg(feature = "render")]
+pub mod snapshot;
+mod spatial;
+pub mod state;
+pub mod theme;
+pub mod tree;
+
+pub use color::ColorScheme;
+pub use config::AppConfig;
+pub use presets::{Preset, PresetManager};
+#[cfg(feature = "render")]
+pub use recorder::Recorder;
+pub use settings::SimulationSettings;
+pub use simulation::{Disc, DlaSimulation, ParticleData, SeedPattern};
+pub use state::SimulationState;
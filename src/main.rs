@@ -1,25 +1,33 @@
 mod app;
 mod braille;
-mod color;
-mod config;
-mod presets;
-mod recorder;
-mod settings;
-mod simulation;
-mod theme;
+mod headless;
+mod sweep;
 mod ui;
 
 use app::{App, Focus, ViewMode};
-use clap::{CommandFactory, FromArgMatches, Parser};
-use config::AppConfig;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use dla_sim_tui::config::AppConfig;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton,
+        MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use dla_sim_tui::mask::DomainMask;
+use dla_sim_tui::presets::PresetManager;
+use dla_sim_tui::recorder::QueuePolicy;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use settings::{BoundaryBehavior, ColorMode, NeighborhoodType, SpawnMode};
-use simulation::SeedPattern;
+use dla_sim_tui::settings::{
+    self, BoundaryBehavior, ColorMode, GrowthModelType, NeighborhoodType, SpawnMode, Species, StickyEdges,
+};
+use dla_sim_tui::custom_seed::CustomSeed;
+use dla_sim_tui::simulation::{DlaSimulation, PaintTool, SeedPattern};
+use dla_sim_tui::state::SimulationState;
+use dla_sim_tui::replay::{self, GrowthCurve, ReplayOptions};
+use dla_sim_tui::theme::BackgroundMode;
+use dla_sim_tui::{snapshot, theme};
 use std::io;
 use std::time::Duration;
 
@@ -39,6 +47,69 @@ struct Args {
     #[arg(long)]
     classic: bool,
 
+    /// Resume a simulation from a saved state file (grid, particles and RNG position)
+    #[arg(long, value_name = "FILE")]
+    resume: Option<String>,
+
+    // === Headless Mode ===
+    /// Run without the TUI until the simulation completes, then write outputs
+    #[arg(long)]
+    headless: bool,
+
+    /// Grid width for headless runs
+    #[arg(long, default_value = "400", value_name = "CELLS")]
+    width: usize,
+
+    /// Grid height for headless runs
+    #[arg(long, default_value = "400", value_name = "CELLS")]
+    height: usize,
+
+    /// Stop a headless run after this many walker steps even if incomplete (0 = no limit)
+    #[arg(long = "max-steps", default_value = "0", value_name = "N")]
+    max_steps: usize,
+
+    /// Write run statistics as JSON (headless)
+    #[arg(long, value_name = "FILE")]
+    stats: Option<String>,
+
+    /// Write every stuck particle as CSV: x,y,age,distance,direction,neighbor_count,deposit (headless)
+    #[arg(long = "grid-dump", value_name = "FILE")]
+    grid_dump: Option<String>,
+
+    /// Write the fractal analysis time series as CSV: particles, Rg and dimension estimates (headless)
+    #[arg(long, value_name = "FILE")]
+    analysis: Option<String>,
+
+    /// Save the full simulation state when the run ends, for a later --resume (headless)
+    #[arg(long = "save-state", value_name = "FILE")]
+    save_state: Option<String>,
+
+    /// Write a PNG snapshot of the finished cluster (headless)
+    #[arg(long, value_name = "FILE")]
+    snapshot: Option<String>,
+
+    /// Image pixels per simulation cell for PNG snapshots (1-16)
+    #[arg(long = "snapshot-scale", default_value = "4", value_name = "N")]
+    snapshot_scale: u32,
+
+    /// Repeat PNG snapshots N times per side (1-8). With --boundary wrap the
+    /// grid is a torus, so the tiles join seamlessly into a texture
+    #[arg(long = "snapshot-tiles", default_value = "1", value_name = "N")]
+    snapshot_tiles: u32,
+
+    /// Ordered dithering for GIF recordings and renders
+    #[arg(long = "gif-dither")]
+    gif_dither: bool,
+
+    /// When a recording's encoder falls behind: drop frames to keep the
+    /// simulation at full speed, or block to keep every frame (drop, block)
+    #[arg(long = "record-policy", default_value = "drop", value_name = "POLICY")]
+    record_policy: QueuePolicy,
+
+    /// Frames a recording can queue for its encoder thread
+    #[arg(long = "record-queue", default_value = "32", value_name = "N")]
+    record_queue: usize,
+
     // === Basic Parameters ===
     /// Number of particles to simulate (auto-capped to ~20% of grid area)
     #[arg(short = 'p', long, default_value = "5000")]
@@ -48,15 +119,45 @@ struct Args {
     #[arg(short = 's', long, default_value = "1.0")]
     stickiness: f32,
 
-    /// Initial seed pattern (point, line, cross, circle, ring, block, noise, scatter, multipoint, starburst)
+    /// Initial seed pattern (point, line, cross, circle, ring, block, noise, scatter, multipoint, starburst, empty,
+    /// or the name of a saved custom seed)
     #[arg(long, default_value = "point")]
     seed: String,
 
+    /// Seed from an image: its dark pixels, scaled to fit the grid, start as stuck particles
+    #[arg(long = "seed-image", value_name = "FILE", conflicts_with = "seed_text")]
+    seed_image: Option<String>,
+
+    /// Seed from text drawn in a built-in bitmap font ("\n" starts a new line)
+    #[arg(long = "seed-text", value_name = "TEXT")]
+    seed_text: Option<String>,
+
     /// Simulation speed (steps per frame, 1-100)
     #[arg(long, default_value = "15")]
     speed: usize,
 
+    /// Worker threads for particle walking (1 = serial, 0 = all cores).
+    /// Parallel runs are reproducible with --rng-seed but grow different clusters than serial runs
+    #[arg(long, default_value = "1", value_name = "N")]
+    threads: usize,
+
+    /// RNG seed for reproducible runs (same seed + settings = identical cluster)
+    #[arg(long = "rng-seed", value_name = "N")]
+    rng_seed: Option<u64>,
+
     // === Movement Parameters ===
+    /// Growth model (dla, eden, ballistic, rla, dbm, dlca)
+    #[arg(long, default_value = "dla")]
+    model: String,
+
+    /// Dielectric breakdown exponent eta (0.0-4.0, 0 = compact, 1 = DLA-like, >1 = needles)
+    #[arg(long, default_value = "1.0")]
+    eta: f32,
+
+    /// Cluster-cluster mobility exponent (0.0-2.0): a cluster of s particles moves at s^-exponent
+    #[arg(long, default_value = "0.5")]
+    mobility: f32,
+
     /// Walk step size per random walk iteration (0.5-5.0)
     #[arg(long = "walk-step", default_value = "2.0")]
     walk_step: f32,
@@ -73,6 +174,10 @@ struct Args {
     #[arg(long = "radial-bias", default_value = "0.0")]
     radial_bias: f32,
 
+    /// Off-lattice mode: particles are discs in continuous space that stick on contact
+    #[arg(long = "off-lattice")]
+    off_lattice: bool,
+
     // === Sticking Parameters ===
     /// Neighborhood type for sticking checks (vonneumann, moore, extended)
     #[arg(long, default_value = "moore")]
@@ -94,6 +199,16 @@ struct Args {
     #[arg(long = "stickiness-gradient", default_value = "0.0")]
     stickiness_gradient: f32,
 
+    // === Species Parameters ===
+    /// Add a particle species as SHARE[:SPAWN[:ANGLE[:FORCE]]], e.g. 0.3:top:90:0.2 (repeatable, up to 8).
+    /// Each species spawns and drifts on its own; without any, walkers use --spawn-mode and --walk-angle/--walk-force
+    #[arg(long = "species", value_name = "SPEC")]
+    species: Vec<Species>,
+
+    /// Sticking chance per (walker, stuck) species pair, rows separated by ';', e.g. "0.1,1;1,0.1"
+    #[arg(long = "stick-matrix", value_name = "MATRIX", default_value = "")]
+    stick_matrix: String,
+
     // === Spawn/Boundary Parameters ===
     /// Spawn mode (circle, edges, corners, random, top, bottom, left, right)
     #[arg(long = "spawn-mode", default_value = "circle")]
@@ -103,6 +218,10 @@ struct Args {
     #[arg(long, default_value = "clamp")]
     boundary: String,
 
+    /// Edges particles deposit on with --boundary stick (all, top, bottom, left, right, top-bottom, left-right)
+    #[arg(long = "sticky-edges", default_value = "all")]
+    sticky_edges: String,
+
     /// Buffer distance between structure and spawn circle (5-50)
     #[arg(long = "spawn-offset", default_value = "10.0")]
     spawn_offset: f32,
@@ -119,8 +238,14 @@ struct Args {
     #[arg(long = "max-iterations", default_value = "10000")]
     max_iterations: usize,
 
+    /// Domain mask image (PNG, PGM, ...) stretched over the grid: black = wall,
+    /// dark grey = absorbing sink, light grey = no-stick zone, white or transparent = open
+    #[arg(long, value_name = "FILE")]
+    mask: Option<String>,
+
     // === Visual Parameters ===
-    /// Color mode (age, distance, density, direction)
+    /// Color mode (age, distance, density, direction, origin, cluster, species,
+    /// depth, strahler, descendants)
     #[arg(long = "color-mode", default_value = "age")]
     color_mode: String,
 
@@ -135,9 +260,96 @@ struct Args {
     /// Color theme (default, lagoon, bluemono, violet, harvest, midnight, rainbow, frost, deep-space, sunset, matrix, amber)
     #[arg(short = 't', long, default_value = "default")]
     theme: String,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run every combination of parameter values headlessly and write one result row per run.
+    /// Options before `sweep` build the base config and grid, as for --headless
+    Sweep(SweepArgs),
+    /// Replay a finished cluster in stick order into a video, GIF or animated PNG
+    /// (.mp4, .webm, .gif, .png), or a directory of PNG frames (a name ending in /).
+    /// Replays --state if given; otherwise options before `render` set up a headless run first
+    Render(RenderArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct SweepArgs {
+    /// Parameter to vary, as NAME=A,B,C or NAME=START:END:STEP (repeatable).
+    /// NAME is any simulation setting or config field, as spelled in config files
+    #[arg(long = "param", value_name = "NAME=VALUES", required = true)]
+    params: Vec<sweep::SweepParam>,
+
+    /// Runs per combination; replicate i uses seed --rng-seed + i (random base if unset)
+    #[arg(long, default_value = "1", value_name = "N")]
+    replicates: usize,
+
+    /// Runs to execute concurrently (0 = all cores)
+    #[arg(long, default_value = "1", value_name = "N")]
+    jobs: usize,
+
+    /// Results file: JSON if it ends in .json, CSV otherwise
+    #[arg(long, value_name = "FILE")]
+    out: String,
+
+    /// Save a PNG snapshot of every run into this directory
+    #[arg(long = "png-dir", value_name = "DIR")]
+    png_dir: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RenderArgs {
+    /// Saved simulation state to replay instead of running a new simulation
+    #[arg(long, value_name = "FILE")]
+    state: Option<String>,
+
+    /// Output file; the extension picks the encoder as for live recording
+    #[arg(long, value_name = "FILE")]
+    out: String,
+
+    /// How the particles shown grow over the frames (linear, ease-in, log)
+    #[arg(long, default_value = "linear")]
+    curve: GrowthCurve,
+
+    /// Output size in pixels as WIDTHxHEIGHT; the grid is scaled to fit and centred
+    /// (default: 4 pixels per cell)
+    #[arg(long, value_name = "WxH", value_parser = parse_resolution)]
+    resolution: Option<(u32, u32)>,
+
+    /// Frames per second
+    #[arg(long, default_value = "30")]
+    fps: u32,
+
+    /// Seconds from the seed to the finished cluster
+    #[arg(long, default_value = "10", value_name = "SECS")]
+    duration: f32,
+
+    /// Seconds to hold the finished cluster at the end
+    #[arg(long, default_value = "2", value_name = "SECS")]
+    hold: f32,
+}
+
+/// Parse a WIDTHxHEIGHT pixel size
+fn parse_resolution(s: &str) -> Result<(u32, u32), String> {
+    let (w, h) = s
+        .to_lowercase()
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?)))
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got '{}'", s))?;
+    if w < 2 || h < 2 {
+        return Err(format!("resolution too small: {}", s));
+    }
+    Ok((w, h))
 }
 
 /// Parse with fallback to default on error
+fn parse_growth_model(s: &str) -> GrowthModelType {
+    s.parse().unwrap_or(GrowthModelType::Dla)
+}
+
 fn parse_neighborhood(s: &str) -> NeighborhoodType {
     s.parse().unwrap_or(NeighborhoodType::Moore)
 }
@@ -150,36 +362,80 @@ fn parse_boundary(s: &str) -> BoundaryBehavior {
     s.parse().unwrap_or(BoundaryBehavior::Clamp)
 }
 
+fn parse_sticky_edges(s: &str) -> StickyEdges {
+    s.parse().unwrap_or(StickyEdges::All)
+}
+
 fn parse_color_mode(s: &str) -> ColorMode {
     s.parse().unwrap_or(ColorMode::Age)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    use clap::parser::ValueSource;
-
     // Parse args and get matches for value_source checking
     let matches = Args::command().get_matches();
     let args = Args::from_arg_matches(&matches)?;
 
-    // Load config file if specified
-    let base_config = if let Some(config_path) = &args.config {
-        match AppConfig::load_from_file(std::path::Path::new(config_path)) {
-            Ok(cfg) => Some(cfg),
-            Err(e) => {
-                eprintln!("Warning: Failed to load config file: {}", e);
-                eprintln!("Using default settings.");
-                None
-            }
-        }
-    } else {
-        None
-    };
+    let preset_manager = PresetManager::new();
+    let config = resolve_config(&args, &matches, &preset_manager);
+    if args.snapshot_tiles > 1 && config.settings.boundary_behavior != BoundaryBehavior::Wrap {
+        eprintln!("Warning: --snapshot-tiles only gives seamless tiles with --boundary wrap");
+    }
 
-    // Helper to check if a CLI arg was explicitly provided
-    let is_explicit = |name: &str| -> bool {
-        matches.value_source(name) == Some(ValueSource::CommandLine)
+    // Load the saved state up front so a bad file fails before the terminal is touched
+    let resume_state = match &args.resume {
+        Some(path) => Some(SimulationState::load_from_file(std::path::Path::new(path))?),
+        None => None,
     };
-    let use_default_args = base_config.is_none() && !args.classic;
+
+    if let Some(Command::Sweep(sweep_args)) = &args.command {
+        let options = sweep::SweepOptions {
+            width: args.width,
+            height: args.height,
+            max_steps: args.max_steps,
+            threads: resolve_threads(args.threads),
+            jobs: resolve_threads(sweep_args.jobs),
+            replicates: sweep_args.replicates,
+            base_seed: config.rng_seed.unwrap_or_else(rand::random),
+            out_path: sweep_args.out.clone(),
+            png_dir: sweep_args.png_dir.clone(),
+            snapshot_scale: args.snapshot_scale,
+            snapshot_tiles: args.snapshot_tiles,
+        };
+        if let Err(e) = sweep::run(&config, &sweep_args.params, &options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Render(render_args)) = &args.command {
+        if let Err(e) = run_render(&args, &matches, &config, render_args) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.headless {
+        let options = headless::HeadlessOptions {
+            width: args.width,
+            height: args.height,
+            max_steps: args.max_steps,
+            threads: resolve_threads(args.threads),
+            stats_path: args.stats.clone(),
+            grid_path: args.grid_dump.clone(),
+            analysis_path: args.analysis.clone(),
+            snapshot_path: args.snapshot.clone(),
+            snapshot_scale: args.snapshot_scale,
+            snapshot_tiles: args.snapshot_tiles,
+            save_state_path: args.save_state.clone(),
+        };
+        if let Err(e) = headless::run(&config, &options, resume_state) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     // Setup terminal
     enable_raw_mode()?;
@@ -198,112 +454,287 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     let (canvas_width, canvas_height) = ui::get_canvas_size(frame_rect, ViewMode::Default);
     let mut app = App::new(canvas_width, canvas_height);
+    app.preset_manager = preset_manager;
+    app.threads = resolve_threads(args.threads);
+    app.snapshot_scale = args.snapshot_scale.clamp(1, snapshot::MAX_SNAPSHOT_SCALE);
+    app.snapshot_tiles = args.snapshot_tiles.clamp(1, snapshot::MAX_SNAPSHOT_TILES);
+    app.recorder.config.dither = args.gif_dither;
+    app.recorder.config.queue_policy = args.record_policy;
+    app.recorder.config.queue_capacity = args.record_queue.max(1);
+
+    // Apply resolved settings, capping the particle count to the grid size
+    app.apply_config(&config);
+    app.simulation.num_particles = app.simulation.num_particles.min(app.simulation.max_particles());
+
+    // Reset with seed pattern (must come after settings are applied),
+    // or pick up a saved run where it stopped
+    if let Some(state) = resume_state {
+        app.simulation = DlaSimulation::from_state(state);
+    } else {
+        app.simulation.reset_with_seed(config.seed_pattern);
+    }
+
+    // Run the app
+    let res = run_app(&mut terminal, &mut app);
+
+    // Cleanup
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), DisableMouseCapture, LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        eprintln!("Error: {:?}", err);
+    }
+
+    // Quitting mid-recording still encodes the queued frames and finalizes the file
+    if app.is_recording() {
+        match app.stop_recording() {
+            Ok(msg) => eprintln!("{}", msg),
+            Err(e) => eprintln!("Recording error: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Replay a saved state, or a new headless run of the resolved config, into a video
+fn run_render(args: &Args, matches: &ArgMatches, config: &AppConfig, render: &RenderArgs) -> Result<(), String> {
+    let sim = match &render.state {
+        Some(path) => {
+            let mut sim = DlaSimulation::from_state(SimulationState::load_from_file(std::path::Path::new(path))?);
+            // A saved state keeps its own colouring unless asked otherwise
+            let is_explicit = |name: &str| matches.value_source(name) == Some(clap::parser::ValueSource::CommandLine);
+            if is_explicit("color_mode") {
+                sim.settings.color_mode = config.settings.color_mode;
+            }
+            if is_explicit("invert") {
+                sim.settings.invert_colors = config.settings.invert_colors;
+            }
+            sim
+        }
+        None => {
+            let mut sim = headless::simulation_from_config(config, args.width, args.height);
+            let threads = resolve_threads(args.threads);
+            eprintln!(
+                "Running {}x{} grid, {} particles, seed {}, {} thread(s)",
+                sim.grid_width,
+                sim.grid_height,
+                sim.num_particles,
+                sim.active_seed(),
+                threads
+            );
+            headless::run_to_completion(&mut sim, args.max_steps, threads, true);
+            sim
+        }
+    };
+
+    let theme = config.theme.theme();
+    let (width, height) = render.resolution.unwrap_or((0, 0));
+    let options = ReplayOptions {
+        width,
+        height,
+        fps: render.fps.max(1),
+        duration: render.duration.max(0.0),
+        hold: render.hold.max(0.0),
+        curve: render.curve,
+        color_scheme: theme.color_scheme,
+        color_by_age: config.color_by_age,
+        background: match theme.background {
+            BackgroundMode::Solid(r, g, b) => (r, g, b),
+            BackgroundMode::Transparent => (0, 0, 0),
+        },
+        highlight: match theme.highlight_color {
+            ratatui::style::Color::Rgb(r, g, b) => (r, g, b),
+            _ => (255, 255, 255),
+        },
+        dither: args.gif_dither,
+    };
+    eprintln!(
+        "Replaying {} particles ({} curve, {:.1}s at {}fps)",
+        sim.particles_stuck,
+        options.curve.name(),
+        options.duration,
+        options.fps
+    );
+    let (filename, frames) = replay::save_replay(&render.out, &sim, &options)?;
+    eprintln!("Wrote {} frames to {}", frames, filename);
+    Ok(())
+}
 
-    // Apply config file settings first (if loaded)
-    if let Some(cfg) = &base_config {
-        app.apply_config(cfg);
+/// Map the --threads value to a worker count (0 = all available cores)
+fn resolve_threads(requested: usize) -> usize {
+    if requested == 0 {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        requested
     }
+}
+
+/// Resolve the effective configuration: config file first, then preset, then CLI args.
+/// CLI args only override when explicitly provided, or when no config file was loaded.
+fn resolve_config(args: &Args, matches: &ArgMatches, preset_manager: &PresetManager) -> AppConfig {
+    use clap::parser::ValueSource;
+
+    // Load config file if specified
+    let base_config = if let Some(config_path) = &args.config {
+        match AppConfig::load_from_file(std::path::Path::new(config_path)) {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                eprintln!("Warning: Failed to load config file: {}", e);
+                eprintln!("Using default settings.");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Helper to check if a CLI arg was explicitly provided
+    let is_explicit = |name: &str| -> bool {
+        matches.value_source(name) == Some(ValueSource::CommandLine)
+    };
+    let use_default_args = base_config.is_none() && !args.classic;
+
+    let mut config = base_config.unwrap_or_default();
 
     // Apply preset if specified (after config, before CLI args)
     if let Some(preset_name) = &args.preset {
-        if let Some(preset) = app.preset_manager.find(preset_name) {
-            app.simulation.settings = preset.settings.clone();
-            app.simulation.stickiness = preset.base_stickiness;
-            app.simulation.num_particles = preset.num_particles;
-            app.simulation.seed_pattern = preset.seed_pattern;
+        if let Some(preset) = preset_manager.find(preset_name) {
+            config.settings = preset.settings.clone();
+            config.stickiness = preset.base_stickiness;
+            config.num_particles = preset.num_particles;
+            config.seed_pattern = preset.seed_pattern.clone();
+            config.rng_seed = preset.rng_seed;
         } else {
             eprintln!("Warning: Preset '{}' not found. Available presets:", preset_name);
-            for name in app.preset_manager.preset_names() {
+            for name in preset_manager.preset_names() {
                 eprintln!("  - {}", name);
             }
         }
     }
 
-    // Apply CLI args - only if explicitly provided (override config) or no config loaded
-    let max_particles = app.simulation.max_particles();
-
     // Basic params
     if is_explicit("particles") || use_default_args {
-        app.simulation.num_particles = args.particles.clamp(100, max_particles);
-    } else {
-        // Cap config value to max_particles
-        app.simulation.num_particles = app.simulation.num_particles.min(max_particles);
+        config.num_particles = args.particles.max(100);
     }
     if is_explicit("stickiness") || use_default_args {
-        app.simulation.stickiness = args.stickiness.clamp(0.1, 1.0);
+        config.stickiness = args.stickiness.clamp(0.1, 1.0);
     }
     if is_explicit("speed") || use_default_args {
-        app.steps_per_frame = args.speed.clamp(1, 100);
+        config.steps_per_frame = args.speed.clamp(1, 100);
+    }
+    if args.rng_seed.is_some() {
+        config.rng_seed = args.rng_seed;
     }
 
+    let settings = &mut config.settings;
+
     // Movement settings
+    if is_explicit("model") || use_default_args {
+        settings.growth_model = parse_growth_model(&args.model);
+    }
+    if is_explicit("eta") || use_default_args {
+        settings.dbm_eta = args.eta.clamp(0.0, 4.0);
+    }
+    if is_explicit("mobility") || use_default_args {
+        settings.mobility_exponent = args.mobility.clamp(0.0, 2.0);
+    }
     if is_explicit("walk_step") || use_default_args {
-        app.simulation.settings.walk_step_size = args.walk_step.clamp(0.5, 5.0);
+        settings.walk_step_size = args.walk_step.clamp(0.5, 5.0);
     }
     if is_explicit("walk_angle") || use_default_args {
-        app.simulation.settings.walk_bias_angle = args.walk_angle.clamp(0.0, 360.0);
+        settings.walk_bias_angle = args.walk_angle.clamp(0.0, 360.0);
     }
     if is_explicit("walk_force") || use_default_args {
-        app.simulation.settings.walk_bias_strength = args.walk_force.clamp(0.0, 0.5);
+        settings.walk_bias_strength = args.walk_force.clamp(0.0, 0.5);
     }
     if is_explicit("radial_bias") || use_default_args {
-        app.simulation.settings.radial_bias = args.radial_bias.clamp(-0.3, 0.3);
+        settings.radial_bias = args.radial_bias.clamp(-0.3, 0.3);
+    }
+    if args.off_lattice {
+        settings.off_lattice = true;
     }
 
     // Sticking settings
     if is_explicit("neighborhood") || use_default_args {
-        app.simulation.settings.neighborhood = parse_neighborhood(&args.neighborhood);
+        settings.neighborhood = parse_neighborhood(&args.neighborhood);
     }
     if is_explicit("multi_contact") || use_default_args {
-        app.simulation.settings.multi_contact_min = args.multi_contact.clamp(1, 4);
+        settings.multi_contact_min = args.multi_contact.clamp(1, 4);
     }
     if is_explicit("tip_stickiness") || use_default_args {
-        app.simulation.settings.tip_stickiness = args.tip_stickiness.clamp(0.1, 1.0);
+        settings.tip_stickiness = args.tip_stickiness.clamp(0.1, 1.0);
     }
     if is_explicit("side_stickiness") || use_default_args {
-        app.simulation.settings.side_stickiness = args.side_stickiness.clamp(0.1, 1.0);
+        settings.side_stickiness = args.side_stickiness.clamp(0.1, 1.0);
     }
     if is_explicit("stickiness_gradient") || use_default_args {
-        app.simulation.settings.stickiness_gradient = args.stickiness_gradient.clamp(-0.5, 0.5);
+        settings.stickiness_gradient = args.stickiness_gradient.clamp(-0.5, 0.5);
+    }
+
+    // Species settings
+    if is_explicit("species") || use_default_args {
+        settings.species = args.species.iter().take(settings::MAX_SPECIES).copied().collect();
+    }
+    if is_explicit("stick_matrix") || use_default_args {
+        match settings::parse_stick_matrix(&args.stick_matrix) {
+            Ok(matrix) => settings.stick_matrix = matrix,
+            Err(e) => eprintln!("Warning: Ignoring --stick-matrix: {}", e),
+        }
+    }
+    if let Err(e) = settings.validate_stick_matrix() {
+        eprintln!("Warning: Ignoring stick matrix: {}", e);
+        settings.stick_matrix.clear();
     }
 
     // Spawn/boundary settings
     if is_explicit("spawn_mode") || use_default_args {
-        app.simulation.settings.spawn_mode = parse_spawn_mode(&args.spawn_mode);
+        settings.spawn_mode = parse_spawn_mode(&args.spawn_mode);
     }
     if is_explicit("boundary") || use_default_args {
-        app.simulation.settings.boundary_behavior = parse_boundary(&args.boundary);
+        settings.boundary_behavior = parse_boundary(&args.boundary);
+    }
+    if is_explicit("sticky_edges") || use_default_args {
+        settings.sticky_edges = parse_sticky_edges(&args.sticky_edges);
     }
     if is_explicit("spawn_offset") || use_default_args {
-        app.simulation.settings.spawn_radius_offset = args.spawn_offset.clamp(5.0, 50.0);
+        settings.spawn_radius_offset = args.spawn_offset.clamp(5.0, 50.0);
     }
     if is_explicit("escape_mult") || use_default_args {
-        app.simulation.settings.escape_multiplier = args.escape_mult.clamp(2.0, 6.0);
+        settings.escape_multiplier = args.escape_mult.clamp(2.0, 6.0);
     }
     if is_explicit("min_radius") || use_default_args {
-        app.simulation.settings.min_spawn_radius = args.min_radius.clamp(20.0, 100.0);
+        settings.min_spawn_radius = args.min_radius.clamp(20.0, 100.0);
     }
     if is_explicit("max_iterations") || use_default_args {
-        app.simulation.settings.max_walk_iterations = args.max_iterations.clamp(1000, 50000);
+        settings.max_walk_iterations = args.max_iterations.clamp(1000, 50000);
+    }
+    if let Some(path) = &args.mask {
+        match DomainMask::load(std::path::Path::new(path)) {
+            Ok(mask) => settings.mask = Some(mask),
+            Err(e) => eprintln!("Warning: Ignoring --mask: {}", e),
+        }
+    } else if use_default_args {
+        settings.mask = None;
     }
 
     // Visual settings
     if is_explicit("color_mode") || use_default_args {
-        app.simulation.settings.color_mode = parse_color_mode(&args.color_mode);
+        settings.color_mode = parse_color_mode(&args.color_mode);
     }
     if is_explicit("highlight") || use_default_args {
-        app.simulation.settings.highlight_recent = args.highlight.clamp(0, 50);
+        settings.highlight_recent = args.highlight.clamp(0, 50);
     }
     if is_explicit("invert") || use_default_args {
-        app.simulation.settings.invert_colors = args.invert;
+        settings.invert_colors = args.invert;
     }
     if is_explicit("theme") || use_default_args {
-        app.set_theme(theme::parse_theme(&args.theme));
+        config.theme = theme::parse_theme(&args.theme);
     }
 
     // Determine seed pattern - CLI overrides config
-    let seed_pattern = if is_explicit("seed") || use_default_args {
-        match args.seed.to_lowercase().as_str() {
+    if is_explicit("seed") || use_default_args {
+        config.seed_pattern = match args.seed.to_lowercase().as_str() {
             "line" => SeedPattern::Line,
             "cross" => SeedPattern::Cross,
             "circle" => SeedPattern::Circle,
@@ -313,31 +744,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "scatter" => SeedPattern::Scatter,
             "multipoint" | "multi-point" => SeedPattern::MultiPoint,
             "starburst" | "spokes" | "star" => SeedPattern::Starburst,
-            _ => SeedPattern::Point,
-        }
-    } else {
-        app.simulation.seed_pattern
+            "empty" | "none" => SeedPattern::Empty,
+            // Saved custom seeds are chosen by name
+            _ => CustomSeed::find(&args.seed).map_or(SeedPattern::Point, SeedPattern::Custom),
+        };
+    }
+    let imported = match (&args.seed_image, &args.seed_text) {
+        (Some(path), _) => Some(SeedPattern::Image(path.into())),
+        (None, Some(text)) => Some(SeedPattern::Text(text.replace("\\n", "\n"))),
+        (None, None) => None,
     };
-
-    // Reset with seed pattern (must come after settings are applied)
-    app.simulation.reset_with_seed(seed_pattern);
-
-    // Run the app
-    let res = run_app(&mut terminal, &mut app);
-
-    // Cleanup
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
-    if let Err(err) = res {
-        eprintln!("Error: {:?}", err);
+    if let Some(pattern) = imported {
+        match pattern.validate() {
+            Ok(()) => config.seed_pattern = pattern,
+            Err(e) => eprintln!("Warning: Ignoring imported seed: {}", e),
+        }
     }
 
-    Ok(())
+    config
 }
 
-fn run_app<B: ratatui::backend::Backend>(
+fn run_app<B: ratatui::backend::Backend + io::Write>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> io::Result<()> {
@@ -410,6 +837,68 @@ fn run_app<B: ratatui::backend::Backend>(
                         continue;
                     }
 
+                    // === Handle snapshot popup keys (if snapshot popup is open) ===
+                    if app.snapshot_popup.is_some() {
+                        match key.code {
+                            KeyCode::Enter => app.confirm_snapshot(),
+                            KeyCode::Esc => app.close_snapshot_popup(),
+                            KeyCode::Up => app.adjust_snapshot_scale(1),
+                            KeyCode::Down => app.adjust_snapshot_scale(-1),
+                            KeyCode::Backspace => {
+                                if let Some(popup) = &mut app.snapshot_popup {
+                                    popup.delete_char();
+                                }
+                            }
+                            KeyCode::Left => {
+                                if let Some(popup) = &mut app.snapshot_popup {
+                                    popup.move_cursor_left();
+                                }
+                            }
+                            KeyCode::Right => {
+                                if let Some(popup) = &mut app.snapshot_popup {
+                                    popup.move_cursor_right();
+                                }
+                            }
+                            KeyCode::Char(c) => {
+                                if let Some(popup) = &mut app.snapshot_popup {
+                                    popup.insert_char(c);
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    // === Handle state save/load popup keys (if state popup is open) ===
+                    if app.state_popup.is_some() {
+                        match key.code {
+                            KeyCode::Enter => app.confirm_state_popup(),
+                            KeyCode::Esc => app.close_state_popup(),
+                            KeyCode::Backspace => {
+                                if let Some(popup) = &mut app.state_popup {
+                                    popup.delete_char();
+                                }
+                            }
+                            KeyCode::Left => {
+                                if let Some(popup) = &mut app.state_popup {
+                                    popup.move_cursor_left();
+                                }
+                            }
+                            KeyCode::Right => {
+                                if let Some(popup) = &mut app.state_popup {
+                                    popup.move_cursor_right();
+                                }
+                            }
+                            KeyCode::Char(c) => {
+                                if let Some(popup) = &mut app.state_popup {
+                                    popup.insert_char(c);
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     // === Handle recording popup keys (if recording popup is open) ===
                     if app.recording_popup.is_some() {
                         match key.code {
@@ -498,11 +987,51 @@ fn run_app<B: ratatui::backend::Backend>(
                         continue;
                     }
 
+                    // === Handle custom seed save popup keys (if open) ===
+                    if app.seed_save_popup.is_some() {
+                        match key.code {
+                            KeyCode::Enter => app.confirm_seed_save(),
+                            KeyCode::Esc => app.close_seed_save_popup(),
+                            KeyCode::Backspace => {
+                                if let Some(popup) = &mut app.seed_save_popup {
+                                    popup.delete_char();
+                                }
+                            }
+                            KeyCode::Left => {
+                                if let Some(popup) = &mut app.seed_save_popup {
+                                    popup.move_cursor_left();
+                                }
+                            }
+                            KeyCode::Right => {
+                                if let Some(popup) = &mut app.seed_save_popup {
+                                    popup.move_cursor_right();
+                                }
+                            }
+                            KeyCode::Char(c) => {
+                                if let Some(popup) = &mut app.seed_save_popup {
+                                    popup.insert_char(c);
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     // Clear export result on any key press
                     if app.export_result.is_some() {
                         app.clear_export_result();
                     }
 
+                    // Clear snapshot result on any key press
+                    if app.snapshot_result.is_some() {
+                        app.clear_snapshot_result();
+                    }
+
+                    // Clear state result on any key press
+                    if app.state_result.is_some() {
+                        app.clear_state_result();
+                    }
+
                     // Clear recording result on any key press
                     if app.recording_result.is_some() {
                         app.clear_recording_result();
@@ -513,6 +1042,53 @@ fn run_app<B: ratatui::backend::Backend>(
                         app.clear_preset_result();
                     }
 
+                    // Clear custom seed result on any key press
+                    if app.paint_result.is_some() {
+                        app.clear_paint_result();
+                    }
+
+                    // === Timeline scrubbing takes the arrow keys while open ===
+                    if app.timeline.is_some() {
+                        let handled = match key.code {
+                            KeyCode::Left => {
+                                app.scrub_timeline_coarse(-1);
+                                true
+                            }
+                            KeyCode::Right => {
+                                app.scrub_timeline_coarse(1);
+                                true
+                            }
+                            KeyCode::Char(',') => {
+                                app.scrub_timeline(-1);
+                                true
+                            }
+                            KeyCode::Char('.') => {
+                                app.scrub_timeline(1);
+                                true
+                            }
+                            KeyCode::Home => {
+                                app.jump_timeline(false);
+                                true
+                            }
+                            KeyCode::End => {
+                                app.jump_timeline(true);
+                                true
+                            }
+                            KeyCode::Enter => {
+                                app.branch_timeline();
+                                true
+                            }
+                            KeyCode::Esc => {
+                                app.toggle_timeline();
+                                true
+                            }
+                            _ => false,
+                        };
+                        if handled {
+                            continue;
+                        }
+                    }
+
                     // === Handle Shift+letter to open popup ===
                     if key.modifiers.contains(KeyModifiers::SHIFT) {
                         if let KeyCode::Char(c) = key.code {
@@ -526,6 +1102,11 @@ fn run_app<B: ratatui::backend::Backend>(
                                 app.open_export_popup();
                                 continue;
                             }
+                            // Shift+V opens PNG snapshot popup
+                            if c == 'V' || c == 'v' {
+                                app.open_snapshot_popup();
+                                continue;
+                            }
                             // Shift+L opens preset load popup
                             if c == 'L' || c == 'l' {
                                 app.open_preset_popup();
@@ -564,6 +1145,9 @@ fn run_app<B: ratatui::backend::Backend>(
                         KeyCode::Char('r') | KeyCode::Char('R') => app.reset(),
                         KeyCode::Char('v') | KeyCode::Char('V') => app.cycle_view_mode(),
                         KeyCode::Char('h') | KeyCode::Char('H') => app.toggle_help(),
+                        // Full state save/load
+                        KeyCode::F(5) => app.open_state_save_popup(),
+                        KeyCode::F(9) => app.open_state_load_popup(),
                         // Recording toggle (backtick)
                         KeyCode::Char('`') => {
                             if app.is_recording() {
@@ -620,6 +1204,22 @@ fn run_app<B: ratatui::backend::Backend>(
                             app.cycle_theme_prev();
                         }
                         KeyCode::Char('a') | KeyCode::Char('A') => app.toggle_color_by_age(),
+                        KeyCode::Char('f') => app.toggle_potential_overlay(),
+                        // Paint mode: mouse capture is only on while painting,
+                        // so the terminal keeps text selection otherwise
+                        KeyCode::Char('d') => {
+                            app.toggle_paint_mode();
+                            if app.paint_mode {
+                                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+                            } else {
+                                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+                            }
+                        }
+                        KeyCode::Char('g') if app.paint_mode => app.cycle_paint_tool(),
+                        KeyCode::Char(',') if app.paint_mode => app.adjust_brush_size(-1),
+                        KeyCode::Char('.') if app.paint_mode => app.adjust_brush_size(1),
+                        KeyCode::Char('u') => app.open_seed_save_popup(),
+                        KeyCode::Char('z') => app.toggle_timeline(),
                         KeyCode::Char('m') | KeyCode::Char('M') => {
                             app.cycle_color_mode();
                             app.focus = Focus::Mode;
@@ -709,6 +1309,50 @@ fn run_app<B: ratatui::backend::Backend>(
                         _ => {}
                     }
                 }
+                Event::Mouse(mouse) if app.paint_mode => {
+                    let size = terminal.size()?;
+                    let canvas = ui::get_canvas_area(
+                        ratatui::layout::Rect {
+                            x: 0,
+                            y: 0,
+                            width: size.width,
+                            height: size.height,
+                        },
+                        app.view_mode,
+                    );
+                    let on_canvas = mouse.column >= canvas.x
+                        && mouse.column < canvas.x + canvas.width
+                        && mouse.row >= canvas.y
+                        && mouse.row < canvas.y + canvas.height;
+                    // Left button paints with the current tool, right button erases
+                    let tool = match mouse.kind {
+                        MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+                            Some(app.paint_tool)
+                        }
+                        MouseEventKind::Down(MouseButton::Right) | MouseEventKind::Drag(MouseButton::Right) => {
+                            Some(PaintTool::Erase)
+                        }
+                        MouseEventKind::ScrollUp => {
+                            app.adjust_brush_size(1);
+                            None
+                        }
+                        MouseEventKind::ScrollDown => {
+                            app.adjust_brush_size(-1);
+                            None
+                        }
+                        _ => None,
+                    };
+                    match tool {
+                        Some(tool) if on_canvas => app.paint_at(
+                            mouse.column - canvas.x,
+                            mouse.row - canvas.y,
+                            canvas.width,
+                            canvas.height,
+                            tool,
+                        ),
+                        _ => app.end_paint_stroke(),
+                    }
+                }
                 Event::Resize(width, height) => {
                     let (canvas_width, canvas_height) = ui::get_canvas_size(
                         ratatui::layout::Rect {
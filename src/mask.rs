@@ -0,0 +1,177 @@
+//! Domain masks: images that mark grid cells as walls, absorbing sinks or
+//! no-stick zones, so clusters can grow inside channels, around pillars or in
+//! a circular dish.
+//!
+//! A mask has its own resolution and is scaled to the grid (nearest cell) at
+//! every reset. Grey levels pick the cell type: black is wall, dark grey is
+//! sink, light grey is no-stick and white (or transparent) is open.
+
+use serde::{Deserialize, Serialize};
+
+/// What a mask does to the grid cells under it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskCell {
+    /// Free space
+    #[default]
+    Open,
+    /// Obstacle: walkers can't enter it and nothing sticks there
+    Wall,
+    /// Absorbing sink: a walker that enters it is removed
+    Sink,
+    /// Walkers pass through but can't stick there
+    NoStick,
+}
+
+impl MaskCell {
+    /// Cell type for a grey level (0-255)
+    pub fn from_luma(luma: u8) -> Self {
+        match luma {
+            0..=63 => MaskCell::Wall,
+            64..=127 => MaskCell::Sink,
+            128..=191 => MaskCell::NoStick,
+            _ => MaskCell::Open,
+        }
+    }
+
+    /// Whether a walker may move into a cell of this type
+    pub fn walkable(self) -> bool {
+        self != MaskCell::Wall
+    }
+
+    /// Dimmed colour drawn under the cluster, or None for open cells
+    pub fn shade(self) -> Option<(u8, u8, u8)> {
+        match self {
+            MaskCell::Open => None,
+            MaskCell::Wall => Some((70, 70, 78)),
+            MaskCell::Sink => Some((96, 34, 40)),
+            MaskCell::NoStick => Some((34, 52, 92)),
+        }
+    }
+
+    /// Character used for the cell in saved masks
+    fn symbol(self) -> char {
+        match self {
+            MaskCell::Open => '.',
+            MaskCell::Wall => '#',
+            MaskCell::Sink => 'o',
+            MaskCell::NoStick => '~',
+        }
+    }
+
+    fn from_symbol(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(MaskCell::Open),
+            '#' => Some(MaskCell::Wall),
+            'o' => Some(MaskCell::Sink),
+            '~' => Some(MaskCell::NoStick),
+            _ => None,
+        }
+    }
+}
+
+/// A mask at its own resolution. Saved in config, preset and state files as
+/// one string per row, with a character per cell (`.` open, `#` wall,
+/// `o` sink, `~` no-stick).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "MaskRows", try_from = "MaskRows")]
+pub struct DomainMask {
+    width: usize,
+    height: usize,
+    cells: Vec<MaskCell>,
+}
+
+impl DomainMask {
+    /// Build a mask by asking `f` for the type of every cell
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> MaskCell) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let cells = (0..width * height).map(|i| f(i % width, i / width)).collect();
+        Self { width, height, cells }
+    }
+
+    /// Read a mask from an image file (PNG, PGM or anything else `image` decodes).
+    /// Transparent pixels are open.
+    #[cfg(feature = "render")]
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("Failed to read mask image: {}", e))?
+            .to_luma_alpha8();
+        let (width, height) = image.dimensions();
+        Ok(Self::from_fn(width as usize, height as usize, |x, y| {
+            let [luma, alpha] = image.get_pixel(x as u32, y as u32).0;
+            if alpha < 128 {
+                MaskCell::Open
+            } else {
+                MaskCell::from_luma(luma)
+            }
+        }))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Type of the mask cell at (x, y)
+    pub fn get(&self, x: usize, y: usize) -> MaskCell {
+        self.cells[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+
+    /// The mask stretched over a grid of the given size, row-major, taking
+    /// the mask cell under the centre of each grid cell
+    pub fn resample(&self, width: usize, height: usize) -> Vec<MaskCell> {
+        let mut cells = Vec::with_capacity(width * height);
+        for gy in 0..height {
+            let y = ((gy as f32 + 0.5) * self.height as f32 / height as f32) as usize;
+            for gx in 0..width {
+                let x = ((gx as f32 + 0.5) * self.width as f32 / width as f32) as usize;
+                cells.push(self.get(x, y));
+            }
+        }
+        cells
+    }
+}
+
+/// Serialized form of a `DomainMask`
+#[derive(Serialize, Deserialize)]
+struct MaskRows {
+    rows: Vec<String>,
+}
+
+impl From<DomainMask> for MaskRows {
+    fn from(mask: DomainMask) -> Self {
+        let rows = mask
+            .cells
+            .chunks(mask.width)
+            .map(|row| row.iter().map(|c| c.symbol()).collect())
+            .collect();
+        Self { rows }
+    }
+}
+
+impl TryFrom<MaskRows> for DomainMask {
+    type Error = String;
+
+    fn try_from(saved: MaskRows) -> Result<Self, Self::Error> {
+        let width = saved.rows.first().map_or(0, |r| r.chars().count());
+        if width == 0 {
+            return Err("mask has no cells".to_string());
+        }
+        let mut cells = Vec::with_capacity(width * saved.rows.len());
+        for (y, row) in saved.rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("mask row {} is not {} cells wide", y, width));
+            }
+            for c in row.chars() {
+                cells.push(MaskCell::from_symbol(c).ok_or_else(|| format!("unknown mask cell '{}'", c))?);
+            }
+        }
+        Ok(Self {
+            width,
+            height: saved.rows.len(),
+            cells,
+        })
+    }
+}
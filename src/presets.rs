@@ -1,11 +1,16 @@
+use crate::mask::{DomainMask, MaskCell};
 use crate::settings::{
-    BoundaryBehavior, NeighborhoodType, SimulationSettings, SpawnMode,
+    BoundaryBehavior, ColorMode, GrowthModelType, NeighborhoodType, SimulationSettings, SpawnMode, Species,
+    StickyEdges,
 };
 use crate::simulation::SeedPattern;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+/// Cells per side of the Petri Dish preset's mask
+const PETRI_DISH_SIZE: usize = 128;
+
 /// A named preset containing simulation settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Preset {
@@ -15,6 +20,9 @@ pub struct Preset {
     pub seed_pattern: SeedPattern,
     pub base_stickiness: f32,
     pub num_particles: usize,
+    /// Fixed RNG seed for reproducible runs (None = random each reset)
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
 }
 
 impl Preset {
@@ -33,6 +41,7 @@ impl Preset {
             seed_pattern,
             base_stickiness,
             num_particles,
+            rng_seed: None,
         }
     }
 }
@@ -221,6 +230,149 @@ impl PresetManager {
                 0.8,
                 5000,
             ),
+            // Off-Lattice - isotropic research-grade DLA
+            Preset::new(
+                "Off-Lattice",
+                "Disc particles in continuous space (D ≈ 1.71)",
+                SimulationSettings {
+                    off_lattice: true,
+                    ..Default::default()
+                },
+                SeedPattern::Point,
+                1.0,
+                5000,
+            ),
+            // Needle Forest - substrate deposition off the bottom wall
+            Preset::new(
+                "Needle Forest",
+                "Deposits growing up from a sticky floor",
+                SimulationSettings {
+                    spawn_mode: SpawnMode::Top,
+                    boundary_behavior: BoundaryBehavior::Stick,
+                    sticky_edges: StickyEdges::Bottom,
+                    ..Default::default()
+                },
+                SeedPattern::Empty,
+                1.0,
+                3000,
+            ),
+            // Eden Blob - perimeter growth instead of diffusion
+            Preset::new(
+                "Eden Blob",
+                "Random perimeter sites fill in: a compact, rough-edged blob",
+                SimulationSettings {
+                    growth_model: GrowthModelType::Eden,
+                    ..Default::default()
+                },
+                SeedPattern::Point,
+                1.0,
+                5000,
+            ),
+            // Ballistic Rain - straight-line deposition on a floor
+            Preset::new(
+                "Ballistic Rain",
+                "Straight falling particles build a dense, porous deposit",
+                SimulationSettings {
+                    growth_model: GrowthModelType::Ballistic,
+                    spawn_mode: SpawnMode::Top,
+                    boundary_behavior: BoundaryBehavior::Stick,
+                    sticky_edges: StickyEdges::Bottom,
+                    ..Default::default()
+                },
+                SeedPattern::Empty,
+                1.0,
+                4000,
+            ),
+            // Lightning - dielectric breakdown with a strong field exponent
+            Preset::new(
+                "Lightning",
+                "Dielectric breakdown at eta 2: a few sharp, sparse discharge channels",
+                SimulationSettings {
+                    growth_model: GrowthModelType::Dielectric,
+                    dbm_eta: 2.0,
+                    neighborhood: NeighborhoodType::Moore,
+                    ..Default::default()
+                },
+                SeedPattern::Point,
+                1.0,
+                1500,
+            ),
+            // Alloy Dendrite - two species that prefer to bond with each other
+            Preset::new(
+                "Alloy Dendrite",
+                "Two species that stick readily to each other but poorly to their own kind",
+                SimulationSettings {
+                    species: vec![Species::default(), Species::default()],
+                    stick_matrix: vec![0.15, 1.0, 1.0, 0.15],
+                    neighborhood: NeighborhoodType::Moore,
+                    color_mode: ColorMode::Species,
+                    ..Default::default()
+                },
+                SeedPattern::Point,
+                1.0,
+                4000,
+            ),
+            // Two-Component Deposit - segregating species on a sticky floor
+            Preset::new(
+                "Two-Component Deposit",
+                "Two falling species, one drifting fast, that mostly stick to their own kind",
+                SimulationSettings {
+                    species: vec![
+                        Species {
+                            share: 0.6,
+                            spawn_mode: SpawnMode::Top,
+                            ..Default::default()
+                        },
+                        Species {
+                            share: 0.4,
+                            spawn_mode: SpawnMode::Top,
+                            bias_angle: 90.0,
+                            bias_strength: 0.3,
+                        },
+                    ],
+                    stick_matrix: vec![1.0, 0.3, 0.3, 1.0],
+                    boundary_behavior: BoundaryBehavior::Stick,
+                    sticky_edges: StickyEdges::Bottom,
+                    color_mode: ColorMode::Species,
+                    ..Default::default()
+                },
+                SeedPattern::Empty,
+                1.0,
+                3000,
+            ),
+            // Petri Dish - growth confined to a circular dish by a wall mask
+            Preset::new(
+                "Petri Dish",
+                "A cluster filling a round dish, with walkers released anywhere inside it",
+                SimulationSettings {
+                    spawn_mode: SpawnMode::Random,
+                    mask: Some(DomainMask::from_fn(PETRI_DISH_SIZE, PETRI_DISH_SIZE, |x, y| {
+                        let half = PETRI_DISH_SIZE as f32 / 2.0;
+                        let (dx, dy) = (x as f32 + 0.5 - half, y as f32 + 0.5 - half);
+                        if dx * dx + dy * dy < (half * 0.92).powi(2) {
+                            MaskCell::Open
+                        } else {
+                            MaskCell::Wall
+                        }
+                    })),
+                    ..Default::default()
+                },
+                SeedPattern::Point,
+                1.0,
+                5000,
+            ),
+            // Frost Word - feathery frost grown around the letters of a word
+            Preset::new(
+                "Frost Word",
+                "Frost growing outward from the word DLA written across the grid",
+                SimulationSettings {
+                    spawn_mode: SpawnMode::Random,
+                    ..Default::default()
+                },
+                SeedPattern::Text("DLA".to_string()),
+                0.7,
+                6000,
+            ),
         ];
     }
 
@@ -238,7 +390,10 @@ impl PresetManager {
                         if entry.path().extension().is_some_and(|e| e == "json") {
                             if let Ok(content) = fs::read_to_string(entry.path()) {
                                 if let Ok(preset) = serde_json::from_str::<Preset>(&content) {
-                                    self.user.push(preset);
+                                    match preset.settings.validate_stick_matrix() {
+                                        Ok(()) => self.user.push(preset),
+                                        Err(e) => eprintln!("Warning: Skipping preset '{}': {}", preset.name, e),
+                                    }
                                 }
                             }
                         }
@@ -1,14 +1,27 @@
 //! Recording functionality for capturing simulation as video/GIF.
 //!
-//! Supports two output modes:
+//! Supports these output modes:
 //! - MP4/WebM via FFmpeg (if installed)
 //! - GIF via native Rust (fallback)
+//! - Animated PNG or a numbered PNG sequence via native Rust (full colour)
+//!
+//! Live recordings encode on a background thread (`EncoderWorker`), so a slow
+//! encoder never holds up the TUI.
 
 use crate::color::ColorScheme;
+use crate::mask::MaskCell;
 use crate::settings::ColorMode;
-use crate::simulation::DlaSimulation;
-use std::io::Write;
+use crate::tree::GrowthTree;
+use crate::simulation::{DlaSimulation, ParticleData};
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 
 /// RGB frame buffer for video encoding
 pub struct RgbFrame {
@@ -33,28 +46,45 @@ pub enum OutputFormat {
     Mp4,
     WebM,
     Gif,
+    /// Lossless animated PNG
+    Apng,
+    /// One PNG file per frame in a directory
+    PngSequence,
 }
 
 impl OutputFormat {
-    /// Detect format from filename extension
+    /// Detect format from filename extension. A name ending in a path
+    /// separator, or naming an existing directory, is a PNG sequence target.
     pub fn from_filename(filename: &str) -> Self {
         let lower = filename.to_lowercase();
-        if lower.ends_with(".gif") {
+        if lower.ends_with('/') || lower.ends_with('\\') || Path::new(filename).is_dir() {
+            OutputFormat::PngSequence
+        } else if lower.ends_with(".gif") {
             OutputFormat::Gif
         } else if lower.ends_with(".webm") {
             OutputFormat::WebM
+        } else if lower.ends_with(".png") || lower.ends_with(".apng") {
+            OutputFormat::Apng
         } else {
             OutputFormat::Mp4
         }
     }
 
+    /// Extension appended to names without one ("" for directories)
     pub fn extension(&self) -> &'static str {
         match self {
             OutputFormat::Mp4 => ".mp4",
             OutputFormat::WebM => ".webm",
             OutputFormat::Gif => ".gif",
+            OutputFormat::Apng => ".png",
+            OutputFormat::PngSequence => "",
         }
     }
+
+    /// Whether the format is encoded by FFmpeg
+    pub fn needs_ffmpeg(&self) -> bool {
+        matches!(self, OutputFormat::Mp4 | OutputFormat::WebM)
+    }
 }
 
 /// Recording configuration
@@ -65,6 +95,14 @@ pub struct RecordingConfig {
     pub framerate: u32,
     /// Background color RGB
     pub background_color: (u8, u8, u8),
+    /// Theme highlight color, kept in GIF palettes
+    pub highlight_color: (u8, u8, u8),
+    /// Ordered dithering for GIF output
+    pub dither: bool,
+    /// Frames that can wait for the encoder thread (default: 32)
+    pub queue_capacity: usize,
+    /// What to do with a frame when the queue is full
+    pub queue_policy: QueuePolicy,
 }
 
 impl Default for RecordingConfig {
@@ -73,6 +111,32 @@ impl Default for RecordingConfig {
             pixel_scale: 4,
             framerate: 30,
             background_color: (0, 0, 0), // Black background
+            highlight_color: (255, 255, 255),
+            dither: false,
+            queue_capacity: 32,
+            queue_policy: QueuePolicy::default(),
+        }
+    }
+}
+
+/// What a recording does when the encoder falls behind and its queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueuePolicy {
+    /// Skip the frame, keeping the simulation at full speed
+    #[default]
+    Drop,
+    /// Wait for the encoder, keeping every frame
+    Block,
+}
+
+impl FromStr for QueuePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "drop" => Ok(QueuePolicy::Drop),
+            "block" | "wait" => Ok(QueuePolicy::Block),
+            _ => Err(format!("unknown queue policy: {}", s)),
         }
     }
 }
@@ -81,7 +145,7 @@ impl Default for RecordingConfig {
 pub enum RecordingState {
     Idle,
     Recording {
-        encoder: Box<dyn FrameEncoder>,
+        encoder: EncoderWorker,
         frame_count: usize,
         filename: String,
         /// Track frames for 30fps capture from 60fps loop
@@ -92,6 +156,9 @@ pub enum RecordingState {
 /// Trait for frame encoders (FFmpeg or GIF)
 pub trait FrameEncoder: Send {
     fn add_frame(&mut self, frame: &RgbFrame) -> Result<(), String>;
+    /// Colours the following frames are drawn from. Only palette-limited
+    /// encoders use it; calling it again with the same hint is cheap.
+    fn set_palette_hint(&mut self, _hint: &PaletteHint) {}
     fn finish(self: Box<Self>) -> Result<(), String>;
 }
 
@@ -122,14 +189,14 @@ impl FfmpegEncoder {
         format: OutputFormat,
     ) -> Result<Self, String> {
         if !Self::is_available() {
-            return Err("FFmpeg not found. Install FFmpeg or use .gif extension.".to_string());
+            return Err("FFmpeg not found. Install FFmpeg or use a .gif or .png extension.".to_string());
         }
 
         let codec_args: Vec<&str> = match format {
             OutputFormat::Mp4 => vec!["-c:v", "libx264", "-preset", "fast", "-crf", "23", "-pix_fmt", "yuv420p"],
             OutputFormat::WebM => vec!["-c:v", "libvpx-vp9", "-crf", "30", "-b:v", "0"],
-            OutputFormat::Gif => {
-                return Err("Use GifEncoder for GIF output".to_string());
+            OutputFormat::Gif | OutputFormat::Apng | OutputFormat::PngSequence => {
+                return Err("FFmpeg only encodes MP4 and WebM".to_string());
             }
         };
 
@@ -172,12 +239,131 @@ impl FrameEncoder for FfmpegEncoder {
     }
 }
 
-/// GIF encoder using the gif crate
+/// Ordered dithering thresholds (4x4 Bayer matrix, 0-15)
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// GIF palette index for pixels unchanged since the previous frame
+const TRANSPARENT_INDEX: u8 = 0;
+
+/// Colours a recording is expected to contain. Encoders limited to a
+/// palette (GIF) build one from these instead of from each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteHint {
+    pub color_scheme: ColorScheme,
+    pub background: (u8, u8, u8),
+    /// Theme highlight colour
+    pub highlight: (u8, u8, u8),
+    /// Ordered dithering for colours that fall between palette entries
+    pub dither: bool,
+}
+
+/// A fixed 256-colour GIF palette (entry 0 is transparent) with a cache of
+/// nearest-entry lookups
+struct GifPalette {
+    colors: Vec<[u8; 3]>,
+    lookup: HashMap<[u8; 3], u8>,
+    /// Dither amplitude per channel (0 = off)
+    spread: f32,
+}
+
+impl GifPalette {
+    /// The fixed colours the renderer draws (background, highlight, white for
+    /// uncoloured particles, mask shades), then the scheme's gradient sampled
+    /// evenly over the remaining entries
+    fn from_hint(hint: &PaletteHint) -> Self {
+        let mut colors = vec![[0; 3]];
+        let mask = [MaskCell::Wall, MaskCell::Sink, MaskCell::NoStick].map(|cell| cell.shade());
+        for (r, g, b) in [hint.background, hint.highlight, (255, 255, 255)].into_iter().chain(mask.into_iter().flatten()) {
+            colors.push([r, g, b]);
+        }
+        let steps = 256 - colors.len();
+        let gradient: Vec<[u8; 3]> = (0..steps)
+            .map(|i| {
+                let (r, g, b) = hint.color_scheme.map_rgb(i as f32 / (steps - 1) as f32);
+                [r, g, b]
+            })
+            .collect();
+        // Dither across one gradient step, the usual gap between a colour and its nearest entries
+        let step = gradient
+            .windows(2)
+            .map(|w| (0..3).map(|c| w[0][c].abs_diff(w[1][c])).max().unwrap_or(0) as f32)
+            .sum::<f32>()
+            / (steps - 1) as f32;
+        colors.extend(gradient);
+        Self::new(colors, if hint.dither { step.max(1.0) } else { 0.0 })
+    }
+
+    /// A 6x6x6 colour cube, for frames of unknown colours
+    fn uniform() -> Self {
+        let mut colors = vec![[0; 3]];
+        for r in 0..6 {
+            for g in 0..6 {
+                for b in 0..6 {
+                    colors.push([r * 51, g * 51, b * 51]);
+                }
+            }
+        }
+        Self::new(colors, 0.0)
+    }
+
+    fn new(colors: Vec<[u8; 3]>, spread: f32) -> Self {
+        Self {
+            colors,
+            lookup: HashMap::new(),
+            spread,
+        }
+    }
+
+    /// Palette index for a pixel at (x, y), never the transparent entry
+    fn index(&mut self, rgb: [u8; 3], x: u32, y: u32) -> u8 {
+        let rgb = if self.spread > 0.0 {
+            let t = (BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as f32 + 0.5) / 16.0 - 0.5;
+            rgb.map(|c| (c as f32 + t * self.spread).round().clamp(0.0, 255.0) as u8)
+        } else {
+            rgb
+        };
+        let colors = &self.colors;
+        *self.lookup.entry(rgb).or_insert_with(|| {
+            (1..colors.len())
+                .min_by_key(|&i| {
+                    (0..3)
+                        .map(|c| (colors[i][c] as i32 - rgb[c] as i32).pow(2) as u32)
+                        .sum::<u32>()
+                })
+                .unwrap_or(1) as u8
+        })
+    }
+
+    /// RGB bytes of all 256 entries
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.colors.iter().flatten().copied().collect();
+        bytes.resize(256 * 3, 0);
+        bytes
+    }
+}
+
+/// GIF encoder using the gif crate.
+///
+/// Frames share one palette, built from the `PaletteHint` (or a uniform
+/// colour cube without one). Each frame only stores the rectangle that changed
+/// since the previous one, with unchanged pixels inside it transparent, and
+/// frames with no change lengthen the previous frame instead.
 pub struct GifEncoder {
-    encoder: gif::Encoder<std::fs::File>,
+    /// Output file until the first frame writes the header with the global palette
+    file: Option<std::fs::File>,
+    encoder: Option<gif::Encoder<std::fs::File>>,
     width: u16,
     height: u16,
     frame_delay: u16, // In centiseconds
+    hint: Option<PaletteHint>,
+    palette: GifPalette,
+    /// Whether `palette` is the global one in the header; later palettes are
+    /// attached to every frame
+    palette_is_global: bool,
+    /// Pixels of the previous frame, to find what changed
+    previous: Option<Vec<u8>>,
+    /// Last frame, held back so that unchanged frames can lengthen it
+    pending: Option<gif::Frame<'static>>,
 }
 
 impl GifEncoder {
@@ -186,118 +372,440 @@ impl GifEncoder {
         let file = std::fs::File::create(filename)
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
-        let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &[])
-            .map_err(|e| format!("Failed to create GIF encoder: {}", e))?;
-
-        encoder
-            .set_repeat(gif::Repeat::Infinite)
-            .map_err(|e| format!("Failed to set repeat: {}", e))?;
-
         // Convert FPS to centiseconds delay
         let frame_delay = (100 / fps).max(1) as u16;
 
         Ok(Self {
-            encoder,
+            file: Some(file),
+            encoder: None,
             width: width as u16,
             height: height as u16,
             frame_delay,
+            hint: None,
+            palette: GifPalette::uniform(),
+            palette_is_global: true,
+            previous: None,
+            pending: None,
         })
     }
 
-    /// Simple color quantization using median cut-like approach
-    fn quantize_frame(pixels: &[u8]) -> (Vec<u8>, Vec<u8>) {
-        // Build a simple 256-color palette from the image
-        use std::collections::HashMap;
-
-        // Count color frequencies
-        let mut color_counts: HashMap<(u8, u8, u8), usize> = HashMap::new();
-        for chunk in pixels.chunks_exact(3) {
-            let color = (chunk[0], chunk[1], chunk[2]);
-            *color_counts.entry(color).or_insert(0) += 1;
-        }
-
-        // Get most frequent colors (up to 256)
-        let mut colors: Vec<_> = color_counts.into_iter().collect();
-        colors.sort_by(|a, b| b.1.cmp(&a.1));
-        colors.truncate(256);
-
-        // Build palette
-        let mut palette = Vec::with_capacity(256 * 3);
-        for (color, _) in &colors {
-            palette.push(color.0);
-            palette.push(color.1);
-            palette.push(color.2);
+    /// Write the header with the current palette as the global one, once
+    fn encoder(&mut self) -> Result<&mut gif::Encoder<std::fs::File>, String> {
+        if let Some(file) = self.file.take() {
+            let mut encoder = gif::Encoder::new(file, self.width, self.height, &self.palette.bytes())
+                .map_err(|e| format!("Failed to create GIF encoder: {}", e))?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(|e| format!("Failed to set repeat: {}", e))?;
+            self.encoder = Some(encoder);
         }
+        self.encoder.as_mut().ok_or_else(|| "GIF already finished".to_string())
+    }
 
-        // Pad palette to 256 colors
-        while palette.len() < 256 * 3 {
-            palette.push(0);
+    fn write_pending(&mut self) -> Result<(), String> {
+        if let Some(frame) = self.pending.take() {
+            self.encoder()?
+                .write_frame(&frame)
+                .map_err(|e| format!("Failed to write GIF frame: {}", e))?;
         }
+        Ok(())
+    }
+}
 
-        // Build color lookup for fast indexing
-        let color_to_idx: HashMap<(u8, u8, u8), u8> = colors
-            .iter()
-            .enumerate()
-            .map(|(i, (c, _))| (*c, i as u8))
-            .collect();
-
-        // Map pixels to indices
-        let mut indices = Vec::with_capacity(pixels.len() / 3);
-        for chunk in pixels.chunks_exact(3) {
-            let color = (chunk[0], chunk[1], chunk[2]);
-            let idx = color_to_idx.get(&color).copied().unwrap_or_else(|| {
-                // Find closest color in palette
-                let mut best_idx = 0u8;
-                let mut best_dist = u32::MAX;
-                for (i, (c, _)) in colors.iter().enumerate() {
-                    let dr = (color.0 as i32 - c.0 as i32).unsigned_abs();
-                    let dg = (color.1 as i32 - c.1 as i32).unsigned_abs();
-                    let db = (color.2 as i32 - c.2 as i32).unsigned_abs();
-                    let dist = dr * dr + dg * dg + db * db;
-                    if dist < best_dist {
-                        best_dist = dist;
-                        best_idx = i as u8;
-                    }
-                }
-                best_idx
-            });
-            indices.push(idx);
+/// Bounding box (x0, y0, x1, y1, inclusive) of the pixels that differ
+/// between two RGB frames of the given width, or None if they are identical
+fn changed_rect(previous: &[u8], pixels: &[u8], width: u32) -> Option<(u32, u32, u32, u32)> {
+    let row_len = width as usize * 3;
+    let mut rect: Option<(u32, u32, u32, u32)> = None;
+    for (y, (old, new)) in previous.chunks_exact(row_len).zip(pixels.chunks_exact(row_len)).enumerate() {
+        if old == new {
+            continue;
         }
-
-        (indices, palette)
+        let differs = |x: &usize| old[x * 3..x * 3 + 3] != new[x * 3..x * 3 + 3];
+        let first = (0..width as usize).find(differs).unwrap_or(0) as u32;
+        let last = (0..width as usize).rev().find(differs).unwrap_or(0) as u32;
+        let y = y as u32;
+        rect = Some(match rect {
+            None => (first, y, last, y),
+            Some((x0, y0, x1, _)) => (x0.min(first), y0, x1.max(last), y),
+        });
     }
+    rect
 }
 
 impl FrameEncoder for GifEncoder {
     fn add_frame(&mut self, frame: &RgbFrame) -> Result<(), String> {
-        let (indices, palette) = Self::quantize_frame(&frame.pixels);
+        // Fix the global palette before the first frame is indexed against it
+        self.encoder()?;
+
+        let rect = match &self.previous {
+            Some(previous) => changed_rect(previous, &frame.pixels, frame.width),
+            None => Some((0, 0, frame.width - 1, frame.height - 1)),
+        };
+        let Some((x0, y0, x1, y1)) = rect else {
+            if let Some(pending) = &mut self.pending {
+                pending.delay = pending.delay.saturating_add(self.frame_delay);
+            }
+            return Ok(());
+        };
+
+        let mut indices = Vec::with_capacity(((x1 - x0 + 1) * (y1 - y0 + 1)) as usize);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let i = ((y * frame.width + x) * 3) as usize;
+                let rgb = [frame.pixels[i], frame.pixels[i + 1], frame.pixels[i + 2]];
+                let unchanged = self.previous.as_ref().is_some_and(|p| p[i..i + 3] == rgb);
+                indices.push(if unchanged {
+                    TRANSPARENT_INDEX
+                } else {
+                    self.palette.index(rgb, x, y)
+                });
+            }
+        }
 
         let gif_frame = gif::Frame {
-            width: self.width,
-            height: self.height,
+            left: x0 as u16,
+            top: y0 as u16,
+            width: (x1 - x0 + 1) as u16,
+            height: (y1 - y0 + 1) as u16,
             delay: self.frame_delay,
+            dispose: gif::DisposalMethod::Keep,
+            transparent: Some(TRANSPARENT_INDEX),
             buffer: std::borrow::Cow::Owned(indices),
-            palette: Some(palette),
+            palette: (!self.palette_is_global).then(|| self.palette.bytes()),
             ..Default::default()
         };
+        self.write_pending()?;
+        self.pending = Some(gif_frame);
+        match &mut self.previous {
+            Some(previous) => previous.copy_from_slice(&frame.pixels),
+            None => self.previous = Some(frame.pixels.clone()),
+        }
+        Ok(())
+    }
 
-        self.encoder
-            .write_frame(&gif_frame)
-            .map_err(|e| format!("Failed to write GIF frame: {}", e))
+    fn set_palette_hint(&mut self, hint: &PaletteHint) {
+        if self.hint == Some(*hint) {
+            return;
+        }
+        self.hint = Some(*hint);
+        self.palette = GifPalette::from_hint(hint);
+        if self.encoder.is_some() {
+            // Too late for the header: send the new palette with every later
+            // frame, starting with a full one
+            self.palette_is_global = false;
+            self.previous = None;
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), String> {
+        self.encoder()?;
+        self.write_pending()?;
+        if let Some(encoder) = self.encoder.take() {
+            encoder
+                .into_inner()
+                .map_err(|e| format!("Failed to finish GIF: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Write one frame as an RGB PNG
+fn write_png<W: Write>(out: W, frame: &RgbFrame, compression: png::Compression) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(out, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(compression);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&frame.pixels)?;
+    writer.finish()
+}
+
+/// Numbered PNG files (`frame_00000.png`, ...) in a directory
+pub struct PngSequenceEncoder {
+    dir: PathBuf,
+    next: usize,
+}
+
+impl PngSequenceEncoder {
+    /// Create the encoder, making the directory if needed
+    pub fn new(dir: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            next: 0,
+        })
+    }
+}
+
+impl FrameEncoder for PngSequenceEncoder {
+    fn add_frame(&mut self, frame: &RgbFrame) -> Result<(), String> {
+        let path = self.dir.join(format!("frame_{:05}.png", self.next));
+        let file = std::fs::File::create(&path).map_err(|e| format!("Failed to create file: {}", e))?;
+        write_png(BufWriter::new(file), frame, png::Compression::Fast)
+            .map_err(|e| format!("Failed to write PNG frame: {}", e))?;
+        self.next += 1;
+        Ok(())
     }
 
     fn finish(self: Box<Self>) -> Result<(), String> {
-        // Encoder finalizes on drop
         Ok(())
     }
 }
 
+/// Lossless animated PNG.
+///
+/// APNG stores the frame count in its header, so frames are kept in memory
+/// as quickly compressed PNGs and the file is written by `finish`. Runs of
+/// identical frames (paused simulation, replay hold) become one longer frame.
+pub struct ApngEncoder {
+    file: std::fs::File,
+    width: u32,
+    height: u32,
+    fps: u16,
+    /// Compressed frames and how many recorded frames each one lasts
+    frames: Vec<(Vec<u8>, u16)>,
+    last: Vec<u8>,
+}
+
+impl ApngEncoder {
+    /// Create a new APNG encoder
+    pub fn new(filename: &str, width: u32, height: u32, fps: u32) -> Result<Self, String> {
+        let file = std::fs::File::create(filename)
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+        Ok(Self {
+            file,
+            width,
+            height,
+            fps: fps.clamp(1, u16::MAX as u32) as u16,
+            frames: Vec::new(),
+            last: Vec::new(),
+        })
+    }
+}
+
+impl FrameEncoder for ApngEncoder {
+    fn add_frame(&mut self, frame: &RgbFrame) -> Result<(), String> {
+        if let Some((_, count)) = self.frames.last_mut() {
+            if *count < u16::MAX && frame.pixels == self.last {
+                *count += 1;
+                return Ok(());
+            }
+        }
+        let mut compressed = Vec::new();
+        write_png(&mut compressed, frame, png::Compression::Fast)
+            .map_err(|e| format!("Failed to encode APNG frame: {}", e))?;
+        self.frames.push((compressed, 1));
+        self.last.clone_from(&frame.pixels);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        if self.frames.is_empty() {
+            return Ok(());
+        }
+        let err = |e: png::EncodingError| format!("Failed to write APNG: {}", e);
+        let mut encoder = png::Encoder::new(BufWriter::new(self.file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Balanced);
+        encoder.set_animated(self.frames.len() as u32, 0).map_err(err)?;
+        let mut writer = encoder.write_header().map_err(err)?;
+
+        let mut pixels = vec![0; (self.width * self.height * 3) as usize];
+        for (compressed, count) in &self.frames {
+            png::Decoder::new(std::io::Cursor::new(compressed))
+                .read_info()
+                .and_then(|mut reader| reader.next_frame(&mut pixels))
+                .map_err(|e| format!("Failed to read APNG frame: {}", e))?;
+            writer.set_frame_delay(*count, self.fps).map_err(err)?;
+            writer.write_image_data(&pixels).map_err(err)?;
+        }
+        writer.finish().map_err(err)
+    }
+}
+
+/// Open an encoder for `filename`, picked by its extension (MP4 when it has
+/// none, which is then appended). MP4/WebM fall back to GIF without FFmpeg.
+/// A directory target gets a PNG sequence. Returns the encoder and the name
+/// of the file or directory it writes.
+pub fn create_encoder(
+    filename: String,
+    width: u32,
+    height: u32,
+    fps: u32,
+) -> Result<(Box<dyn FrameEncoder>, String), String> {
+    let format = OutputFormat::from_filename(&filename);
+
+    // Native PNG formats are only picked by their own extension or a directory
+    match format {
+        OutputFormat::PngSequence => {
+            let encoder = PngSequenceEncoder::new(&filename)?;
+            return Ok((Box::new(encoder), filename));
+        }
+        OutputFormat::Apng => {
+            let encoder = ApngEncoder::new(&filename, width, height, fps)?;
+            return Ok((Box::new(encoder), filename));
+        }
+        _ => {}
+    }
+
+    // Ensure filename has correct extension
+    let filename = if !filename.to_lowercase().ends_with(format.extension()) {
+        format!("{}{}", filename, format.extension())
+    } else {
+        filename
+    };
+
+    // Try FFmpeg for video, falling back to GIF
+    if format.needs_ffmpeg() && FfmpegEncoder::is_available() {
+        let encoder = FfmpegEncoder::new(&filename, width, height, fps, format)?;
+        return Ok((Box::new(encoder), filename));
+    }
+    let filename = filename.replace(".mp4", ".gif").replace(".webm", ".gif");
+    let encoder = GifEncoder::new(&filename, width, height, fps)?;
+    Ok((Box::new(encoder), filename))
+}
+
+/// Work sent to an encoder thread
+enum EncoderJob {
+    Frame(RgbFrame),
+    Palette(PaletteHint),
+}
+
+/// Runs a `FrameEncoder` on its own thread, fed through a bounded queue.
+/// Encoded frames come back to be rendered into again, so a recording
+/// allocates at most queue capacity + 2 frame buffers.
+pub struct EncoderWorker {
+    sender: Option<SyncSender<EncoderJob>>,
+    handle: Option<JoinHandle<Result<(), String>>>,
+    recycled: Receiver<RgbFrame>,
+    /// Buffer of the last dropped frame
+    spare: Option<RgbFrame>,
+    /// Frames sent but not yet encoded
+    queued: Arc<AtomicUsize>,
+    dropped: usize,
+    policy: QueuePolicy,
+    hint: Option<PaletteHint>,
+}
+
+impl EncoderWorker {
+    /// Start a thread encoding into `encoder`, queueing up to `capacity` frames
+    pub fn spawn(mut encoder: Box<dyn FrameEncoder>, capacity: usize, policy: QueuePolicy) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<EncoderJob>(capacity.max(1));
+        let (recycle, recycled) = mpsc::channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let pending = Arc::clone(&queued);
+        let handle = std::thread::spawn(move || {
+            for job in receiver {
+                match job {
+                    EncoderJob::Palette(hint) => encoder.set_palette_hint(&hint),
+                    EncoderJob::Frame(frame) => {
+                        let result = encoder.add_frame(&frame);
+                        pending.fetch_sub(1, Ordering::Relaxed);
+                        let _ = recycle.send(frame);
+                        result?;
+                    }
+                }
+            }
+            // The queue closes once every frame sent has been received
+            encoder.finish()
+        });
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+            recycled,
+            spare: None,
+            queued,
+            dropped: 0,
+            policy,
+            hint: None,
+        }
+    }
+
+    /// Frames waiting to be encoded
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Frames skipped because the queue was full
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// A frame buffer to render into, reused from earlier frames when possible
+    pub fn buffer(&mut self, width: u32, height: u32) -> RgbFrame {
+        self.spare
+            .take()
+            .or_else(|| self.recycled.try_recv().ok())
+            .filter(|f| f.width == width && f.height == height)
+            .unwrap_or_else(|| RgbFrame::new(width, height))
+    }
+
+    /// Pass a palette hint to the encoder, ahead of the frames that follow.
+    /// Unchanged hints aren't resent.
+    pub fn set_palette_hint(&mut self, hint: &PaletteHint) -> Result<(), String> {
+        if self.hint == Some(*hint) {
+            return Ok(());
+        }
+        self.hint = Some(*hint);
+        let sender = self.sender.as_ref().ok_or("Encoder already finished")?;
+        if sender.send(EncoderJob::Palette(*hint)).is_err() {
+            return Err(self.worker_error());
+        }
+        Ok(())
+    }
+
+    /// Queue a frame for encoding. Returns false if the queue was full and
+    /// the policy dropped it.
+    pub fn push(&mut self, frame: RgbFrame) -> Result<bool, String> {
+        let sender = self.sender.as_ref().ok_or("Encoder already finished")?;
+        // Count before sending so the worker never finishes a frame not yet counted
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        let result = match self.policy {
+            QueuePolicy::Block => sender.send(EncoderJob::Frame(frame)).map_err(|_| ()),
+            QueuePolicy::Drop => match sender.try_send(EncoderJob::Frame(frame)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(EncoderJob::Frame(frame))) => {
+                    self.queued.fetch_sub(1, Ordering::Relaxed);
+                    self.dropped += 1;
+                    self.spare = Some(frame);
+                    return Ok(false);
+                }
+                Err(_) => Err(()),
+            },
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(()) => Err(self.worker_error()),
+        }
+    }
+
+    /// Close the queue, wait for the remaining frames to be encoded and
+    /// finalize the output
+    pub fn finish(mut self) -> Result<(), String> {
+        self.sender = None;
+        match self.handle.take() {
+            Some(handle) => handle.join().map_err(|_| "Encoder thread panicked".to_string())?,
+            None => Ok(()),
+        }
+    }
+
+    /// The error that stopped the worker, once its queue has disconnected
+    fn worker_error(&mut self) -> String {
+        self.sender = None;
+        match self.handle.take().map(|h| h.join()) {
+            Some(Ok(Err(e))) => e,
+            Some(Err(_)) => "Encoder thread panicked".to_string(),
+            _ => "Encoder stopped".to_string(),
+        }
+    }
+}
+
 /// Main recorder struct
 pub struct Recorder {
     pub state: RecordingState,
     pub config: RecordingConfig,
-    /// Reusable frame buffer to avoid allocations
-    frame_buffer: Option<RgbFrame>,
     /// Video dimensions (locked when recording starts)
     video_width: u32,
     video_height: u32,
@@ -308,7 +816,6 @@ impl Default for Recorder {
         Self {
             state: RecordingState::Idle,
             config: RecordingConfig::default(),
-            frame_buffer: None,
             video_width: 0,
             video_height: 0,
         }
@@ -333,6 +840,14 @@ impl Recorder {
         }
     }
 
+    /// Frames waiting for the encoder and frames dropped so far (if recording)
+    pub fn queue_stats(&self) -> Option<(usize, usize)> {
+        match &self.state {
+            RecordingState::Recording { encoder, .. } => Some((encoder.queued(), encoder.dropped())),
+            _ => None,
+        }
+    }
+
     /// Start recording
     pub fn start(
         &mut self,
@@ -352,50 +867,9 @@ impl Recorder {
         self.video_width = (self.video_width / 2) * 2;
         self.video_height = (self.video_height / 2) * 2;
 
-        let format = OutputFormat::from_filename(&filename);
-
-        // Ensure filename has correct extension
-        let filename = if !filename.to_lowercase().ends_with(format.extension()) {
-            format!("{}{}", filename, format.extension())
-        } else {
-            filename
-        };
-
-        // Create encoder
-        let encoder: Box<dyn FrameEncoder> = match format {
-            OutputFormat::Gif => {
-                Box::new(GifEncoder::new(
-                    &filename,
-                    self.video_width,
-                    self.video_height,
-                    self.config.framerate,
-                )?)
-            }
-            _ => {
-                // Try FFmpeg first, fall back to GIF
-                if FfmpegEncoder::is_available() {
-                    Box::new(FfmpegEncoder::new(
-                        &filename,
-                        self.video_width,
-                        self.video_height,
-                        self.config.framerate,
-                        format,
-                    )?)
-                } else {
-                    // Fall back to GIF
-                    let gif_filename = filename.replace(".mp4", ".gif").replace(".webm", ".gif");
-                    Box::new(GifEncoder::new(
-                        &gif_filename,
-                        self.video_width,
-                        self.video_height,
-                        self.config.framerate,
-                    )?)
-                }
-            }
-        };
-
-        // Allocate frame buffer
-        self.frame_buffer = Some(RgbFrame::new(self.video_width, self.video_height));
+        let (encoder, filename) =
+            create_encoder(filename, self.video_width, self.video_height, self.config.framerate)?;
+        let encoder = EncoderWorker::spawn(encoder, self.config.queue_capacity, self.config.queue_policy);
 
         self.state = RecordingState::Recording {
             encoder,
@@ -407,7 +881,7 @@ impl Recorder {
         Ok(())
     }
 
-    /// Stop recording and finalize the file
+    /// Stop recording, wait for the queued frames to be encoded and finalize the file
     pub fn stop(&mut self) -> Result<String, String> {
         let state = std::mem::replace(&mut self.state, RecordingState::Idle);
 
@@ -415,9 +889,13 @@ impl Recorder {
             RecordingState::Recording {
                 encoder, filename, frame_count, ..
             } => {
+                let dropped = encoder.dropped();
                 encoder.finish()?;
-                self.frame_buffer = None;
-                Ok(format!("Saved {} frames to {}", frame_count, filename))
+                if dropped > 0 {
+                    Ok(format!("Saved {} frames to {} ({} dropped)", frame_count, filename, dropped))
+                } else {
+                    Ok(format!("Saved {} frames to {}", frame_count, filename))
+                }
             }
             RecordingState::Idle => Err("Not recording".to_string()),
         }
@@ -433,7 +911,7 @@ impl Recorder {
         }
     }
 
-    /// Capture and encode a frame
+    /// Render a frame and queue it for the encoder thread
     pub fn capture_frame(
         &mut self,
         simulation: &DlaSimulation,
@@ -442,17 +920,22 @@ impl Recorder {
         color_mode: ColorMode,
         invert_colors: bool,
     ) -> Result<(), String> {
-        if !self.is_recording() {
+        let (width, height) = (self.video_width, self.video_height);
+        let RecordingState::Recording {
+            encoder,
+            frame_count,
+            ..
+        } = &mut self.state
+        else {
             return Ok(());
-        }
-
-        // Take frame buffer temporarily to avoid borrow conflicts
-        let mut frame = self.frame_buffer.take().ok_or("No frame buffer")?;
+        };
 
         // Render simulation to frame
+        let mut frame = encoder.buffer(width, height);
         Self::render_frame_static(
             &mut frame,
             simulation,
+            usize::MAX,
             color_scheme,
             color_by_age,
             color_mode,
@@ -461,28 +944,28 @@ impl Recorder {
             self.config.background_color,
         );
 
-        // Encode frame
-        if let RecordingState::Recording {
-            encoder,
-            frame_count,
-            ..
-        } = &mut self.state
-        {
-            encoder.add_frame(&frame)?;
+        // The scheme can change mid-recording
+        encoder.set_palette_hint(&PaletteHint {
+            color_scheme: *color_scheme,
+            background: self.config.background_color,
+            highlight: self.config.highlight_color,
+            dither: self.config.dither,
+        })?;
+        // Hand the frame to the encoder thread
+        if encoder.push(frame)? {
             *frame_count += 1;
         }
 
-        // Put frame buffer back
-        self.frame_buffer = Some(frame);
-
         Ok(())
     }
 
-    /// Render simulation state to RGB frame buffer (static version to avoid borrow issues)
+    /// Render simulation state to RGB frame buffer (static version to avoid borrow issues).
+    /// Only particles up to `max_age` are drawn (usize::MAX for all of them).
     #[allow(clippy::too_many_arguments)]
-    fn render_frame_static(
+    pub(crate) fn render_frame_static(
         frame: &mut RgbFrame,
         simulation: &DlaSimulation,
+        max_age: usize,
         color_scheme: &ColorScheme,
         color_by_age: bool,
         color_mode: ColorMode,
@@ -496,6 +979,8 @@ impl Recorder {
         // Pre-calculate for color mapping
         let inv_num_particles = 1.0 / simulation.num_particles.max(1) as f32;
         let max_radius = simulation.max_radius.max(1.0);
+        let species_count = simulation.settings.species_count();
+        let tree = color_mode.uses_tree().then(|| GrowthTree::new(simulation.discs()));
 
         // Fill with background
         for chunk in frame.pixels.chunks_exact_mut(3) {
@@ -504,27 +989,80 @@ impl Recorder {
             chunk[2] = bg.2;
         }
 
+        // Dim the walls, sinks and no-stick zones of a domain mask
+        if simulation.has_mask() {
+            for sim_y in 0..sim_height {
+                for sim_x in 0..sim_width {
+                    let Some(shade) = simulation.mask_cell(sim_x, sim_y).shade() else {
+                        continue;
+                    };
+                    for vy in sim_y as u32 * scale..((sim_y as u32 + 1) * scale).min(frame.height) {
+                        for vx in sim_x as u32 * scale..((sim_x as u32 + 1) * scale).min(frame.width) {
+                            let idx = ((vy * frame.width + vx) * 3) as usize;
+                            frame.pixels[idx] = shade.0;
+                            frame.pixels[idx + 1] = shade.1;
+                            frame.pixels[idx + 2] = shade.2;
+                        }
+                    }
+                }
+            }
+        }
+
+        // `disc` is the particle's index in the disc list, for the tree modes
+        let particle_color = |particle: &ParticleData, disc: Option<usize>| {
+            // Calculate color value based on mode
+            let value = match color_mode {
+                ColorMode::Age => particle.age as f32 * inv_num_particles,
+                ColorMode::Distance => particle.distance / max_radius,
+                ColorMode::Density => particle.neighbor_count as f32 / 8.0,
+                ColorMode::Direction => {
+                    (particle.direction + std::f32::consts::PI) / std::f32::consts::TAU
+                }
+                ColorMode::Origin => {
+                    let age = particle.age as f32 * inv_num_particles * 0.5;
+                    if particle.deposit { 0.5 + age } else { age }
+                }
+                ColorMode::Cluster => particle.cluster_shade(),
+                ColorMode::Species => particle.species_shade(species_count),
+                ColorMode::TreeDepth | ColorMode::Strahler | ColorMode::Descendants => tree
+                    .as_ref()
+                    .zip(disc)
+                    .map_or(0.0, |(tree, i)| tree.shade(i, color_mode)),
+            };
+
+            // Get RGB color
+            let t = if invert_colors { 1.0 - value } else { value };
+            if color_by_age {
+                color_scheme.map_rgb(t)
+            } else {
+                (255, 255, 255)
+            }
+        };
+
+        // Off-lattice particles are drawn as discs at their exact positions
+        if simulation.settings.off_lattice {
+            let (width, height) = (frame.width, frame.height);
+            let s = scale as f32;
+            simulation.for_each_disc_pixel(s, s, width, height, |vx, vy, i, particle| {
+                if particle.age > max_age {
+                    return;
+                }
+                let color = particle_color(particle, Some(i));
+                let idx = ((vy * width + vx) * 3) as usize;
+                frame.pixels[idx] = color.0;
+                frame.pixels[idx + 1] = color.1;
+                frame.pixels[idx + 2] = color.2;
+            });
+            return;
+        }
+
         // Render each simulation pixel
         for sim_y in 0..sim_height {
             for sim_x in 0..sim_width {
-                if let Some(particle) = simulation.get_particle(sim_x, sim_y) {
-                    // Calculate color value based on mode
-                    let value = match color_mode {
-                        ColorMode::Age => particle.age as f32 * inv_num_particles,
-                        ColorMode::Distance => particle.distance / max_radius,
-                        ColorMode::Density => particle.neighbor_count as f32 / 8.0,
-                        ColorMode::Direction => {
-                            (particle.direction + std::f32::consts::PI) / std::f32::consts::TAU
-                        }
-                    };
-
-                    // Get RGB color
-                    let t = if invert_colors { 1.0 - value } else { value };
-                    let color = if color_by_age {
-                        color_scheme.map_rgb(t)
-                    } else {
-                        (255, 255, 255)
-                    };
+                // A cell shows its oldest particle, so a hidden one hides the cell
+                if let Some(particle) = simulation.get_particle(sim_x, sim_y).filter(|p| p.age <= max_age) {
+                    let disc = if tree.is_some() { simulation.disc_at(sim_x, sim_y) } else { None };
+                    let color = particle_color(&particle, disc);
 
                     // Write pixel block (scale x scale)
                     for py in 0..scale {
@@ -547,3 +1085,156 @@ impl Recorder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, value: u8) -> RgbFrame {
+        RgbFrame {
+            width,
+            height,
+            pixels: vec![value; (width * height * 3) as usize],
+        }
+    }
+
+    #[test]
+    fn test_png_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_name = dir.path().to_str().unwrap();
+        assert!(OutputFormat::from_filename(dir_name) == OutputFormat::PngSequence);
+        assert!(OutputFormat::from_filename("frames/") == OutputFormat::PngSequence);
+        assert!(OutputFormat::from_filename("run.APNG") == OutputFormat::Apng);
+        assert!(OutputFormat::from_filename("run") == OutputFormat::Mp4);
+
+        // One file per frame in the sequence directory
+        let seq = dir.path().join("seq/");
+        let (mut encoder, _) = create_encoder(seq.to_str().unwrap().to_string(), 4, 2, 10).unwrap();
+        for v in [0, 100] {
+            encoder.add_frame(&solid(4, 2, v)).unwrap();
+        }
+        encoder.finish().unwrap();
+        assert!(seq.join("frame_00000.png").is_file() && seq.join("frame_00001.png").is_file());
+
+        // Repeated frames merge into one longer APNG frame
+        let path = dir.path().join("run.png");
+        let (mut encoder, name) = create_encoder(path.to_str().unwrap().to_string(), 4, 2, 10).unwrap();
+        assert_eq!(name, path.to_str().unwrap());
+        for v in [0, 200, 200, 200] {
+            encoder.add_frame(&solid(4, 2, v)).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let mut reader = png::Decoder::new(std::io::BufReader::new(std::fs::File::open(&path).unwrap()))
+            .read_info()
+            .unwrap();
+        assert_eq!(reader.info().animation_control.unwrap().num_frames, 2);
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert!(pixels.iter().all(|&p| p == 0));
+        reader.next_frame(&mut pixels).unwrap();
+        assert!(pixels.iter().all(|&p| p == 200));
+        assert_eq!(reader.info().frame_control.unwrap().delay_num, 3);
+    }
+
+    #[test]
+    fn test_gif_delta_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.gif");
+        let hint = PaletteHint {
+            color_scheme: ColorScheme::Fire,
+            background: (10, 20, 30),
+            highlight: (255, 204, 0),
+            dither: false,
+        };
+        let (mut encoder, _) = create_encoder(path.to_str().unwrap().to_string(), 8, 6, 10).unwrap();
+        encoder.set_palette_hint(&hint);
+
+        let mut frame = RgbFrame::new(8, 6);
+        for chunk in frame.pixels.chunks_exact_mut(3) {
+            chunk.copy_from_slice(&[10, 20, 30]);
+        }
+        encoder.add_frame(&frame).unwrap();
+        let (r, g, b) = ColorScheme::Fire.map_rgb(0.5);
+        for (x, y) in [(2, 1), (3, 1), (2, 2), (3, 2)] {
+            let i = (y * 8 + x) * 3;
+            frame.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+        }
+        encoder.add_frame(&frame).unwrap();
+        encoder.add_frame(&frame).unwrap();
+        frame.pixels[(5 * 8 + 7) * 3..].copy_from_slice(&[255, 255, 255]);
+        encoder.add_frame(&frame).unwrap();
+        encoder.finish().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(std::fs::File::open(&path).unwrap()).unwrap();
+        let first = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!((first.width, first.height), (8, 6));
+        assert_eq!(&first.buffer[..4], &[10, 20, 30, 255]);
+
+        // Only the changed square is stored, and the repeat doubles its delay
+        let second = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!((second.left, second.top, second.width, second.height), (2, 1, 2, 2));
+        assert_eq!(second.delay, 20);
+        for px in second.buffer.chunks_exact(4) {
+            assert!(px[3] == 255 && px[..3].iter().zip([r, g, b]).all(|(&a, b)| a.abs_diff(b) <= 4));
+        }
+        let third = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!((third.left, third.top, third.width, third.height), (7, 5, 1, 1));
+        assert!(decoder.read_next_frame().unwrap().is_none());
+
+        // Dithering spreads a colour between entries over several of them
+        let mut palette = GifPalette::from_hint(&PaletteHint { dither: true, ..hint });
+        let between = ColorScheme::Fire.map_rgb(0.503);
+        let [r, g, b] = [between.0, between.1, between.2];
+        let indices: std::collections::HashSet<u8> =
+            (0..16).map(|i| palette.index([r, g, b], i % 4, i / 4)).collect();
+        assert!(indices.len() >= 2 && !indices.contains(&TRANSPARENT_INDEX));
+    }
+
+    /// Counts frames, signalling when one arrives and then waiting for the gate
+    struct Gated {
+        started: mpsc::Sender<()>,
+        gate: Receiver<()>,
+        done: Arc<AtomicUsize>,
+    }
+
+    impl FrameEncoder for Gated {
+        fn add_frame(&mut self, _frame: &RgbFrame) -> Result<(), String> {
+            let _ = self.started.send(());
+            let _ = self.gate.recv();
+            self.done.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn finish(self: Box<Self>) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_worker_drops_when_full() {
+        let (started, started_rx) = mpsc::channel();
+        let (gate_tx, gate) = mpsc::channel();
+        let done = Arc::new(AtomicUsize::new(0));
+        let encoder = Gated {
+            started,
+            gate,
+            done: Arc::clone(&done),
+        };
+        let mut worker = EncoderWorker::spawn(Box::new(encoder), 1, QueuePolicy::Drop);
+
+        // One frame being encoded and one waiting fill a queue of one
+        assert!(worker.push(RgbFrame::new(2, 2)).unwrap());
+        started_rx.recv().unwrap();
+        assert!(worker.push(RgbFrame::new(2, 2)).unwrap());
+        assert!(!worker.push(RgbFrame::new(2, 2)).unwrap());
+        assert_eq!((worker.queued(), worker.dropped()), (2, 1));
+
+        // Finishing waits for both queued frames
+        drop(gate_tx);
+        worker.finish().unwrap();
+        assert_eq!(done.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,275 @@
+//! Offline replay: re-animate a finished cluster in stick (age) order and
+//! encode it through the recorder's `FrameEncoder`s.
+//!
+//! Unlike live recording, the video's pace comes only from the replay
+//! options, not from the simulation speed or the terminal size.
+
+use crate::color::ColorScheme;
+use crate::recorder::{self, FrameEncoder, PaletteHint, Recorder, RgbFrame};
+use crate::simulation::DlaSimulation;
+use crate::snapshot::MAX_SNAPSHOT_SCALE;
+use std::str::FromStr;
+
+/// Video pixels per simulation cell when no resolution is given
+pub const DEFAULT_REPLAY_SCALE: u32 = 4;
+
+/// How the particles shown grow over the frames of a replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrowthCurve {
+    /// The same number of new particles every frame
+    #[default]
+    Linear,
+    /// Few particles per frame at first, speeding up towards the end
+    EaseIn,
+    /// The particle count grows by the same factor every frame, so the
+    /// early growth around the seed gets as much time as the late branches
+    Log,
+}
+
+impl GrowthCurve {
+    pub fn name(&self) -> &str {
+        match self {
+            GrowthCurve::Linear => "Linear",
+            GrowthCurve::EaseIn => "Ease-in",
+            GrowthCurve::Log => "Log",
+        }
+    }
+
+    /// Particles shown (1..=total) a fraction `t` (0-1) of the way through
+    pub fn shown(&self, t: f32, total: usize) -> usize {
+        let n = total as f32;
+        let shown = match self {
+            GrowthCurve::Linear => t * n,
+            GrowthCurve::EaseIn => t * t * n,
+            GrowthCurve::Log => n.powf(t),
+        };
+        (shown.round() as usize).clamp(1, total.max(1))
+    }
+}
+
+impl FromStr for GrowthCurve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" | "lin" => Ok(GrowthCurve::Linear),
+            "ease-in" | "easein" | "ease" => Ok(GrowthCurve::EaseIn),
+            "log" | "logarithmic" => Ok(GrowthCurve::Log),
+            _ => Err(format!("unknown growth curve: {}", s)),
+        }
+    }
+}
+
+/// Replay video settings
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Output size in pixels (0 = grid size times DEFAULT_REPLAY_SCALE).
+    /// The grid is scaled to fit and centred, and odd sizes are rounded down
+    /// to even for the video codecs.
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Seconds from the seed to the finished cluster
+    pub duration: f32,
+    /// Seconds the finished cluster stays on screen at the end
+    pub hold: f32,
+    pub curve: GrowthCurve,
+    pub color_scheme: ColorScheme,
+    pub color_by_age: bool,
+    pub background: (u8, u8, u8),
+    /// Theme highlight colour, kept in GIF palettes
+    pub highlight: (u8, u8, u8),
+    /// Ordered dithering for GIF output
+    pub dither: bool,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            fps: 30,
+            duration: 10.0,
+            hold: 2.0,
+            curve: GrowthCurve::default(),
+            color_scheme: ColorScheme::default(),
+            color_by_age: true,
+            background: (0, 0, 0),
+            highlight: (255, 255, 255),
+            dither: false,
+        }
+    }
+}
+
+impl ReplayOptions {
+    /// Output size for a simulation, after defaults and even rounding
+    pub fn frame_size(&self, simulation: &DlaSimulation) -> (u32, u32) {
+        let (w, h) = if self.width == 0 || self.height == 0 {
+            (
+                simulation.grid_width as u32 * DEFAULT_REPLAY_SCALE,
+                simulation.grid_height as u32 * DEFAULT_REPLAY_SCALE,
+            )
+        } else {
+            (self.width, self.height)
+        };
+        ((w / 2 * 2).max(2), (h / 2 * 2).max(2))
+    }
+}
+
+/// Newest age shown in each growth frame, for `frames` frames along `curve`.
+/// The last frame shows the whole cluster.
+pub fn frame_ages(simulation: &DlaSimulation, frames: usize, curve: GrowthCurve) -> Vec<usize> {
+    let mut ages: Vec<usize> = simulation.discs().iter().map(|d| d.data.age).collect();
+    if ages.is_empty() {
+        return vec![0; frames];
+    }
+    ages.sort_unstable();
+    (1..=frames)
+        .map(|k| ages[curve.shown(k as f32 / frames as f32, ages.len()) - 1])
+        .collect()
+}
+
+/// Replay a cluster into `encoder`. Returns the number of frames written.
+pub fn render_replay(
+    simulation: &DlaSimulation,
+    options: &ReplayOptions,
+    encoder: &mut dyn FrameEncoder,
+) -> Result<usize, String> {
+    let fps = options.fps.max(1);
+    let (width, height) = options.frame_size(simulation);
+    let (gw, gh) = (simulation.grid_width as f32, simulation.grid_height as f32);
+
+    // Render at the smallest whole scale at least as large as the output,
+    // then sample it into the centred, aspect-preserving output rectangle
+    let fit = (width as f32 / gw).min(height as f32 / gh);
+    let scale = (fit.ceil() as u32).clamp(1, MAX_SNAPSHOT_SCALE);
+    let mut source = RgbFrame::new(simulation.grid_width as u32 * scale, simulation.grid_height as u32 * scale);
+    let (fitted_w, fitted_h) = ((gw * fit) as u32, (gh * fit) as u32);
+    let (x0, y0) = ((width - fitted_w) / 2, (height - fitted_h) / 2);
+    let ratio = scale as f32 / fit;
+    let columns: Vec<u32> = (0..fitted_w)
+        .map(|x| ((x as f32 * ratio) as u32).min(source.width - 1))
+        .collect();
+    let rows: Vec<u32> = (0..fitted_h)
+        .map(|y| ((y as f32 * ratio) as u32).min(source.height - 1))
+        .collect();
+
+    encoder.set_palette_hint(&PaletteHint {
+        color_scheme: options.color_scheme,
+        background: options.background,
+        highlight: options.highlight,
+        dither: options.dither,
+    });
+
+    let mut frame = RgbFrame::new(width, height);
+    for chunk in frame.pixels.chunks_exact_mut(3) {
+        chunk.copy_from_slice(&[options.background.0, options.background.1, options.background.2]);
+    }
+
+    let growth_frames = ((options.duration * fps as f32).round() as usize).max(1);
+    let hold_frames = (options.hold.max(0.0) * fps as f32).round() as usize;
+    let ages = frame_ages(simulation, growth_frames, options.curve);
+    let mut written = 0;
+    let mut last_age = None;
+    for age in ages {
+        // Curves that add no particles in a frame repeat the previous image
+        if last_age != Some(age) {
+            Recorder::render_frame_static(
+                &mut source,
+                simulation,
+                age,
+                &options.color_scheme,
+                options.color_by_age,
+                simulation.settings.color_mode,
+                simulation.settings.invert_colors,
+                scale,
+                options.background,
+            );
+            for (y, &sy) in rows.iter().enumerate() {
+                for (x, &sx) in columns.iter().enumerate() {
+                    let src = ((sy * source.width + sx) * 3) as usize;
+                    let dst = (((y0 + y as u32) * width + x0 + x as u32) * 3) as usize;
+                    frame.pixels[dst..dst + 3].copy_from_slice(&source.pixels[src..src + 3]);
+                }
+            }
+            last_age = Some(age);
+        }
+        encoder.add_frame(&frame)?;
+        written += 1;
+    }
+    for _ in 0..hold_frames {
+        encoder.add_frame(&frame)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Replay a cluster into a video or GIF file, picking the encoder by
+/// extension like live recording. Returns the file written and its frame count.
+pub fn save_replay(
+    filename: &str,
+    simulation: &DlaSimulation,
+    options: &ReplayOptions,
+) -> Result<(String, usize), String> {
+    let (width, height) = options.frame_size(simulation);
+    let (mut encoder, filename) = recorder::create_encoder(filename.to_string(), width, height, options.fps.max(1))?;
+    let frames = render_replay(simulation, options, encoder.as_mut())?;
+    encoder.finish()?;
+    Ok((filename, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::SeedPattern;
+
+    /// Keeps the frames it is given
+    struct Collect(Vec<Vec<u8>>);
+
+    impl FrameEncoder for Collect {
+        fn add_frame(&mut self, frame: &RgbFrame) -> Result<(), String> {
+            self.0.push(frame.pixels.clone());
+            Ok(())
+        }
+
+        fn finish(self: Box<Self>) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replay_frames_follow_curve() {
+        let mut sim = DlaSimulation::new(60, 40);
+        sim.rng_seed = Some(3);
+        sim.num_particles = 200;
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+
+        for curve in [GrowthCurve::Linear, GrowthCurve::EaseIn, GrowthCurve::Log] {
+            let ages = frame_ages(&sim, 20, curve);
+            assert!(ages.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(*ages.last().unwrap(), sim.latest_age());
+        }
+        // Ease-in and log both hold back early growth compared to linear
+        let linear = frame_ages(&sim, 20, GrowthCurve::Linear);
+        assert!(frame_ages(&sim, 20, GrowthCurve::EaseIn)[4] < linear[4]);
+        assert!(frame_ages(&sim, 20, GrowthCurve::Log)[4] < linear[4]);
+
+        // 1s of growth plus a 0.5s hold at 10fps, letterboxed into 200x100
+        let options = ReplayOptions {
+            width: 201,
+            height: 100,
+            fps: 10,
+            duration: 1.0,
+            hold: 0.5,
+            ..Default::default()
+        };
+        let mut out = Collect(Vec::new());
+        assert_eq!(render_replay(&sim, &options, &mut out).unwrap(), 15);
+        assert!(out.0.iter().all(|f| f.len() == 200 * 100 * 3));
+        assert_ne!(out.0[0], out.0[9]);
+        assert_eq!(out.0[9], out.0[14]);
+        // 60x40 fits 200x100 at 2.5x, leaving 25px bars at the sides
+        assert_eq!(&out.0[14][..3], &[0, 0, 0]);
+    }
+}
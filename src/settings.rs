@@ -1,6 +1,76 @@
+use crate::mask::DomainMask;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+/// Growth rule deciding where each new particle joins the cluster
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum GrowthModelType {
+    /// Diffusion-limited aggregation: random walkers stick on first contact
+    #[default]
+    Dla,
+    /// Eden growth: a random empty site on the cluster perimeter is filled - compact blobs
+    Eden,
+    /// Ballistic aggregation: particles fly in straight lines from the spawn source - dense, porous deposits
+    Ballistic,
+    /// Reaction-limited aggregation: random walkers touch the cluster many times before sticking
+    ReactionLimited,
+    /// Dielectric breakdown: perimeter sites grow with probability proportional to the field strength to the power eta
+    Dielectric,
+    /// Cluster-cluster aggregation: free particles and whole clusters random-walk and merge on contact
+    ClusterCluster,
+}
+
+impl GrowthModelType {
+    pub fn name(&self) -> &str {
+        match self {
+            GrowthModelType::Dla => "DLA",
+            GrowthModelType::Eden => "Eden",
+            GrowthModelType::Ballistic => "Ballistic",
+            GrowthModelType::ReactionLimited => "RLA",
+            GrowthModelType::Dielectric => "DBM",
+            GrowthModelType::ClusterCluster => "DLCA",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            GrowthModelType::Dla => GrowthModelType::Eden,
+            GrowthModelType::Eden => GrowthModelType::Ballistic,
+            GrowthModelType::Ballistic => GrowthModelType::ReactionLimited,
+            GrowthModelType::ReactionLimited => GrowthModelType::Dielectric,
+            GrowthModelType::Dielectric => GrowthModelType::ClusterCluster,
+            GrowthModelType::ClusterCluster => GrowthModelType::Dla,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            GrowthModelType::Dla => GrowthModelType::ClusterCluster,
+            GrowthModelType::Eden => GrowthModelType::Dla,
+            GrowthModelType::Ballistic => GrowthModelType::Eden,
+            GrowthModelType::ReactionLimited => GrowthModelType::Ballistic,
+            GrowthModelType::Dielectric => GrowthModelType::ReactionLimited,
+            GrowthModelType::ClusterCluster => GrowthModelType::Dielectric,
+        }
+    }
+}
+
+impl FromStr for GrowthModelType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dla" | "diffusion" => Ok(GrowthModelType::Dla),
+            "eden" => Ok(GrowthModelType::Eden),
+            "ballistic" | "ba" => Ok(GrowthModelType::Ballistic),
+            "rla" | "reaction" | "reaction-limited" => Ok(GrowthModelType::ReactionLimited),
+            "dbm" | "dielectric" => Ok(GrowthModelType::Dielectric),
+            "dlca" | "cluster" | "cluster-cluster" => Ok(GrowthModelType::ClusterCluster),
+            _ => Err(format!("unknown growth model: {}", s)),
+        }
+    }
+}
+
 /// Neighborhood type for sticking checks
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub enum NeighborhoodType {
@@ -38,6 +108,15 @@ impl NeighborhoodType {
         }
     }
 
+    /// Distance to the farthest neighbor offset (how far away a stuck particle can be felt)
+    pub fn reach(&self) -> f32 {
+        match self {
+            NeighborhoodType::VonNeumann => 1.0,
+            NeighborhoodType::Moore => std::f32::consts::SQRT_2,
+            NeighborhoodType::Extended => 2.0 * std::f32::consts::SQRT_2,
+        }
+    }
+
     /// Get the neighbor offsets for this neighborhood type
     pub fn offsets(&self) -> &'static [(i32, i32)] {
         match self {
@@ -161,7 +240,7 @@ pub enum BoundaryBehavior {
     Wrap,
     /// Reflect off edges
     Bounce,
-    /// Particles stick to edges
+    /// Edges (those selected by sticky_edges) act as a substrate particles deposit on
     Stick,
     /// Particles are removed/respawned at edges (canonical DLA)
     #[default]
@@ -215,6 +294,94 @@ impl FromStr for BoundaryBehavior {
     }
 }
 
+/// Which grid edges act as a substrate under the Stick boundary
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum StickyEdges {
+    /// All four edges
+    #[default]
+    All,
+    /// Top edge only
+    Top,
+    /// Bottom edge only
+    Bottom,
+    /// Left edge only
+    Left,
+    /// Right edge only
+    Right,
+    /// Top and bottom edges
+    TopBottom,
+    /// Left and right edges
+    LeftRight,
+}
+
+impl StickyEdges {
+    pub fn name(&self) -> &str {
+        match self {
+            StickyEdges::All => "All",
+            StickyEdges::Top => "Top",
+            StickyEdges::Bottom => "Bottom",
+            StickyEdges::Left => "Left",
+            StickyEdges::Right => "Right",
+            StickyEdges::TopBottom => "TopBottom",
+            StickyEdges::LeftRight => "LeftRight",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            StickyEdges::All => StickyEdges::Top,
+            StickyEdges::Top => StickyEdges::Bottom,
+            StickyEdges::Bottom => StickyEdges::Left,
+            StickyEdges::Left => StickyEdges::Right,
+            StickyEdges::Right => StickyEdges::TopBottom,
+            StickyEdges::TopBottom => StickyEdges::LeftRight,
+            StickyEdges::LeftRight => StickyEdges::All,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            StickyEdges::All => StickyEdges::LeftRight,
+            StickyEdges::Top => StickyEdges::All,
+            StickyEdges::Bottom => StickyEdges::Top,
+            StickyEdges::Left => StickyEdges::Bottom,
+            StickyEdges::Right => StickyEdges::Left,
+            StickyEdges::TopBottom => StickyEdges::Right,
+            StickyEdges::LeftRight => StickyEdges::TopBottom,
+        }
+    }
+
+    /// Whether each edge is sticky, as (left, right, top, bottom)
+    pub fn sides(&self) -> (bool, bool, bool, bool) {
+        match self {
+            StickyEdges::All => (true, true, true, true),
+            StickyEdges::Top => (false, false, true, false),
+            StickyEdges::Bottom => (false, false, false, true),
+            StickyEdges::Left => (true, false, false, false),
+            StickyEdges::Right => (false, true, false, false),
+            StickyEdges::TopBottom => (false, false, true, true),
+            StickyEdges::LeftRight => (true, true, false, false),
+        }
+    }
+}
+
+impl FromStr for StickyEdges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(StickyEdges::All),
+            "top" => Ok(StickyEdges::Top),
+            "bottom" => Ok(StickyEdges::Bottom),
+            "left" => Ok(StickyEdges::Left),
+            "right" => Ok(StickyEdges::Right),
+            "topbottom" | "top-bottom" => Ok(StickyEdges::TopBottom),
+            "leftright" | "left-right" => Ok(StickyEdges::LeftRight),
+            _ => Err(format!("unknown sticky edges: {}", s)),
+        }
+    }
+}
+
 /// Color mode - what property determines particle color
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub enum ColorMode {
@@ -227,6 +394,18 @@ pub enum ColorMode {
     Density,
     /// Color by approach direction (angle)
     Direction,
+    /// Seed-grown particles in the lower half of the gradient, wall deposits in the upper half
+    Origin,
+    /// A distinct shade per cluster (cluster-cluster aggregation)
+    Cluster,
+    /// An equal band of the gradient per species (multi-species runs)
+    Species,
+    /// Chemical distance from the seed along the growth tree
+    TreeDepth,
+    /// Strahler order of the branch the particle sits on
+    Strahler,
+    /// Number of particles that grew from this one (log scale)
+    Descendants,
 }
 
 impl ColorMode {
@@ -236,6 +415,12 @@ impl ColorMode {
             ColorMode::Distance => "Distance",
             ColorMode::Density => "Density",
             ColorMode::Direction => "Direction",
+            ColorMode::Origin => "Origin",
+            ColorMode::Cluster => "Cluster",
+            ColorMode::Species => "Species",
+            ColorMode::TreeDepth => "Tree Depth",
+            ColorMode::Strahler => "Strahler",
+            ColorMode::Descendants => "Descendants",
         }
     }
 
@@ -244,18 +429,35 @@ impl ColorMode {
             ColorMode::Age => ColorMode::Distance,
             ColorMode::Distance => ColorMode::Density,
             ColorMode::Density => ColorMode::Direction,
-            ColorMode::Direction => ColorMode::Age,
+            ColorMode::Direction => ColorMode::Origin,
+            ColorMode::Origin => ColorMode::Cluster,
+            ColorMode::Cluster => ColorMode::Species,
+            ColorMode::Species => ColorMode::TreeDepth,
+            ColorMode::TreeDepth => ColorMode::Strahler,
+            ColorMode::Strahler => ColorMode::Descendants,
+            ColorMode::Descendants => ColorMode::Age,
         }
     }
 
     pub fn prev(&self) -> Self {
         match self {
-            ColorMode::Age => ColorMode::Direction,
+            ColorMode::Age => ColorMode::Descendants,
             ColorMode::Distance => ColorMode::Age,
             ColorMode::Density => ColorMode::Distance,
             ColorMode::Direction => ColorMode::Density,
+            ColorMode::Origin => ColorMode::Direction,
+            ColorMode::Cluster => ColorMode::Origin,
+            ColorMode::Species => ColorMode::Cluster,
+            ColorMode::TreeDepth => ColorMode::Species,
+            ColorMode::Strahler => ColorMode::TreeDepth,
+            ColorMode::Descendants => ColorMode::Strahler,
         }
     }
+
+    /// Whether the mode colours by the growth tree (see `tree::GrowthTree`)
+    pub fn uses_tree(&self) -> bool {
+        matches!(self, ColorMode::TreeDepth | ColorMode::Strahler | ColorMode::Descendants)
+    }
 }
 
 impl FromStr for ColorMode {
@@ -267,15 +469,115 @@ impl FromStr for ColorMode {
             "distance" | "dist" => Ok(ColorMode::Distance),
             "density" | "dens" => Ok(ColorMode::Density),
             "direction" | "dir" => Ok(ColorMode::Direction),
+            "origin" | "deposit" => Ok(ColorMode::Origin),
+            "cluster" => Ok(ColorMode::Cluster),
+            "species" => Ok(ColorMode::Species),
+            "depth" | "tree-depth" | "chemical" => Ok(ColorMode::TreeDepth),
+            "strahler" | "order" => Ok(ColorMode::Strahler),
+            "descendants" | "desc" => Ok(ColorMode::Descendants),
             _ => Err(format!("unknown color mode: {}", s)),
         }
     }
 }
 
+/// Most species a multi-species run can hold
+pub const MAX_SPECIES: usize = 8;
+
+/// One kind of particle in a multi-species run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Species {
+    /// Relative share of walkers that are this species
+    pub share: f32,
+    /// Where walkers of this species spawn from
+    pub spawn_mode: SpawnMode,
+    /// Bias angle in degrees for this species' drift (0-360)
+    pub bias_angle: f32,
+    /// Strength of this species' directional bias (0.0-0.5, 0 = isotropic)
+    pub bias_strength: f32,
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Self {
+            share: 1.0,
+            spawn_mode: SpawnMode::default(),
+            bias_angle: 0.0,
+            bias_strength: 0.0,
+        }
+    }
+}
+
+impl FromStr for Species {
+    type Err = String;
+
+    /// Parse `SHARE[:SPAWN[:ANGLE[:STRENGTH]]]`, e.g. `0.3:top:90:0.2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':').map(str::trim);
+        let number = |part: &str, what: &str| {
+            part.parse::<f32>()
+                .map_err(|_| format!("invalid species {} '{}' in '{}'", what, part, s))
+        };
+        let mut species = Species {
+            share: number(parts.next().unwrap_or_default(), "share")?.max(0.0),
+            ..Default::default()
+        };
+        if let Some(part) = parts.next() {
+            species.spawn_mode = part.parse()?;
+        }
+        if let Some(part) = parts.next() {
+            species.bias_angle = number(part, "bias angle")?.rem_euclid(360.0);
+        }
+        if let Some(part) = parts.next() {
+            species.bias_strength = number(part, "bias strength")?.clamp(0.0, 0.5);
+        }
+        if parts.next().is_some() {
+            return Err(format!("too many fields in species '{}'", s));
+        }
+        Ok(species)
+    }
+}
+
+/// Parse a square stick matrix written row by row, e.g. `0.1,1;1,0.1`.
+/// Entries are clamped to 0-1; an empty string gives an empty matrix.
+pub fn parse_stick_matrix(s: &str) -> Result<Vec<f32>, String> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows: Vec<&str> = s.split(';').collect();
+    let mut matrix = Vec::with_capacity(rows.len() * rows.len());
+    for row in &rows {
+        let entries: Vec<&str> = row.split(',').map(str::trim).collect();
+        if entries.len() != rows.len() {
+            return Err(format!(
+                "stick matrix must be square: row '{}' has {} entries, expected {}",
+                row,
+                entries.len(),
+                rows.len()
+            ));
+        }
+        for entry in entries {
+            let value: f32 = entry
+                .parse()
+                .map_err(|_| format!("invalid stick matrix entry '{}'", entry))?;
+            matrix.push(value.clamp(0.0, 1.0));
+        }
+    }
+    Ok(matrix)
+}
+
 /// All simulation settings consolidated into one struct
+/// Missing fields in older config/preset files fall back to the defaults.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationSettings {
     // === Movement Parameters ===
+    /// Growth rule (DLA, Eden, ballistic, reaction-limited, dielectric breakdown or cluster-cluster)
+    pub growth_model: GrowthModelType,
+    /// Dielectric breakdown exponent eta (0.0-4.0, 0 = Eden-like, 1 = DLA-like, >1 = needles)
+    pub dbm_eta: f32,
+    /// Cluster-cluster mobility exponent (0.0-2.0): a cluster of s particles moves at s^-exponent
+    pub mobility_exponent: f32,
     /// Distance particles move per random walk step (0.5-5.0)
     pub walk_step_size: f32,
     /// Bias angle in degrees for directional drift (0-360)
@@ -290,6 +592,11 @@ pub struct SimulationSettings {
     pub adaptive_step_factor: f32,
     /// Use pure lattice walk (4 cardinal directions) instead of continuous angles
     pub lattice_walk: bool,
+    /// Jump across empty space using the distance-to-cluster map (unbiased
+    /// continuous walks only; lattice walks ignore it)
+    pub distance_jumps: bool,
+    /// Off-lattice mode: particles are discs in continuous space that stick on contact
+    pub off_lattice: bool,
 
     // === Sticking Parameters ===
     /// Neighborhood type for checking adjacent particles
@@ -303,11 +610,20 @@ pub struct SimulationSettings {
     /// Stickiness variation by distance from center (-0.5 to 0.5 per 100px)
     pub stickiness_gradient: f32,
 
+    // === Species Parameters ===
+    /// Particle species, up to MAX_SPECIES (empty = one species using the spawn mode and walk bias)
+    pub species: Vec<Species>,
+    /// Sticking chance for each pair of walker species (row) and stuck species (column),
+    /// N×N row-major for N species, or empty for all 1.0.
+    pub stick_matrix: Vec<f32>,
+
     // === Spawn/Boundary Parameters ===
     /// Where particles spawn from
     pub spawn_mode: SpawnMode,
     /// What happens at grid boundaries
     pub boundary_behavior: BoundaryBehavior,
+    /// Edges that particles deposit on when the boundary is Stick
+    pub sticky_edges: StickyEdges,
     /// Buffer distance between structure edge and spawn circle (5-50)
     pub spawn_radius_offset: f32,
     /// Multiplier for escape distance (2.0-6.0)
@@ -316,6 +632,8 @@ pub struct SimulationSettings {
     pub min_spawn_radius: f32,
     /// Maximum walk iterations before respawn (1000-50000)
     pub max_walk_iterations: usize,
+    /// Walls, sinks and no-stick zones stretched over the grid (None = open grid)
+    pub mask: Option<DomainMask>,
 
     // === Visual Parameters ===
     /// What property determines particle color
@@ -332,6 +650,9 @@ impl Default for SimulationSettings {
     fn default() -> Self {
         Self {
             // Movement - canonical DLA uses unit lattice steps
+            growth_model: GrowthModelType::default(),
+            dbm_eta: 1.0,
+            mobility_exponent: 0.5,
             walk_step_size: 1.0,
             walk_bias_angle: 0.0,
             walk_bias_strength: 0.0,
@@ -339,6 +660,8 @@ impl Default for SimulationSettings {
             adaptive_step: false, // Disabled by default for accurate DLA
             adaptive_step_factor: 3.0,
             lattice_walk: true, // Classic 4-direction lattice walk
+            distance_jumps: false, // Opt-in: changes the RNG sequence of seeded runs
+            off_lattice: false,
 
             // Sticking
             neighborhood: NeighborhoodType::default(), // VonNeumann (4-neighbor)
@@ -347,13 +670,19 @@ impl Default for SimulationSettings {
             side_stickiness: 1.0,
             stickiness_gradient: 0.0,
 
+            // Species - a single species by default
+            species: Vec::new(),
+            stick_matrix: Vec::new(),
+
             // Spawn/Boundary - unbounded-space behavior
             spawn_mode: SpawnMode::default(), // Circle
             boundary_behavior: BoundaryBehavior::Absorb, // Respawn at edges for unbounded feel
+            sticky_edges: StickyEdges::default(),
             spawn_radius_offset: 10.0,
             escape_multiplier: 3.0, // Higher multiplier reduces premature respawns
             min_spawn_radius: 15.0, // Lower for faster small-cluster convergence
             max_walk_iterations: 10000,
+            mask: None,
 
             // Visual
             color_mode: ColorMode::default(),
@@ -410,6 +739,16 @@ impl SimulationSettings {
         self.spawn_radius_offset = (self.spawn_radius_offset + delta).clamp(5.0, 50.0);
     }
 
+    /// Adjust dielectric breakdown eta within bounds
+    pub fn adjust_dbm_eta(&mut self, delta: f32) {
+        self.dbm_eta = (self.dbm_eta + delta).clamp(0.0, 4.0);
+    }
+
+    /// Adjust cluster mobility exponent within bounds
+    pub fn adjust_mobility_exponent(&mut self, delta: f32) {
+        self.mobility_exponent = (self.mobility_exponent + delta).clamp(0.0, 2.0);
+    }
+
     /// Adjust escape multiplier within bounds
     pub fn adjust_escape_multiplier(&mut self, delta: f32) {
         self.escape_multiplier = (self.escape_multiplier + delta).clamp(2.0, 6.0);
@@ -446,6 +785,16 @@ impl SimulationSettings {
         self.lattice_walk = !self.lattice_walk;
     }
 
+    /// Toggle distance-field jumps on/off
+    pub fn toggle_distance_jumps(&mut self) {
+        self.distance_jumps = !self.distance_jumps;
+    }
+
+    /// Toggle off-lattice (disc) mode on/off
+    pub fn toggle_off_lattice(&mut self) {
+        self.off_lattice = !self.off_lattice;
+    }
+
     /// Cycle min brightness through preset values
     pub fn cycle_min_brightness(&mut self) {
         const PRESETS: [f32; 4] = [0.0, 0.15, 0.30, 0.45];
@@ -458,6 +807,69 @@ impl SimulationSettings {
         self.min_brightness = PRESETS[next_idx];
     }
 
+    /// Number of particle species in play (at least one)
+    pub fn species_count(&self) -> usize {
+        self.species.len().clamp(1, MAX_SPECIES)
+    }
+
+    /// Definition of a species. Without a species list, the single species
+    /// uses the global spawn mode and walk bias.
+    pub fn species_def(&self, index: u8) -> Species {
+        match self.species.get(index as usize) {
+            Some(species) if (index as usize) < MAX_SPECIES => *species,
+            _ => Species {
+                share: 1.0,
+                spawn_mode: self.spawn_mode,
+                bias_angle: self.walk_bias_angle,
+                bias_strength: self.walk_bias_strength,
+            },
+        }
+    }
+
+    /// Species of a new walker for a uniform roll in 0-1, weighted by share
+    pub fn pick_species(&self, roll: f32) -> u8 {
+        let species = &self.species[..self.species_count().min(self.species.len())];
+        let total: f32 = species.iter().map(|s| s.share.max(0.0)).sum();
+        if total <= 0.0 {
+            return 0;
+        }
+        let mut target = roll * total;
+        for (i, s) in species.iter().enumerate() {
+            target -= s.share.max(0.0);
+            if target < 0.0 {
+                return i as u8;
+            }
+        }
+        (species.len() - 1) as u8
+    }
+
+    /// Check that a stick matrix has one entry per species pair
+    pub fn validate_stick_matrix(&self) -> Result<(), String> {
+        let n = self.species_count();
+        if self.stick_matrix.is_empty() || self.stick_matrix.len() == n * n {
+            return Ok(());
+        }
+        Err(format!(
+            "stick matrix has {} entries, but {} species need {}x{} = {}",
+            self.stick_matrix.len(),
+            n,
+            n,
+            n,
+            n * n
+        ))
+    }
+
+    /// Sticking chance multiplier for a walker of one species touching a stuck particle of another
+    pub fn pair_stickiness(&self, walker: u8, stuck: u8) -> f32 {
+        let n = self.species_count();
+        if walker as usize >= n || stuck as usize >= n {
+            return 1.0;
+        }
+        self.stick_matrix
+            .get(walker as usize * n + stuck as usize)
+            .map_or(1.0, |v| v.clamp(0.0, 1.0))
+    }
+
     /// Calculate effective stickiness based on neighbor count and distance
     pub fn effective_stickiness(&self, neighbor_count: usize, distance_from_center: f32, base_stickiness: f32) -> f32 {
         // Determine if this is a tip (few neighbors) or side (many neighbors)
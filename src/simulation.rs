@@ -1,12 +1,50 @@
-use crate::settings::{BoundaryBehavior, SimulationSettings, SpawnMode};
-use rand::rngs::ThreadRng;
-use rand::Rng;
+use crate::custom_seed::CustomSeed;
+use crate::mask::MaskCell;
+use crate::settings::{BoundaryBehavior, GrowthModelType, NeighborhoodType, SimulationSettings, SpawnMode, Species};
+use crate::spatial::SpatialHash;
+use crate::state::{SimulationState, StoredParticle, STATE_VERSION};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+mod clusters;
+mod growth;
+mod imported;
+mod paint;
+mod timeline;
+
+pub use paint::PaintTool;
 
 // Simulation boundary and spawn constants
 const BOUNDARY_MARGIN: f32 = 1.0;
 /// Extra buffer beyond max_radius for spawn circle (20% expansion)
 const SPAWN_RADIUS_EXPANSION: f32 = 1.2;
+/// Tries at finding a Random spawn point outside the spawn radius before
+/// settling for the last one
+const RANDOM_SPAWN_ATTEMPTS: usize = 64;
+
+// Distance field constants
+/// Cap on the distance-to-cluster map (cells). Larger values allow longer jumps
+/// near the cluster but make each stick more expensive to record.
+const MAX_FIELD_DISTANCE: u8 = 24;
+/// Slack subtracted from the clearance before jumping: covers the walker's
+/// offset within its cell (~0.71) on both ends of the jump, plus rounding
+const JUMP_MARGIN: f32 = 2.0;
+
+// Parallel walking constants
+/// Walkers advanced per parallel batch (fixed so results don't depend on thread count)
+pub const PARALLEL_BATCH: usize = 128;
+/// Extra distance beyond the neighborhood reach within which two sticks in one batch clash
+const CONFLICT_MARGIN: f32 = 1.5;
+
+// Off-lattice constants
+/// Radius of an off-lattice particle in cells
+pub const DISC_RADIUS: f32 = 0.5;
+/// Centre-to-centre distance at which two discs touch
+const DISC_CONTACT: f32 = 2.0 * DISC_RADIUS;
+/// Discs whose centres lie within this distance of a contact point count as its neighbors
+const DISC_NEIGHBOR_RADIUS: f32 = DISC_CONTACT + 0.5;
 
 // Seed pattern geometry constants
 /// Ring thickness for the ring seed pattern
@@ -25,7 +63,7 @@ const NOISE_CORE_DENSITY: f32 = 0.35;
 const NOISE_EDGE_DENSITY: f32 = 0.65;
 
 /// Seed pattern types for initial structure
-#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub enum SeedPattern {
     #[default]
     Point,
@@ -38,6 +76,14 @@ pub enum SeedPattern {
     Scatter,
     MultiPoint,
     Starburst,
+    /// No seed: growth starts only from sticky walls
+    Empty,
+    /// Dark pixels of an image file, scaled to fit the middle of the grid
+    Image(PathBuf),
+    /// A string drawn in the built-in bitmap font across the middle of the grid
+    Text(String),
+    /// Seed particles and walls painted on the canvas, stretched over the grid
+    Custom(CustomSeed),
 }
 
 impl SeedPattern {
@@ -53,6 +99,20 @@ impl SeedPattern {
             SeedPattern::Scatter => "Scatter",
             SeedPattern::MultiPoint => "Multi-Point",
             SeedPattern::Starburst => "Starburst",
+            SeedPattern::Empty => "Empty",
+            SeedPattern::Image(_) => "Image",
+            SeedPattern::Text(_) => "Text",
+            SeedPattern::Custom(seed) => &seed.name,
+        }
+    }
+
+    /// Check that an imported seed can be built: the image must be readable
+    /// and the text must draw something. The built-in patterns always can.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            SeedPattern::Image(path) => imported::Bitmap::load_image(path).map(|_| ()),
+            SeedPattern::Text(text) if text.trim().is_empty() => Err("Seed text is empty".to_string()),
+            _ => Ok(()),
         }
     }
 
@@ -67,13 +127,19 @@ impl SeedPattern {
             SeedPattern::NoisePatch => SeedPattern::Scatter,
             SeedPattern::Scatter => SeedPattern::MultiPoint,
             SeedPattern::MultiPoint => SeedPattern::Starburst,
-            SeedPattern::Starburst => SeedPattern::Point,
+            SeedPattern::Starburst => SeedPattern::Empty,
+            // Imported seeds sit between Empty and Point in the cycle
+            SeedPattern::Empty | SeedPattern::Image(_) | SeedPattern::Text(_) | SeedPattern::Custom(_) => {
+                SeedPattern::Point
+            }
         }
     }
 
     pub fn prev(&self) -> SeedPattern {
         match self {
-            SeedPattern::Point => SeedPattern::Starburst,
+            SeedPattern::Point | SeedPattern::Image(_) | SeedPattern::Text(_) | SeedPattern::Custom(_) => {
+                SeedPattern::Empty
+            }
             SeedPattern::Line => SeedPattern::Point,
             SeedPattern::Cross => SeedPattern::Line,
             SeedPattern::Circle => SeedPattern::Cross,
@@ -83,12 +149,13 @@ impl SeedPattern {
             SeedPattern::Scatter => SeedPattern::NoisePatch,
             SeedPattern::MultiPoint => SeedPattern::Scatter,
             SeedPattern::Starburst => SeedPattern::MultiPoint,
+            SeedPattern::Empty => SeedPattern::Starburst,
         }
     }
 }
 
 /// Additional data stored per particle for advanced color modes
-#[derive(Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct ParticleData {
     /// Order in which particle was stuck (age)
     pub age: usize,
@@ -98,6 +165,79 @@ pub struct ParticleData {
     pub direction: f32,
     /// Number of neighbors when stuck
     pub neighbor_count: u8,
+    /// Grew from a sticky wall (Stick boundary) rather than from the seed
+    #[serde(default)]
+    pub deposit: bool,
+    /// Label shared by every particle of a cluster under cluster-cluster aggregation
+    #[serde(default)]
+    pub cluster: u32,
+    /// Species of the walker that stuck (seed particles are species 0)
+    #[serde(default)]
+    pub species: u8,
+    /// Index in the disc list of the particle this one attached to. None for
+    /// seeds, particles stuck only to a wall, and cluster-cluster runs.
+    #[serde(default)]
+    pub parent: Option<u32>,
+}
+
+impl ParticleData {
+    /// Colour value (0-1) for the cluster label. Steps of the golden ratio
+    /// keep consecutive labels far apart on the gradient.
+    pub fn cluster_shade(&self) -> f32 {
+        (self.cluster as f32 * 0.618_034).fract()
+    }
+
+    /// Colour value (0-1) for the species: the middle of its share of the
+    /// gradient, so the two ends of a cyclic gradient never pair up
+    pub fn species_shade(&self, species_count: usize) -> f32 {
+        ((self.species as f32 + 0.5) / species_count.max(1) as f32).min(1.0)
+    }
+}
+
+/// A stuck particle as a disc centre in continuous space.
+/// Lattice particles sit at the centre of their cell.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Disc {
+    pub x: f32,
+    pub y: f32,
+    #[serde(flatten)]
+    pub data: ParticleData,
+}
+
+/// Result of walking one particle
+#[derive(Debug, Clone, Copy)]
+enum WalkOutcome {
+    /// Particle should stick at this cell
+    Stick {
+        ix: usize,
+        iy: usize,
+        distance: f32,
+        direction: f32,
+        neighbor_count: usize,
+        species: u8,
+    },
+    /// Off-lattice particle should stick with its centre at this point
+    StickDisc {
+        x: f32,
+        y: f32,
+        distance: f32,
+        direction: f32,
+        neighbor_count: usize,
+        species: u8,
+    },
+    /// Particle escaped, was absorbed or gave up; nothing changes
+    Respawn,
+}
+
+impl WalkOutcome {
+    /// Where the particle would stick, if it sticks
+    fn site(&self) -> Option<(f32, f32)> {
+        match *self {
+            WalkOutcome::Stick { ix, iy, .. } => Some((ix as f32 + 0.5, iy as f32 + 0.5)),
+            WalkOutcome::StickDisc { x, y, .. } => Some((x, y)),
+            WalkOutcome::Respawn => None,
+        }
+    }
 }
 
 /// DLA simulation state
@@ -109,12 +249,40 @@ pub struct DlaSimulation {
     pub num_particles: usize,
     pub stickiness: f32,
     pub particles_stuck: usize,
+    /// Particles placed by an imported seed (text, image or drawing). They come on
+    /// top of num_particles, so a large seed doesn't use up the growth budget.
+    seed_particles: usize,
     pub max_radius: f32,
     pub paused: bool,
     pub seed_pattern: SeedPattern,
     /// Advanced simulation settings
     pub settings: SimulationSettings,
-    rng: ThreadRng,
+    /// Fixed RNG seed for reproducible runs (None = fresh random seed on every reset)
+    pub rng_seed: Option<u64>,
+    /// Seed actually used for the current run (equals rng_seed when one is set)
+    active_seed: u64,
+    rng: ChaCha8Rng,
+    /// Distance (cells, capped at MAX_FIELD_DISTANCE) from each cell to the nearest stuck particle
+    distance_field: Vec<u8>,
+    /// Largest distance of any stuck cell centre from the grid centre
+    bound_radius: f32,
+    /// Every stuck particle in stick order, as a disc (the grid above is its raster)
+    discs: Vec<Disc>,
+    /// Bucketed disc indices for contact queries
+    disc_hash: SpatialHash,
+    /// Electric potential, solved only while the dielectric breakdown model runs
+    potential: growth::PotentialField,
+    /// Empty sites next to the cluster, kept only while lattice Eden growth runs
+    perimeter: growth::Perimeter,
+    /// Cluster bookkeeping for cluster-cluster aggregation
+    clusters: clusters::ClusterSet,
+    /// Clusters have moved since the distance field and disc hash were built
+    structure_moved: bool,
+    /// Particles were erased during the current paint stroke, and the distance
+    /// field and max_radius still await the rebuild at its end
+    erase_pending: bool,
+    /// settings.mask stretched over the grid at the last reset (empty = no mask)
+    mask: Vec<MaskCell>,
 }
 
 impl DlaSimulation {
@@ -126,11 +294,24 @@ impl DlaSimulation {
             num_particles: 5000,
             stickiness: 1.0,
             particles_stuck: 0,
+            seed_particles: 0,
             max_radius: 1.0,
             paused: false,
             seed_pattern: SeedPattern::Point,
             settings: SimulationSettings::default(),
-            rng: rand::thread_rng(),
+            rng_seed: None,
+            active_seed: 0,
+            rng: ChaCha8Rng::seed_from_u64(0),
+            distance_field: Vec::new(),
+            bound_radius: 0.0,
+            discs: Vec::new(),
+            disc_hash: SpatialHash::default(),
+            potential: growth::PotentialField::default(),
+            perimeter: growth::Perimeter::default(),
+            clusters: clusters::ClusterSet::default(),
+            structure_moved: false,
+            erase_pending: false,
+            mask: Vec::new(),
         };
         sim.reset();
         sim
@@ -141,59 +322,430 @@ impl DlaSimulation {
         (self.grid_width as f32 / 2.0, self.grid_height as f32 / 2.0)
     }
 
+    /// Whether the grid is a periodic domain (Wrap boundary): opposite edges
+    /// are joined for walking, neighbour lookups and rendering
+    pub fn is_periodic(&self) -> bool {
+        self.settings.boundary_behavior == BoundaryBehavior::Wrap
+    }
+
+    /// Displacement from `(x0, y0)` to `(x1, y1)`, taking the shortest way
+    /// around the seams on a periodic grid
+    fn displacement(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> (f32, f32) {
+        let (mut dx, mut dy) = (x1 - x0, y1 - y0);
+        if self.is_periodic() {
+            let (w, h) = (self.grid_width as f32, self.grid_height as f32);
+            dx -= w * (dx / w).round();
+            dy -= h * (dy / h).round();
+        }
+        (dx, dy)
+    }
+
+    /// Displacement of a point from the grid centre (shortest way on a periodic grid)
+    fn center_offset(&self, x: f32, y: f32) -> (f32, f32) {
+        let (cx, cy) = self.center();
+        self.displacement(cx, cy, x, y)
+    }
+
+    /// Map a point back into the grid on a periodic domain; other boundaries
+    /// leave it alone
+    fn wrap_point(&self, x: f32, y: f32) -> (f32, f32) {
+        if !self.is_periodic() {
+            return (x, y);
+        }
+        let wrap = |v: f32, period: f32| {
+            let r = v.rem_euclid(period);
+            // rem_euclid can round up to the period itself for tiny negatives
+            if r >= period { 0.0 } else { r }
+        };
+        (wrap(x, self.grid_width as f32), wrap(y, self.grid_height as f32))
+    }
+
+    /// Grid index of the cell at (ix + dx, iy + dy), wrapping on a periodic grid.
+    /// None if the cell lies off a bounded grid.
+    fn neighbor_index(&self, ix: usize, iy: usize, dx: i32, dy: i32) -> Option<usize> {
+        let (w, h) = (self.grid_width as i32, self.grid_height as i32);
+        let (mut nx, mut ny) = (ix as i32 + dx, iy as i32 + dy);
+        if self.is_periodic() {
+            nx = nx.rem_euclid(w);
+            ny = ny.rem_euclid(h);
+        } else if nx < 0 || nx >= w || ny < 0 || ny >= h {
+            return None;
+        }
+        Some(ny as usize * self.grid_width + nx as usize)
+    }
+
+    /// Whether cell (ix, iy) lies where walkers go. Bounded grids keep the
+    /// outermost ring free; a periodic grid has no edge.
+    fn in_walk_range(&self, ix: usize, iy: usize) -> bool {
+        self.is_periodic()
+            || (ix > 0 && ix < self.grid_width - 1 && iy > 0 && iy < self.grid_height - 1)
+    }
+
+    /// Whether a walker at cell (ix, iy) may stick there: inside the walk
+    /// range and open in the mask
+    fn in_sticking_range(&self, ix: usize, iy: usize) -> bool {
+        self.in_walk_range(ix, iy) && self.mask_cell(ix, iy) == MaskCell::Open
+    }
+
+    /// Whether a domain mask is in effect
+    pub fn has_mask(&self) -> bool {
+        !self.mask.is_empty()
+    }
+
+    /// Mask type of grid cell (ix, iy); Open without a mask
+    pub fn mask_cell(&self, ix: usize, iy: usize) -> MaskCell {
+        self.mask.get(iy * self.grid_width + ix).copied().unwrap_or_default()
+    }
+
+    /// Mask type under the point (x, y); Open without a mask or off the grid
+    fn mask_at(&self, x: f32, y: f32) -> MaskCell {
+        if self.mask.is_empty() || x < 0.0 || y < 0.0 {
+            return MaskCell::Open;
+        }
+        let (ix, iy) = (x as usize, y as usize);
+        if ix >= self.grid_width || iy >= self.grid_height {
+            return MaskCell::Open;
+        }
+        self.mask[iy * self.grid_width + ix]
+    }
+
+    /// The first wall or sink a straight move from (x0, y0) to (x1, y1) runs
+    /// into, sampled about once per cell, or Open if there is none
+    fn mask_on_path(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> MaskCell {
+        if self.mask.is_empty() {
+            return MaskCell::Open;
+        }
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let samples = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+        for i in 1..=samples {
+            let t = i as f32 / samples as f32;
+            let (sx, sy) = self.wrap_point(x0 + t * dx, y0 + t * dy);
+            let cell = self.mask_at(sx, sy);
+            if matches!(cell, MaskCell::Wall | MaskCell::Sink) {
+                return cell;
+            }
+        }
+        MaskCell::Open
+    }
+
+    /// Stretch settings.mask over the current grid
+    fn rebuild_mask(&mut self) {
+        self.mask = match &self.settings.mask {
+            Some(mask) => mask.resample(self.grid_width, self.grid_height),
+            None => Vec::new(),
+        };
+    }
+
+    /// Whether a walker has anything to stick to (an empty seed needs sticky walls),
+    /// and a dielectric breakdown hasn't already reached its electrode
+    fn can_grow(&self) -> bool {
+        (self.particles_stuck > 0 || self.sticky_walls().is_some())
+            && !(self.potential().is_some() && self.potential.bridged)
+    }
+
     /// Execute one particle simulation step
     /// Returns true if simulation should continue, false if complete
     pub fn step(&mut self) -> bool {
-        if self.paused || self.particles_stuck >= self.num_particles {
+        if self.paused {
+            return false;
+        }
+        if self.clusters_move() {
+            return self.step_clusters();
+        }
+        self.settle_clusters();
+        if self.particles_stuck >= self.particle_budget() || !self.can_grow() {
             return false;
         }
 
+        growth::model(self.settings.growth_model).prepare(self);
+        let mut rng = self.rng.clone();
+        let outcome = self.walk_particle(&mut rng);
+        self.rng = rng;
+        self.commit_walk(outcome);
+        true
+    }
+
+    /// Advance one batch of PARALLEL_BATCH walkers on up to `threads` worker threads.
+    ///
+    /// Every walker gets its own RNG seeded from the main RNG and walks against the
+    /// grid as it was at the start of the batch. Sticks are then committed in walker
+    /// order; a walker whose site lies within reach of a particle committed earlier in
+    /// the same batch is re-walked against the updated grid. Results depend only on
+    /// the seed, never on the thread count or scheduling.
+    /// Cluster-cluster aggregation moves one cluster at a time, so its batches run serially.
+    /// Returns the number of walkers processed (0 if paused or complete).
+    pub fn step_parallel(&mut self, threads: usize) -> usize {
+        if self.paused {
+            return 0;
+        }
+        if self.clusters_move() {
+            return (0..PARALLEL_BATCH).take_while(|_| self.step_clusters()).count();
+        }
+        self.settle_clusters();
+        if self.particles_stuck >= self.particle_budget() || !self.can_grow() {
+            return 0;
+        }
+
+        growth::model(self.settings.growth_model).prepare(self);
+        let walkers = PARALLEL_BATCH.min(self.particle_budget() - self.particles_stuck);
+        let mut rngs: Vec<ChaCha8Rng> = (0..walkers)
+            .map(|_| ChaCha8Rng::seed_from_u64(self.rng.gen()))
+            .collect();
+
+        // Walk every particle against a read-only view of the grid
+        let chunk_size = walkers.div_ceil(threads.clamp(1, walkers));
+        let outcomes: Vec<WalkOutcome> = {
+            let sim = &*self;
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = rngs
+                    .chunks_mut(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            chunk.iter_mut().map(|rng| sim.walk_particle(rng)).collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().expect("walker thread panicked"))
+                    .collect()
+            })
+        };
+
+        // Commit in walker order, setting aside sticks that clash with this batch
+        let reach = if self.settings.off_lattice {
+            DISC_NEIGHBOR_RADIUS
+        } else {
+            self.settings.neighborhood.reach()
+        };
+        let conflict_radius = reach + CONFLICT_MARGIN;
+        let conflict_sq = conflict_radius * conflict_radius;
+        let mut committed: Vec<(f32, f32)> = Vec::new();
+        let mut conflicts = Vec::new();
+        for (i, outcome) in outcomes.into_iter().enumerate() {
+            if let Some((sx, sy)) = outcome.site() {
+                let clash = committed.iter().any(|&(cx, cy)| {
+                    let (dx, dy) = self.displacement(cx, cy, sx, sy);
+                    dx * dx + dy * dy <= conflict_sq
+                });
+                if clash {
+                    conflicts.push(i);
+                } else if self.particles_stuck < self.particle_budget() {
+                    self.commit_walk(outcome);
+                    committed.push((sx, sy));
+                }
+            }
+        }
+
+        // Re-walk conflicting particles against the updated grid, still in walker order
+        for i in conflicts {
+            if self.particles_stuck >= self.particle_budget() {
+                break;
+            }
+            let outcome = self.walk_particle(&mut rngs[i]);
+            self.commit_walk(outcome);
+        }
+
+        walkers
+    }
+
+    /// Apply the result of a walk to the grid
+    fn commit_walk(&mut self, outcome: WalkOutcome) {
+        match outcome {
+            WalkOutcome::Stick {
+                ix,
+                iy,
+                distance,
+                direction,
+                neighbor_count,
+                species,
+            } => {
+                // A walk may race an earlier commit for the same cell
+                if self.grid[iy * self.grid_width + ix].is_none() {
+                    let (x, y) = (ix as f32 + 0.5, iy as f32 + 0.5);
+                    self.stick_particle(x, y, distance, direction, neighbor_count, species);
+                }
+            }
+            WalkOutcome::StickDisc {
+                x,
+                y,
+                distance,
+                direction,
+                neighbor_count,
+                species,
+            } => {
+                if !self.disc_overlaps(x, y) {
+                    self.stick_particle(x, y, distance, direction, neighbor_count, species);
+                }
+            }
+            WalkOutcome::Respawn => {}
+        }
+    }
+
+    /// Spawn radius - outside the structure with proportional buffer
+    fn spawn_radius(&self) -> f32 {
+        (self.max_radius * SPAWN_RADIUS_EXPANSION + self.settings.spawn_radius_offset)
+            .max(self.settings.min_spawn_radius)
+    }
+
+    /// Squared distance from the centre beyond which a walker is given up.
+    /// With sticky walls a walker has to be free to reach them, and nothing
+    /// escapes a periodic grid: those walkers only end by sticking or running
+    /// out of iterations.
+    fn escape_dist_sq(&self, spawn_radius: f32) -> f32 {
+        if self.sticky_walls().is_some() || self.is_periodic() {
+            return f32::INFINITY;
+        }
+        let escape = spawn_radius * self.settings.escape_multiplier;
+        escape * escape
+    }
+
+    /// Sticky walls as (left, right, top, bottom), or None unless the boundary is Stick
+    fn sticky_walls(&self) -> Option<(bool, bool, bool, bool)> {
+        (self.settings.boundary_behavior == BoundaryBehavior::Stick)
+            .then(|| self.settings.sticky_edges.sides())
+    }
+
+    /// Circle jumps are only statistically exact for an isotropic continuous
+    /// walk (a lattice walk's exit point isn't uniform on the circle, and the
+    /// jump would leave the lattice), and would hop over the walls and sinks of a mask
+    fn jumps_enabled(&self, walker: &Species) -> bool {
+        self.settings.distance_jumps
+            && !self.settings.lattice_walk
+            && walker.bias_strength <= 0.0
+            && self.mask.is_empty()
+            && self.settings.radial_bias.abs() <= 0.001
+    }
+
+    /// Walk step length at a given distance from the centre
+    fn walk_step_at(&self, dist: f32) -> f32 {
+        let base_walk_step = self.settings.walk_step_size;
+        if !self.settings.adaptive_step {
+            return base_walk_step;
+        }
+        let adaptive_factor = self.settings.adaptive_step_factor;
+        let safe_dist = (dist - self.max_radius - 2.0).max(0.0);
+        // Use large steps when far, small steps when close
+        // Minimum step is base step, maximum is base * factor
+        let adaptive = base_walk_step + (safe_dist / 10.0).min(adaptive_factor - 1.0) * base_walk_step;
+        adaptive.min(base_walk_step * adaptive_factor)
+    }
+
+    /// Grow one particle with the selected growth model. Only reads the grid,
+    /// so many particles can be grown at once.
+    fn walk_particle(&self, rng: &mut ChaCha8Rng) -> WalkOutcome {
+        let species = self.pick_species(rng);
+        growth::model(self.settings.growth_model).grow(self, rng, species)
+    }
+
+    /// Species of a new walker. Single-species runs leave the RNG untouched,
+    /// so their seeded results don't change.
+    fn pick_species(&self, rng: &mut ChaCha8Rng) -> u8 {
+        if self.settings.species.len() > 1 {
+            self.settings.pick_species(rng.gen())
+        } else {
+            0
+        }
+    }
+
+    /// Chance that a walker of `species` sticks at (x, y): the stickiness
+    /// settings, scaled by how readily it sticks to the species it touches
+    fn sticking_chance(&self, x: f32, y: f32, neighbor_count: usize, distance: f32, species: u8) -> f32 {
+        self.settings.effective_stickiness(neighbor_count, distance, self.stickiness)
+            * self.species_affinity(x, y, species)
+    }
+
+    /// Mean stick-matrix entry between a walker of `species` at (x, y) and the
+    /// stuck particles it touches. Sticky walls stick to every species.
+    fn species_affinity(&self, x: f32, y: f32, species: u8) -> f32 {
+        if self.settings.stick_matrix.is_empty() {
+            return 1.0;
+        }
+        let mut total = 0.0;
+        let mut contacts = 0;
+        let mut tally = |p: &ParticleData| {
+            total += self.settings.pair_stickiness(species, p.species);
+            contacts += 1;
+        };
+
+        let walls = if self.settings.off_lattice {
+            let r = DISC_NEIGHBOR_RADIUS;
+            self.for_each_disc_in(x - r, y - r, x + r, y + r, |disc, sx, sy| {
+                if (disc.x + sx - x).powi(2) + (disc.y + sy - y).powi(2) <= r * r {
+                    tally(&disc.data);
+                }
+            });
+            self.disc_wall_contacts(x, y)
+        } else {
+            let (ix, iy) = (x as usize, y as usize);
+            for &(ndx, ndy) in self.settings.neighborhood.offsets() {
+                if let Some(p) = self.neighbor_index(ix, iy, ndx, ndy).and_then(|n| self.grid[n].as_ref()) {
+                    tally(p);
+                }
+            }
+            self.wall_contacts(ix, iy)
+        };
+
+        if contacts + walls == 0 {
+            return 1.0;
+        }
+        (total + walls as f32) / (contacts + walls) as f32
+    }
+
+    /// Walk a single particle from spawn until it sticks, escapes or runs out of
+    /// iterations. Every sticking chance is scaled by `sticking`; below one the
+    /// walker is reaction-limited, and instead of respawning when it bumps into
+    /// the cluster it stays against it and keeps walking.
+    fn walk_diffusive(&self, rng: &mut ChaCha8Rng, sticking: f32, species: u8) -> WalkOutcome {
+        if self.settings.off_lattice {
+            return self.walk_disc(rng, sticking, species);
+        }
+        let walker = self.settings.species_def(species);
+        let persistent = sticking < 1.0;
+
         let (center_x, center_y) = self.center();
 
         // Get settings values
-        let spawn_radius_offset = self.settings.spawn_radius_offset;
-        let min_spawn_radius = self.settings.min_spawn_radius;
-        let escape_mult = self.settings.escape_multiplier;
         let max_iterations = self.settings.max_walk_iterations;
         let base_walk_step = self.settings.walk_step_size;
-        let adaptive_step = self.settings.adaptive_step;
-        let adaptive_factor = self.settings.adaptive_step_factor;
         let lattice_walk = self.settings.lattice_walk;
 
-        // Spawn radius - outside the structure with proportional buffer
-        let spawn_radius = (self.max_radius * SPAWN_RADIUS_EXPANSION + spawn_radius_offset).max(min_spawn_radius);
+        let use_jumps = self.jumps_enabled(&walker);
+        let stick_reach = self.settings.neighborhood.reach();
+
+        let spawn_radius = self.spawn_radius();
 
         // Pre-calculate squared escape distance (avoids sqrt in hot loop)
-        let escape_dist_sq = spawn_radius * spawn_radius * escape_mult * escape_mult;
+        let escape_dist_sq = self.escape_dist_sq(spawn_radius);
 
         // Pre-calculate boundary limits
         let x_max = self.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
         let y_max = self.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
 
         // Spawn particle based on spawn mode
-        let (mut x, mut y) = self.spawn_particle(center_x, center_y, spawn_radius);
+        let Some((mut x, mut y)) = self.spawn_walker(rng, walker.spawn_mode, center_x, center_y, spawn_radius) else {
+            return WalkOutcome::Respawn;
+        };
 
         // Track the approach direction for color mode
-        let mut last_dx = x - center_x;
-        let mut last_dy = y - center_y;
+        let (mut last_dx, mut last_dy) = self.center_offset(x, y);
 
         // Random walk until it sticks or escapes
         for _ in 0..max_iterations {
             // Check if we've gone too far (using squared distance to avoid sqrt)
-            let dx = x - center_x;
-            let dy = y - center_y;
+            let (dx, dy) = self.center_offset(x, y);
             let dist_sq = dx * dx + dy * dy;
 
             if dist_sq > escape_dist_sq {
                 // Escaped, restart
-                return true;
+                return WalkOutcome::Respawn;
             }
 
             // Check if next to a stuck particle
             let ix = x as usize;
             let iy = y as usize;
 
-            if ix > 0 && ix < self.grid_width - 1 && iy > 0 && iy < self.grid_height - 1 {
+            if self.in_sticking_range(ix, iy) {
                 // Count neighbors using the configured neighborhood type
                 let (neighbor_count, has_neighbor) = self.count_neighbors(ix, iy);
 
@@ -202,14 +754,11 @@ impl DlaSimulation {
                     let distance = dist_sq.sqrt();
 
                     // Calculate effective stickiness
-                    let effective_stickiness = self.settings.effective_stickiness(
-                        neighbor_count,
-                        distance,
-                        self.stickiness,
-                    );
+                    let effective_stickiness =
+                        self.sticking_chance(x, y, neighbor_count, distance, species) * sticking;
 
                     // Check if we should stick
-                    if self.rng.gen::<f32>() < effective_stickiness {
+                    if rng.gen::<f32>() < effective_stickiness {
                         let idx = iy * self.grid_width + ix;
 
                         // Only stick if cell is empty - if occupied, continue walking
@@ -218,18 +767,14 @@ impl DlaSimulation {
                             let direction = last_dy.atan2(last_dx);
 
                             // Stick here with particle data
-                            self.grid[idx] = Some(ParticleData {
-                                age: self.particles_stuck,
+                            return WalkOutcome::Stick {
+                                ix,
+                                iy,
                                 distance,
                                 direction,
-                                neighbor_count: neighbor_count as u8,
-                            });
-                            self.particles_stuck += 1;
-
-                            // Update max radius
-                            self.max_radius = self.max_radius.max(distance);
-
-                            return true;
+                                neighbor_count,
+                                species,
+                            };
                         }
                         // Cell occupied - particle continues walking (accurate DLA behavior)
                     }
@@ -237,26 +782,27 @@ impl DlaSimulation {
             }
 
             // Store previous position for direction tracking
-            last_dx = x - center_x;
-            last_dy = y - center_y;
+            (last_dx, last_dy) = (dx, dy);
+
+            // Far from the cluster: jump straight to a random point on the largest
+            // circle that cannot contain a sticking site. For an unbiased walk this
+            // is where the walk would first leave that circle anyway.
+            if use_jumps {
+                let jump = self.jump_radius(x, y, dist_sq.sqrt(), x_max, y_max, stick_reach);
+                if jump > base_walk_step {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    (x, y) = self.wrap_point(x + jump * angle.cos(), y + jump * angle.sin());
+                    continue;
+                }
+            }
 
             // Calculate adaptive step size based on distance from cluster
-            let walk_step = if adaptive_step {
-                let dist = dist_sq.sqrt();
-                let safe_dist = (dist - self.max_radius - 2.0).max(0.0);
-                // Use large steps when far, small steps when close
-                // Minimum step is base step, maximum is base * factor
-                let adaptive = base_walk_step
-                    + (safe_dist / 10.0).min(adaptive_factor - 1.0) * base_walk_step;
-                adaptive.min(base_walk_step * adaptive_factor)
-            } else {
-                base_walk_step
-            };
+            let walk_step = self.walk_step_at(dist_sq.sqrt());
 
             // Calculate walk angle - either lattice (4 directions) or continuous
             let walk_angle = if lattice_walk {
                 // Pure lattice: 4 cardinal directions only (classic Witten-Sander DLA)
-                match self.rng.gen_range(0..4) {
+                match rng.gen_range(0..4) {
                     0 => 0.0,                                    // Right
                     1 => std::f32::consts::FRAC_PI_2,            // Up
                     2 => std::f32::consts::PI,                   // Left
@@ -264,14 +810,21 @@ impl DlaSimulation {
                 }
             } else {
                 // Continuous random angle with optional bias
-                let base_angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
-                self.apply_walk_bias(base_angle, x, y, center_x, center_y)
+                let base_angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                self.apply_walk_bias(base_angle, x, y, &walker)
             };
 
             // Calculate new position
             let new_x = x + walk_step * walk_angle.cos();
             let new_y = y + walk_step * walk_angle.sin();
 
+            // Walls in the mask block the step, sinks absorb the walker
+            match self.mask_on_path(x, y, new_x, new_y) {
+                MaskCell::Wall => continue,
+                MaskCell::Sink => return WalkOutcome::Respawn,
+                _ => {}
+            }
+
             // Path sampling for large steps to prevent tunneling through the cluster
             if walk_step > 1.5 {
                 // Sample along the path using Bresenham-style stepping
@@ -279,90 +832,351 @@ impl DlaSimulation {
                     self.sample_path_for_collision(x, y, new_x, new_y)
                 {
                     // Found occupied cell along path - try to stick at the last empty position
-                    let distance = ((stick_x - center_x).powi(2) + (stick_y - center_y).powi(2)).sqrt();
-                    let effective_stickiness = self.settings.effective_stickiness(
-                        neighbor_count,
-                        distance,
-                        self.stickiness,
-                    );
-
-                    if self.rng.gen::<f32>() < effective_stickiness {
+                    let (sdx, sdy) = self.center_offset(stick_x, stick_y);
+                    let distance = (sdx * sdx + sdy * sdy).sqrt();
+                    let effective_stickiness =
+                        self.sticking_chance(stick_x, stick_y, neighbor_count, distance, species) * sticking;
+
+                    if rng.gen::<f32>() < effective_stickiness {
                         let ix = stick_x as usize;
                         let iy = stick_y as usize;
-                        if ix > 0 && ix < self.grid_width - 1 && iy > 0 && iy < self.grid_height - 1 {
+                        if self.in_sticking_range(ix, iy) {
                             let idx = iy * self.grid_width + ix;
                             if self.grid[idx].is_none() {
                                 let direction = last_dy.atan2(last_dx);
-                                self.grid[idx] = Some(ParticleData {
-                                    age: self.particles_stuck,
+                                return WalkOutcome::Stick {
+                                    ix,
+                                    iy,
                                     distance,
                                     direction,
-                                    neighbor_count: neighbor_count as u8,
-                                });
-                                self.particles_stuck += 1;
-                                self.max_radius = self.max_radius.max(distance);
-                                return true;
+                                    neighbor_count,
+                                    species,
+                                };
                             }
                         }
                     }
+                    if persistent {
+                        (x, y) = (stick_x, stick_y);
+                        continue;
+                    }
                     // Didn't stick - respawn particle (don't let it continue through cluster)
-                    return true;
+                    return WalkOutcome::Respawn;
                 }
             }
 
             // Check if landing position is occupied - respawn instead of walking through
-            let land_ix = new_x as usize;
-            let land_iy = new_y as usize;
+            let (land_x, land_y) = self.wrap_point(new_x, new_y);
+            let land_ix = land_x as usize;
+            let land_iy = land_y as usize;
             if land_ix < self.grid_width && land_iy < self.grid_height {
                 let land_idx = land_iy * self.grid_width + land_ix;
                 if self.grid[land_idx].is_some() {
+                    if persistent {
+                        continue;
+                    }
                     // Landing on occupied cell - respawn particle
-                    return true;
+                    return WalkOutcome::Respawn;
                 }
             }
 
             // Take walk step
+            let (prev_x, prev_y) = (x, y);
             x = new_x;
             y = new_y;
 
             // Apply boundary behavior
             (x, y) = self.apply_boundary(x, y, x_max, y_max);
+            match self.mask_at(x, y) {
+                MaskCell::Wall => (x, y) = (prev_x, prev_y),
+                MaskCell::Sink => return WalkOutcome::Respawn,
+                _ => {}
+            }
 
             // Handle absorb boundary - if we hit edge, respawn
             if self.settings.boundary_behavior == BoundaryBehavior::Absorb
                 && (x <= BOUNDARY_MARGIN || x >= x_max || y <= BOUNDARY_MARGIN || y >= y_max)
             {
-                return true; // Respawn
+                return WalkOutcome::Respawn;
             }
         }
 
-        true
+        WalkOutcome::Respawn
     }
 
-    /// Spawn a particle based on the configured spawn mode
-    fn spawn_particle(&mut self, center_x: f32, center_y: f32, spawn_radius: f32) -> (f32, f32) {
+    /// Off-lattice walk: the particle is a disc moving in continuous space that
+    /// sticks where it first touches a stuck disc. Lattice Walk is ignored.
+    fn walk_disc(&self, rng: &mut ChaCha8Rng, sticking: f32, species: u8) -> WalkOutcome {
+        let walker = self.settings.species_def(species);
+        let (center_x, center_y) = self.center();
+        let spawn_radius = self.spawn_radius();
+        let escape_dist_sq = self.escape_dist_sq(spawn_radius);
+        let x_max = self.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+        let y_max = self.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+        let use_jumps = self.jumps_enabled(&walker);
+
+        let Some((mut x, mut y)) = self.spawn_walker(rng, walker.spawn_mode, center_x, center_y, spawn_radius) else {
+            return WalkOutcome::Respawn;
+        };
+
+        for _ in 0..self.settings.max_walk_iterations {
+            let (dx, dy) = self.center_offset(x, y);
+            let dist_sq = dx * dx + dy * dy;
+            if dist_sq > escape_dist_sq {
+                return WalkOutcome::Respawn;
+            }
+
+            let walk_step = self.walk_step_at(dist_sq.sqrt());
+            if use_jumps {
+                let jump = self.jump_radius(x, y, dist_sq.sqrt(), x_max, y_max, DISC_CONTACT);
+                if jump > walk_step {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    (x, y) = self.wrap_point(x + jump * angle.cos(), y + jump * angle.sin());
+                    continue;
+                }
+            }
+
+            let base_angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let angle = self.apply_walk_bias(base_angle, x, y, &walker);
+            let (ux, uy) = (angle.cos(), angle.sin());
+
+            match self.mask_on_path(x, y, x + ux * walk_step, y + uy * walk_step) {
+                MaskCell::Wall => continue,
+                MaskCell::Sink => return WalkOutcome::Respawn,
+                _ => {}
+            }
+
+            if let Some(t) = self.disc_contact(x, y, ux, uy, walk_step) {
+                let (cx, cy) = self.wrap_point(x + ux * t, y + uy * t);
+                let neighbor_count = self.count_disc_neighbors(cx, cy);
+                if neighbor_count >= self.settings.multi_contact_min as usize && self.mask_at(cx, cy) == MaskCell::Open {
+                    let (cdx, cdy) = self.center_offset(cx, cy);
+                    let distance = (cdx * cdx + cdy * cdy).sqrt();
+                    let effective_stickiness =
+                        self.sticking_chance(cx, cy, neighbor_count, distance, species) * sticking;
+                    if rng.gen::<f32>() < effective_stickiness {
+                        return WalkOutcome::StickDisc {
+                            x: cx,
+                            y: cy,
+                            distance,
+                            direction: dy.atan2(dx),
+                            neighbor_count,
+                            species,
+                        };
+                    }
+                }
+                // Didn't stick - rest against the cluster and keep walking
+                x = cx;
+                y = cy;
+                continue;
+            }
+
+            let (prev_x, prev_y) = (x, y);
+            x += ux * walk_step;
+            y += uy * walk_step;
+            (x, y) = self.apply_boundary(x, y, x_max, y_max);
+            match self.mask_at(x, y) {
+                MaskCell::Wall => (x, y) = (prev_x, prev_y),
+                MaskCell::Sink => return WalkOutcome::Respawn,
+                _ => {}
+            }
+
+            // Pressed against a sticky wall: the wall counts as one more neighbour
+            let walls = self.disc_wall_contacts(x, y);
+            if walls > 0 {
+                let neighbor_count = self.count_disc_neighbors(x, y) + walls;
+                if neighbor_count >= self.settings.multi_contact_min as usize && self.mask_at(x, y) == MaskCell::Open {
+                    let (wdx, wdy) = self.center_offset(x, y);
+                    let distance = (wdx * wdx + wdy * wdy).sqrt();
+                    let effective_stickiness =
+                        self.sticking_chance(x, y, neighbor_count, distance, species) * sticking;
+                    if rng.gen::<f32>() < effective_stickiness && !self.disc_overlaps(x, y) {
+                        return WalkOutcome::StickDisc {
+                            x,
+                            y,
+                            distance,
+                            direction: dy.atan2(dx),
+                            neighbor_count,
+                            species,
+                        };
+                    }
+                }
+            }
+
+            if self.settings.boundary_behavior == BoundaryBehavior::Absorb
+                && (x <= BOUNDARY_MARGIN || x >= x_max || y <= BOUNDARY_MARGIN || y >= y_max)
+            {
+                return WalkOutcome::Respawn;
+            }
+        }
+
+        WalkOutcome::Respawn
+    }
+
+    /// Distance along the unit direction (ux, uy) at which a disc starting at (x, y)
+    /// first touches a stuck disc, if that happens within `len`
+    fn disc_contact(&self, x: f32, y: f32, ux: f32, uy: f32, len: f32) -> Option<f32> {
+        let contact_sq = DISC_CONTACT * DISC_CONTACT;
+        let (ex, ey) = (x + ux * len, y + uy * len);
+        let mut nearest: Option<f32> = None;
+
+        self.for_each_disc_in(
+            x.min(ex) - DISC_CONTACT,
+            y.min(ey) - DISC_CONTACT,
+            x.max(ex) + DISC_CONTACT,
+            y.max(ey) + DISC_CONTACT,
+            |disc, sx, sy| {
+                let px = x - (disc.x + sx);
+                let py = y - (disc.y + sy);
+                let b = px * ux + py * uy;
+                let c = px * px + py * py - contact_sq;
+                let t = if c <= 0.0 {
+                    // Already touching: only a step towards the disc makes contact
+                    if b >= 0.0 {
+                        return;
+                    }
+                    0.0
+                } else {
+                    let disc_sq = b * b - c;
+                    if b >= 0.0 || disc_sq < 0.0 {
+                        return;
+                    }
+                    -b - disc_sq.sqrt()
+                };
+                if t <= len && nearest.is_none_or(|n| t < n) {
+                    nearest = Some(t);
+                }
+            },
+        );
+
+        nearest
+    }
+
+    /// Call `f` with every stuck disc whose centre may lie in the rectangle, and the
+    /// offset to add to its position. On a periodic grid the rectangle can hang over
+    /// the edges, and discs near the opposite edge are visited shifted by a period.
+    fn for_each_disc_in(&self, x0: f32, y0: f32, x1: f32, y1: f32, mut f: impl FnMut(&Disc, f32, f32)) {
+        self.for_each_disc_index_in(x0, y0, x1, y1, |i, sx, sy| f(&self.discs[i], sx, sy));
+    }
+
+    /// Like `for_each_disc_in`, passing the disc's index instead
+    fn for_each_disc_index_in(&self, x0: f32, y0: f32, x1: f32, y1: f32, mut f: impl FnMut(usize, f32, f32)) {
+        let (w, h) = (self.grid_width as f32, self.grid_height as f32);
+        let shifts = |lo: f32, hi: f32, period: f32| {
+            let mut out = vec![0.0];
+            if self.is_periodic() {
+                if lo < 0.0 {
+                    out.push(-period);
+                }
+                if hi >= period {
+                    out.push(period);
+                }
+            }
+            out
+        };
+        for sy in shifts(y0, y1, h) {
+            for &sx in &shifts(x0, x1, w) {
+                self.disc_hash
+                    .for_each_in(x0 - sx, y0 - sy, x1 - sx, y1 - sy, |i| f(i, sx, sy));
+            }
+        }
+    }
+
+    /// Number of stuck discs around an off-lattice contact point
+    fn count_disc_neighbors(&self, x: f32, y: f32) -> usize {
+        let r = DISC_NEIGHBOR_RADIUS;
+        let mut count = 0;
+        self.for_each_disc_in(x - r, y - r, x + r, y + r, |disc, sx, sy| {
+            let dx = disc.x + sx - x;
+            let dy = disc.y + sy - y;
+            if dx * dx + dy * dy <= r * r {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    /// Number of sticky walls a disc centred at (x, y) is pressed against.
+    /// Walkers are clamped to the boundary margin, so that is where they touch.
+    fn disc_wall_contacts(&self, x: f32, y: f32) -> usize {
+        let Some((left, right, top, bottom)) = self.sticky_walls() else {
+            return 0;
+        };
+        let x_max = self.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+        let y_max = self.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+        [
+            left && x <= BOUNDARY_MARGIN,
+            right && x >= x_max,
+            top && y <= BOUNDARY_MARGIN,
+            bottom && y >= y_max,
+        ]
+        .iter()
+        .filter(|&&touching| touching)
+        .count()
+    }
+
+    /// Whether a disc centred at (x, y) would overlap a stuck disc
+    fn disc_overlaps(&self, x: f32, y: f32) -> bool {
+        // Slack for rounding on a contact computed against the same disc
+        let r = DISC_CONTACT - 0.01;
+        let mut overlaps = false;
+        self.for_each_disc_in(x - r, y - r, x + r, y + r, |disc, sx, sy| {
+            let dx = disc.x + sx - x;
+            let dy = disc.y + sy - y;
+            overlaps |= dx * dx + dy * dy < r * r;
+        });
+        overlaps
+    }
+
+    /// Spawn point for a walker that avoids the walls and sinks of a mask,
+    /// or None if no free point turned up
+    fn spawn_walker(
+        &self,
+        rng: &mut ChaCha8Rng,
+        spawn_mode: SpawnMode,
+        center_x: f32,
+        center_y: f32,
+        spawn_radius: f32,
+    ) -> Option<(f32, f32)> {
+        let attempts = if self.mask.is_empty() { 1 } else { RANDOM_SPAWN_ATTEMPTS };
+        (0..attempts)
+            .map(|_| self.spawn_particle(rng, spawn_mode, center_x, center_y, spawn_radius))
+            .find(|&(x, y)| matches!(self.mask_at(x, y), MaskCell::Open | MaskCell::NoStick))
+    }
+
+    /// Spawn a particle from the given spawn mode
+    fn spawn_particle(
+        &self,
+        rng: &mut ChaCha8Rng,
+        spawn_mode: SpawnMode,
+        center_x: f32,
+        center_y: f32,
+        spawn_radius: f32,
+    ) -> (f32, f32) {
         let w = self.grid_width as f32;
         let h = self.grid_height as f32;
 
-        match self.settings.spawn_mode {
+        match spawn_mode {
             SpawnMode::Circle => {
-                let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
-                (
-                    (center_x + spawn_radius * angle.cos()).clamp(1.0, w - 2.0),
-                    (center_y + spawn_radius * angle.sin()).clamp(1.0, h - 2.0),
-                )
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let x = center_x + spawn_radius * angle.cos();
+                let y = center_y + spawn_radius * angle.sin();
+                if self.is_periodic() {
+                    self.wrap_point(x, y)
+                } else {
+                    (x.clamp(1.0, w - 2.0), y.clamp(1.0, h - 2.0))
+                }
             }
             SpawnMode::Edges => {
                 // Random edge
-                match self.rng.gen_range(0..4) {
-                    0 => (self.rng.gen_range(1.0..w - 1.0), 1.0), // Top
-                    1 => (self.rng.gen_range(1.0..w - 1.0), h - 2.0), // Bottom
-                    2 => (1.0, self.rng.gen_range(1.0..h - 1.0)), // Left
-                    _ => (w - 2.0, self.rng.gen_range(1.0..h - 1.0)), // Right
+                match rng.gen_range(0..4) {
+                    0 => (rng.gen_range(1.0..w - 1.0), 1.0), // Top
+                    1 => (rng.gen_range(1.0..w - 1.0), h - 2.0), // Bottom
+                    2 => (1.0, rng.gen_range(1.0..h - 1.0)), // Left
+                    _ => (w - 2.0, rng.gen_range(1.0..h - 1.0)), // Right
                 }
             }
             SpawnMode::Corners => {
-                match self.rng.gen_range(0..4) {
+                match rng.gen_range(0..4) {
                     0 => (1.0, 1.0),
                     1 => (w - 2.0, 1.0),
                     2 => (1.0, h - 2.0),
@@ -370,21 +1184,22 @@ impl DlaSimulation {
                 }
             }
             SpawnMode::Random => {
-                // Random position outside spawn radius
-                loop {
-                    let x = self.rng.gen_range(1.0..w - 1.0);
-                    let y = self.rng.gen_range(1.0..h - 1.0);
-                    let dx = x - center_x;
-                    let dy = y - center_y;
+                // Random position outside spawn radius. Once the cluster fills a
+                // periodic grid there may be no such place, so give up eventually.
+                let mut pos = (center_x, center_y);
+                for _ in 0..RANDOM_SPAWN_ATTEMPTS {
+                    pos = (rng.gen_range(1.0..w - 1.0), rng.gen_range(1.0..h - 1.0));
+                    let (dx, dy) = self.center_offset(pos.0, pos.1);
                     if dx * dx + dy * dy > spawn_radius * spawn_radius * 0.5 {
-                        return (x, y);
+                        break;
                     }
                 }
+                pos
             }
-            SpawnMode::Top => (self.rng.gen_range(1.0..w - 1.0), 1.0),
-            SpawnMode::Bottom => (self.rng.gen_range(1.0..w - 1.0), h - 2.0),
-            SpawnMode::Left => (1.0, self.rng.gen_range(1.0..h - 1.0)),
-            SpawnMode::Right => (w - 2.0, self.rng.gen_range(1.0..h - 1.0)),
+            SpawnMode::Top => (rng.gen_range(1.0..w - 1.0), 1.0),
+            SpawnMode::Bottom => (rng.gen_range(1.0..w - 1.0), h - 2.0),
+            SpawnMode::Left => (1.0, rng.gen_range(1.0..h - 1.0)),
+            SpawnMode::Right => (w - 2.0, rng.gen_range(1.0..h - 1.0)),
         }
     }
 
@@ -395,11 +1210,7 @@ impl DlaSimulation {
         let mut has_any = false;
 
         for &(ndx, ndy) in offsets {
-            let nx = ix as i32 + ndx;
-            let ny = iy as i32 + ndy;
-
-            if nx >= 0 && nx < self.grid_width as i32 && ny >= 0 && ny < self.grid_height as i32 {
-                let nidx = ny as usize * self.grid_width + nx as usize;
+            if let Some(nidx) = self.neighbor_index(ix, iy, ndx, ndy) {
                 if self.grid[nidx].is_some() {
                     count += 1;
                     has_any = true;
@@ -407,24 +1218,54 @@ impl DlaSimulation {
             }
         }
 
-        (count, has_any)
+        let walls = self.wall_contacts(ix, iy);
+        (count + walls, has_any || walls > 0)
     }
 
-    /// Apply walk bias (directional and radial)
-    fn apply_walk_bias(&self, base_angle: f32, x: f32, y: f32, center_x: f32, center_y: f32) -> f32 {
+    /// Number of neighbourhood offsets around a cell that land on a sticky wall.
+    /// The outermost ring of cells, which walkers never enter, is the wall.
+    fn wall_contacts(&self, ix: usize, iy: usize) -> usize {
+        let Some((left, right, top, bottom)) = self.sticky_walls() else {
+            return 0;
+        };
+        let (w, h) = (self.grid_width as i32, self.grid_height as i32);
+        self.settings
+            .neighborhood
+            .offsets()
+            .iter()
+            .filter(|&&(ndx, ndy)| {
+                let nx = ix as i32 + ndx;
+                let ny = iy as i32 + ndy;
+                (left && nx <= 0) || (right && nx >= w - 1) || (top && ny <= 0) || (bottom && ny >= h - 1)
+            })
+            .count()
+    }
+
+    /// Whether a particle stuck at (x, y) belongs to a wall deposit: it touches
+    /// a sticky wall, or the particle it attached to is a deposit
+    fn is_deposit(&self, x: f32, y: f32, parent: Option<usize>) -> bool {
+        let walls = if self.settings.off_lattice {
+            self.disc_wall_contacts(x, y)
+        } else {
+            self.wall_contacts(x as usize, y as usize)
+        };
+        walls > 0 || parent.is_some_and(|i| self.discs[i].data.deposit)
+    }
+
+    /// Apply walk bias (the walker's directional bias and the radial bias)
+    fn apply_walk_bias(&self, base_angle: f32, x: f32, y: f32, walker: &Species) -> f32 {
         let mut angle = base_angle;
 
         // Apply directional bias
-        if self.settings.walk_bias_strength > 0.0 {
-            let bias_angle_rad = self.settings.walk_bias_angle.to_radians();
+        if walker.bias_strength > 0.0 {
+            let bias_angle_rad = walker.bias_angle.to_radians();
             let diff = (bias_angle_rad - base_angle).sin();
-            angle += self.settings.walk_bias_strength * diff;
+            angle += walker.bias_strength * diff;
         }
 
         // Apply radial bias
         if self.settings.radial_bias.abs() > 0.001 {
-            let dx = x - center_x;
-            let dy = y - center_y;
+            let (dx, dy) = self.center_offset(x, y);
             let radial_angle = dy.atan2(dx);
 
             // Positive radial_bias = toward center, negative = away
@@ -449,18 +1290,8 @@ impl DlaSimulation {
                 y = y.clamp(BOUNDARY_MARGIN, y_max);
             }
             BoundaryBehavior::Wrap => {
-                let width = x_max - BOUNDARY_MARGIN;
-                let height = y_max - BOUNDARY_MARGIN;
-                if x < BOUNDARY_MARGIN {
-                    x += width;
-                } else if x > x_max {
-                    x -= width;
-                }
-                if y < BOUNDARY_MARGIN {
-                    y += height;
-                } else if y > y_max {
-                    y -= height;
-                }
+                // The whole grid is one period of a torus
+                (x, y) = self.wrap_point(x, y);
             }
             BoundaryBehavior::Bounce => {
                 if x < BOUNDARY_MARGIN {
@@ -509,13 +1340,14 @@ impl DlaSimulation {
 
         for i in 1..=num_samples {
             let t = i as f32 / num_samples as f32;
-            let sample_x = x0 + t * dx;
-            let sample_y = y0 + t * dy;
+            let (sample_x, sample_y) = self.wrap_point(x0 + t * dx, y0 + t * dy);
 
+            // Bounds check (only a bounded grid can be left)
+            if sample_x < 0.0 || sample_y < 0.0 {
+                continue;
+            }
             let ix = sample_x as usize;
             let iy = sample_y as usize;
-
-            // Bounds check
             if ix >= self.grid_width || iy >= self.grid_height {
                 continue;
             }
@@ -527,10 +1359,9 @@ impl DlaSimulation {
                 // Also count neighbors at that position for stickiness calculation
                 let last_ix = last_empty_x as usize;
                 let last_iy = last_empty_y as usize;
-                let (neighbor_count, _) = if last_ix > 0
-                    && last_ix < self.grid_width - 1
-                    && last_iy > 0
-                    && last_iy < self.grid_height - 1
+                let (neighbor_count, _) = if last_ix < self.grid_width
+                    && last_iy < self.grid_height
+                    && self.in_sticking_range(last_ix, last_iy)
                 {
                     self.count_neighbors(last_ix, last_iy)
                 } else {
@@ -548,7 +1379,7 @@ impl DlaSimulation {
 
     /// Reset the simulation with the current seed pattern
     pub fn reset(&mut self) {
-        self.reset_with_seed(self.seed_pattern);
+        self.reset_with_seed(self.seed_pattern.clone());
     }
 
     /// Reset with a specific seed pattern
@@ -561,9 +1392,11 @@ impl DlaSimulation {
             self.grid.fill(None);
         }
 
-        self.seed_pattern = pattern;
+        self.reseed_rng();
+        self.rebuild_mask();
+        self.seed_particles = 0;
 
-        match pattern {
+        match &pattern {
             SeedPattern::Point => self.seed_point(),
             SeedPattern::Line => self.seed_line(),
             SeedPattern::Cross => self.seed_cross(),
@@ -574,11 +1407,460 @@ impl DlaSimulation {
             SeedPattern::Scatter => self.seed_scatter(),
             SeedPattern::MultiPoint => self.seed_multi_point(),
             SeedPattern::Starburst => self.seed_starburst(),
+            SeedPattern::Empty => {
+                self.particles_stuck = 0;
+                self.max_radius = 0.0;
+            }
+            SeedPattern::Image(path) => self.seed_image(path),
+            SeedPattern::Text(text) => self.seed_text(text),
+            SeedPattern::Custom(seed) => self.seed_custom(seed),
         }
+        self.seed_pattern = pattern;
 
+        if self.clusters_move() {
+            self.scatter_free_particles();
+        }
+
+        self.rebuild_discs();
+        self.rebuild_distance_field();
+        self.potential = growth::PotentialField::default();
+        self.perimeter = growth::Perimeter::default();
+        self.clusters = clusters::ClusterSet::default();
+        self.structure_moved = false;
+        self.erase_pending = false;
+        if self.clusters_move() {
+            self.rebuild_clusters();
+        }
         self.paused = false;
     }
 
+    /// Re-initialize the RNG for a new run from rng_seed (or a fresh random seed)
+    fn reseed_rng(&mut self) {
+        self.active_seed = self
+            .rng_seed
+            .unwrap_or_else(|| rand::thread_rng().gen::<u32>() as u64);
+        self.rng = ChaCha8Rng::seed_from_u64(self.active_seed);
+    }
+
+    /// Seed used by the current run (reuse it with --rng-seed to reproduce the run)
+    pub fn active_seed(&self) -> u64 {
+        self.active_seed
+    }
+
+    /// Capture the full simulation state, including the RNG position
+    pub fn to_state(&self) -> SimulationState {
+        let particles = self
+            .grid
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, cell)| {
+                cell.map(|data| StoredParticle {
+                    x: idx % self.grid_width,
+                    y: idx / self.grid_width,
+                    data,
+                })
+            })
+            .collect();
+
+        SimulationState {
+            version: STATE_VERSION,
+            grid_width: self.grid_width,
+            grid_height: self.grid_height,
+            num_particles: self.num_particles,
+            stickiness: self.stickiness,
+            particles_stuck: self.particles_stuck,
+            seed_particles: self.seed_particles,
+            max_radius: self.max_radius,
+            paused: self.paused,
+            seed_pattern: self.seed_pattern.clone(),
+            settings: self.settings.clone(),
+            rng_seed: self.rng_seed,
+            active_seed: self.active_seed,
+            rng: self.rng.clone(),
+            particles,
+            discs: self.discs.clone(),
+            clusters: self.stored_clusters(),
+        }
+    }
+
+    /// Rebuild a simulation from a saved state so it continues exactly where it stopped
+    pub fn from_state(state: SimulationState) -> Self {
+        let mut grid = vec![None; state.grid_width * state.grid_height];
+        for p in &state.particles {
+            if p.x < state.grid_width && p.y < state.grid_height {
+                grid[p.y * state.grid_width + p.x] = Some(p.data);
+            }
+        }
+
+        let mut sim = Self {
+            grid_width: state.grid_width,
+            grid_height: state.grid_height,
+            grid,
+            num_particles: state.num_particles,
+            stickiness: state.stickiness,
+            particles_stuck: state.particles_stuck,
+            seed_particles: state.seed_particles,
+            max_radius: state.max_radius,
+            paused: state.paused,
+            seed_pattern: state.seed_pattern,
+            settings: state.settings,
+            rng_seed: state.rng_seed,
+            active_seed: state.active_seed,
+            rng: state.rng,
+            distance_field: Vec::new(),
+            bound_radius: 0.0,
+            discs: Vec::new(),
+            disc_hash: SpatialHash::default(),
+            potential: growth::PotentialField::default(),
+            perimeter: growth::Perimeter::default(),
+            clusters: clusters::ClusterSet::default(),
+            structure_moved: false,
+            erase_pending: false,
+            mask: Vec::new(),
+        };
+        sim.rebuild_mask();
+        if state.discs.is_empty() {
+            sim.rebuild_discs();
+        } else {
+            sim.disc_hash = SpatialHash::new(sim.grid_width, sim.grid_height);
+            for disc in state.discs {
+                sim.push_disc(disc);
+            }
+        }
+        if let Some(clusters) = state.clusters {
+            sim.restore_clusters(clusters);
+        }
+        sim.rebuild_distance_field();
+        sim
+    }
+
+    /// Potential per grid cell (row-major, 0-1) while the dielectric breakdown
+    /// model is running, for the heat-map overlay
+    pub fn potential(&self) -> Option<&[f32]> {
+        (self.settings.growth_model == GrowthModelType::Dielectric && !self.potential.values.is_empty())
+            .then_some(self.potential.values.as_slice())
+    }
+
+    /// Every stuck particle as a disc, in stick order (seed particles first)
+    pub fn discs(&self) -> &[Disc] {
+        &self.discs
+    }
+
+    /// Call `f` for every pixel of a `width`×`height` image covered by a disc, with
+    /// the disc's index and data, when a grid cell spans `scale_x`×`scale_y` pixels.
+    /// The pixel under each centre is always included, so discs never vanish at
+    /// small scales.
+    pub fn for_each_disc_pixel(
+        &self,
+        scale_x: f32,
+        scale_y: f32,
+        width: u32,
+        height: u32,
+        mut f: impl FnMut(u32, u32, usize, &ParticleData),
+    ) {
+        let (rx, ry) = (DISC_RADIUS * scale_x, DISC_RADIUS * scale_y);
+        // On a periodic grid discs straddling an edge are drawn on both sides
+        let periodic = self.is_periodic();
+        let (w, h) = (width as i64, height as i64);
+        for (i, disc) in self.discs.iter().enumerate() {
+            let cx = disc.x * scale_x;
+            let cy = disc.y * scale_y;
+            let mut x0 = (cx - rx).floor() as i64;
+            let mut y0 = (cy - ry).floor() as i64;
+            let mut x1 = (cx + rx).ceil() as i64;
+            let mut y1 = (cy + ry).ceil() as i64;
+            if !periodic {
+                (x0, y0) = (x0.max(0), y0.max(0));
+                (x1, y1) = (x1.min(w), y1.min(h));
+            }
+            for vy in y0..y1 {
+                for vx in x0..x1 {
+                    let dx = (vx as f32 + 0.5 - cx) / rx;
+                    let dy = (vy as f32 + 0.5 - cy) / ry;
+                    if dx * dx + dy * dy <= 1.0 {
+                        f(vx.rem_euclid(w) as u32, vy.rem_euclid(h) as u32, i, &disc.data);
+                    }
+                }
+            }
+            let (vx, vy) = (cx as u32, cy as u32);
+            if vx < width && vy < height {
+                f(vx, vy, i, &disc.data);
+            }
+        }
+    }
+
+    /// Record a newly stuck particle centred at (x, y): disc, grid cell, counters
+    /// and distance field
+    fn stick_particle(&mut self, x: f32, y: f32, distance: f32, direction: f32, neighbor_count: usize, species: u8) {
+        let ix = (x.max(0.0) as usize).min(self.grid_width - 1);
+        let iy = (y.max(0.0) as usize).min(self.grid_height - 1);
+        let parent = self.nearest_disc(x, y);
+        let data = ParticleData {
+            age: self.particles_stuck,
+            distance,
+            direction,
+            neighbor_count: neighbor_count as u8,
+            deposit: self.is_deposit(x, y, parent),
+            cluster: 0,
+            species,
+            parent: parent.map(|i| i as u32),
+        };
+        // Two off-lattice discs can share a cell; the raster keeps the first
+        let cell = &mut self.grid[iy * self.grid_width + ix];
+        if cell.is_none() {
+            *cell = Some(data);
+        }
+        self.push_disc(Disc { x, y, data });
+        self.particles_stuck += 1;
+        // Wall deposits don't widen the spawn circle around the seed cluster
+        if !data.deposit {
+            self.max_radius = self.max_radius.max(distance);
+        }
+        self.stamp_distance(ix, iy);
+    }
+
+    /// Index of the closest stuck disc a particle at (x, y) is touching,
+    /// preferring the oldest on a tie
+    fn nearest_disc(&self, x: f32, y: f32) -> Option<usize> {
+        let r = if self.settings.off_lattice {
+            DISC_NEIGHBOR_RADIUS
+        } else {
+            self.settings.neighborhood.reach() + 0.01
+        };
+        let mut nearest: Option<(f32, usize)> = None;
+        self.for_each_disc_index_in(x - r, y - r, x + r, y + r, |i, sx, sy| {
+            let disc = &self.discs[i];
+            let dx = disc.x + sx - x;
+            let dy = disc.y + sy - y;
+            let d_sq = dx * dx + dy * dy;
+            let closer = nearest.is_none_or(|(best, j)| d_sq < best || (d_sq == best && i < j));
+            if d_sq <= r * r && closer {
+                nearest = Some((d_sq, i));
+            }
+        });
+        nearest.map(|(_, i)| i)
+    }
+
+    /// Index in the disc list of the particle the grid shows at (x, y): the
+    /// first disc to stick with its centre in that cell
+    pub fn disc_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.get_particle(x, y)?;
+        self.first_disc_in(y * self.grid_width + x)
+    }
+
+    /// Lowest disc index with its centre in the given grid cell
+    fn first_disc_in(&self, cell: usize) -> Option<usize> {
+        let mut first: Option<usize> = None;
+        let (fx, fy) = ((cell % self.grid_width) as f32, (cell / self.grid_width) as f32);
+        self.disc_hash.for_each_in(fx, fy, fx + 0.99, fy + 0.99, |i| {
+            if self.disc_cell(i as u32) == cell && first.is_none_or(|j| i < j) {
+                first = Some(i);
+            }
+        });
+        first
+    }
+
+    fn push_disc(&mut self, disc: Disc) {
+        self.disc_hash.insert(self.discs.len(), disc.x, disc.y);
+        self.discs.push(disc);
+    }
+
+    /// Recreate the disc list from the grid, one disc per occupied cell centre
+    fn rebuild_discs(&mut self) {
+        self.discs.clear();
+        self.disc_hash = SpatialHash::new(self.grid_width, self.grid_height);
+        for iy in 0..self.grid_height {
+            for ix in 0..self.grid_width {
+                if let Some(data) = self.grid[iy * self.grid_width + ix] {
+                    self.push_disc(Disc {
+                        x: ix as f32 + 0.5,
+                        y: iy as f32 + 0.5,
+                        data,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Drop every disc for which `keep` is false, then rebuild the distance
+    /// field. Returns the number of discs dropped.
+    fn retain_discs(&mut self, keep: impl FnMut(&Disc) -> bool) -> usize {
+        let removed = self.remove_discs(keep);
+        if removed > 0 {
+            self.rebuild_distance_field();
+        }
+        removed
+    }
+
+    /// Drop every disc for which `keep` is false. Parent links are repointed
+    /// to the new indices (children of a dropped particle become roots), and
+    /// the grid and spatial hash follow; only cells and discs from the first
+    /// dropped one on are touched. The distance field is left as it is, which
+    /// can only understate distances. Returns the number of discs dropped.
+    fn remove_discs(&mut self, mut keep: impl FnMut(&Disc) -> bool) -> usize {
+        let kept_flags: Vec<bool> = self.discs.iter().map(&mut keep).collect();
+        let Some(first) = kept_flags.iter().position(|&k| !k) else {
+            return 0;
+        };
+        let mut remap = Vec::with_capacity(kept_flags.len());
+        let mut kept = 0u32;
+        for &k in &kept_flags {
+            remap.push(k.then_some(kept));
+            kept += u32::from(k);
+        }
+        let removed = kept_flags.len() - kept as usize;
+        let removed_seeds = (first..kept_flags.len())
+            .filter(|&i| !kept_flags[i] && self.is_imported_seed(&self.discs[i].data))
+            .count();
+
+        let emptied: Vec<usize> = (first..kept_flags.len())
+            .filter(|&i| !kept_flags[i])
+            .map(|i| self.disc_cell(i as u32))
+            .collect();
+        let mut flags = kept_flags.iter();
+        self.discs.retain(|_| *flags.next().unwrap_or(&true));
+        // Parents are older than their children, so only later discs point past `first`
+        for disc in &mut self.discs[first..] {
+            disc.data.parent = disc.data.parent.and_then(|p| remap.get(p as usize).copied().flatten());
+        }
+        self.disc_hash.clear();
+        for (i, disc) in self.discs.iter().enumerate() {
+            self.disc_hash.insert(i, disc.x, disc.y);
+        }
+
+        // The grid keeps a copy of the first disc in each cell
+        for cell in emptied {
+            self.grid[cell] = self.first_disc_in(cell).map(|i| self.discs[i].data);
+        }
+        for i in first..self.discs.len() {
+            let cell = self.disc_cell(i as u32);
+            if self.first_disc_in(cell) == Some(i) {
+                self.grid[cell] = Some(self.discs[i].data);
+            }
+        }
+        self.particles_stuck = self.particles_stuck.saturating_sub(removed);
+        self.seed_particles = self.seed_particles.saturating_sub(removed_seeds);
+        removed
+    }
+
+    /// Distance from the centre of the farthest seed-grown particle
+    fn measure_max_radius(&self) -> f32 {
+        let (cx, cy) = self.center();
+        self.discs
+            .iter()
+            .filter(|d| !d.data.deposit)
+            .map(|d| {
+                let (dx, dy) = self.displacement(cx, cy, d.x, d.y);
+                (dx * dx + dy * dy).sqrt()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Lower the distance field around an occupied cell
+    fn stamp_distance(&mut self, ix: usize, iy: usize) {
+        let (dx, dy) = self.center_offset(ix as f32 + 0.5, iy as f32 + 0.5);
+        self.bound_radius = self.bound_radius.max((dx * dx + dy * dy).sqrt());
+
+        let r = MAX_FIELD_DISTANCE as i32;
+        let r_sq = r * r;
+        let (w, h) = (self.grid_width as i32, self.grid_height as i32);
+        // A periodic grid wraps the stamp around the seams (at most one period)
+        let (x0, x1, y0, y1) = if self.is_periodic() {
+            (
+                ix as i32 - r.min(w / 2),
+                ix as i32 + r.min((w - 1) / 2),
+                iy as i32 - r.min(h / 2),
+                iy as i32 + r.min((h - 1) / 2),
+            )
+        } else {
+            (
+                (ix as i32 - r).max(0),
+                (ix as i32 + r).min(w - 1),
+                (iy as i32 - r).max(0),
+                (iy as i32 + r).min(h - 1),
+            )
+        };
+        for ny in y0..=y1 {
+            let ddy = ny - iy as i32;
+            let row = ny.rem_euclid(h) as usize * self.grid_width;
+            for nx in x0..=x1 {
+                let ddx = nx - ix as i32;
+                let d_sq = ddx * ddx + ddy * ddy;
+                if d_sq <= r_sq {
+                    let d = (d_sq as f32).sqrt() as u8;
+                    let cell = &mut self.distance_field[row + nx.rem_euclid(w) as usize];
+                    if d < *cell {
+                        *cell = d;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recompute the distance field from scratch (after seeding or loading a state).
+    /// Only cells on the cluster surface are stamped; interior cells can never be
+    /// nearest to an empty cell, and are just zeroed themselves.
+    fn rebuild_distance_field(&mut self) {
+        self.distance_field = vec![MAX_FIELD_DISTANCE; self.grid_width * self.grid_height];
+        self.bound_radius = 0.0;
+
+        let w = self.grid_width;
+        let h = self.grid_height;
+        let mut surface = Vec::new();
+        for iy in 0..h {
+            for ix in 0..w {
+                if self.grid[iy * w + ix].is_none() {
+                    continue;
+                }
+                self.distance_field[iy * w + ix] = 0;
+                let on_edge = ix == 0 || iy == 0 || ix == w - 1 || iy == h - 1;
+                let exposed = on_edge
+                    || NeighborhoodType::Moore
+                        .offsets()
+                        .iter()
+                        .any(|&(dx, dy)| {
+                            let n = (iy as i32 + dy) as usize * w + (ix as i32 + dx) as usize;
+                            self.grid[n].is_none()
+                        });
+                if exposed {
+                    surface.push((ix, iy));
+                }
+            }
+        }
+        for (ix, iy) in surface {
+            self.stamp_distance(ix, iy);
+        }
+    }
+
+    /// Radius of a circle around (x, y) that is guaranteed free of sticking sites
+    /// and stays inside the grid, or 0 when no safe jump is possible.
+    fn jump_radius(&self, x: f32, y: f32, dist_from_center: f32, x_max: f32, y_max: f32, stick_reach: f32) -> f32 {
+        let ix = x as usize;
+        let iy = y as usize;
+        if ix >= self.grid_width || iy >= self.grid_height {
+            return 0.0;
+        }
+
+        // Capped field value near the cluster, bounding circle further out
+        let field = self.distance_field[iy * self.grid_width + ix] as f32;
+        let clearance = field.max(dist_from_center - self.bound_radius);
+        let mut edge = if self.is_periodic() {
+            // No edges on a torus, but a circle wider than the grid would overlap itself
+            (self.grid_width.min(self.grid_height) as f32 / 2.0) - JUMP_MARGIN
+        } else {
+            (x - BOUNDARY_MARGIN)
+                .min(x_max - x)
+                .min(y - BOUNDARY_MARGIN)
+                .min(y_max - y)
+        };
+        // Sticky walls are sticking sites too
+        if self.sticky_walls().is_some() {
+            edge -= stick_reach + JUMP_MARGIN;
+        }
+
+        (clearance - stick_reach - JUMP_MARGIN).min(edge).max(0.0)
+    }
+
     /// Helper to create seed particle data
     fn seed_particle(&self) -> ParticleData {
         ParticleData {
@@ -586,6 +1868,10 @@ impl DlaSimulation {
             distance: 0.0,
             direction: 0.0,
             neighbor_count: 0,
+            deposit: false,
+            cluster: 0,
+            species: 0,
+            parent: None,
         }
     }
 
@@ -881,14 +2167,35 @@ impl DlaSimulation {
         }
     }
 
+    /// Particles placed by an imported seed, which don't count against num_particles
+    pub fn seed_particles(&self) -> usize {
+        self.seed_particles
+    }
+
+    /// Total particles the run stops at: num_particles grown plus any imported seed
+    pub fn particle_budget(&self) -> usize {
+        self.num_particles + self.seed_particles
+    }
+
+    /// Whether `data` belongs to an imported seed (kept out of the dimension fits).
+    /// Grown particles are aged from the seed count up, so only seeds are age 0.
+    pub fn is_imported_seed(&self, data: &ParticleData) -> bool {
+        self.seed_particles > 0 && data.age == 0 && !data.deposit
+    }
+
     /// Get simulation progress as a ratio (0.0 to 1.0)
     pub fn progress(&self) -> f32 {
-        self.particles_stuck as f32 / self.num_particles as f32
+        let grown = self.particles_stuck.saturating_sub(self.seed_particles);
+        self.cluster_progress()
+            .unwrap_or(grown as f32 / self.num_particles as f32)
     }
 
-    /// Check if simulation is complete
+    /// Check if simulation is complete (one cluster left under cluster-cluster aggregation)
     pub fn is_complete(&self) -> bool {
-        self.particles_stuck >= self.num_particles
+        match self.cluster_count() {
+            Some(count) => count <= 1,
+            None => self.particles_stuck >= self.particle_budget(),
+        }
     }
 
     /// Toggle pause state
@@ -929,10 +2236,10 @@ impl DlaSimulation {
         self.stickiness = (self.stickiness + delta).clamp(0.1, 1.0);
     }
 
-    /// Calculate fractal dimension using box-counting method
+    /// Calculate fractal dimension using box-counting method, leaving out an imported seed
     /// Returns (dimension, r_squared) where r_squared indicates fit quality
     pub fn calculate_fractal_dimension(&self) -> (f32, f32) {
-        if self.particles_stuck < 50 {
+        if self.particles_stuck.saturating_sub(self.seed_particles) < 50 {
             return (0.0, 0.0); // Not enough data
         }
 
@@ -971,7 +2278,8 @@ impl DlaSimulation {
                             let y = by + dy;
                             if x < self.grid_width
                                 && y < self.grid_height
-                                && self.grid[y * self.grid_width + x].is_some()
+                                && self.grid[y * self.grid_width + x]
+                                    .is_some_and(|p| !self.is_imported_seed(&p))
                             {
                                 count += 1;
                                 break 'box_check;
@@ -1025,5 +2333,722 @@ impl DlaSimulation {
 
         (slope.abs(), r_squared)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mask::DomainMask;
+
+    /// Run a small simulation to completion with the given seed and pattern
+    fn run_seeded(seed: u64, pattern: SeedPattern) -> DlaSimulation {
+        let mut sim = DlaSimulation::new(96, 96);
+        sim.rng_seed = Some(seed);
+        sim.num_particles = 300;
+        sim.reset_with_seed(pattern);
+        while sim.step() {}
+        sim
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_grid() {
+        for pattern in [SeedPattern::Point, SeedPattern::NoisePatch, SeedPattern::Scatter] {
+            let a = run_seeded(42, pattern.clone());
+            let b = run_seeded(42, pattern);
+            assert_eq!(a.particles_stuck, b.particles_stuck);
+            assert_eq!(a.max_radius, b.max_radius);
+            assert_eq!(a.grid, b.grid);
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_give_different_grids() {
+        let a = run_seeded(1, SeedPattern::Point);
+        let b = run_seeded(2, SeedPattern::Point);
+        assert_ne!(a.grid, b.grid);
+    }
+
+    #[test]
+    fn test_reset_replays_seeded_run() {
+        let mut sim = run_seeded(7, SeedPattern::Point);
+        let first = sim.grid.clone();
+        sim.reset();
+        while sim.step() {}
+        assert_eq!(sim.grid, first);
+        assert_eq!(sim.active_seed(), 7);
+    }
+
+    /// Every cell's distance-field value is at most its true distance to the nearest stuck particle
+    fn assert_field_never_overstates(sim: &DlaSimulation) {
+        let stuck: Vec<(f32, f32)> = (0..sim.grid_height)
+            .flat_map(|y| (0..sim.grid_width).map(move |x| (x, y)))
+            .filter(|&(x, y)| sim.get_particle(x, y).is_some())
+            .map(|(x, y)| (x as f32, y as f32))
+            .collect();
+        for y in 0..sim.grid_height {
+            for x in 0..sim.grid_width {
+                let nearest = stuck
+                    .iter()
+                    .map(|&(sx, sy)| ((sx - x as f32).powi(2) + (sy - y as f32).powi(2)).sqrt())
+                    .fold(f32::INFINITY, f32::min);
+                let field = sim.distance_field[y * sim.grid_width + x] as f32;
+                assert!(field <= nearest, "field {} > distance {} at ({}, {})", field, nearest, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_field_never_overstates() {
+        let mut sim = DlaSimulation::new(80, 80);
+        sim.rng_seed = Some(5);
+        sim.num_particles = 400;
+        sim.settings.lattice_walk = false;
+        sim.settings.distance_jumps = true;
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+        assert_field_never_overstates(&sim);
+
+        // Removing particles (retain_discs) rebuilds the field
+        sim.truncate_to_age(150);
+        assert_field_never_overstates(&sim);
+
+        // Painted seeds are stamped, erased ones rebuild the field when the stroke ends
+        sim.paint(10.0, 10.0, 3.0, PaintTool::Seed);
+        assert_field_never_overstates(&sim);
+        sim.paint(40.0, 40.0, 4.0, PaintTool::Erase);
+        assert_field_never_overstates(&sim);
+        sim.end_paint_stroke();
+        assert_field_never_overstates(&sim);
+    }
+
+    #[test]
+    fn test_jumps_keep_fractal_dimension() {
+        // Mean over a few fixed seeds, since single clusters vary
+        let dimension = |jumps: bool| {
+            let seeds = [8, 9, 10, 11];
+            let total: f32 = seeds
+                .iter()
+                .map(|&seed| {
+                    let mut sim = DlaSimulation::new(160, 160);
+                    sim.rng_seed = Some(seed);
+                    sim.num_particles = 1500;
+                    sim.settings.lattice_walk = false;
+                    sim.settings.distance_jumps = jumps;
+                    sim.reset_with_seed(SeedPattern::Point);
+                    while sim.step() {}
+                    crate::analysis::rg_dimension(sim.discs()).unwrap().exponent
+                })
+                .sum();
+            total / seeds.len() as f32
+        };
+        let (off, on) = (dimension(false), dimension(true));
+        assert!((off - on).abs() < 0.15, "D without jumps {} vs with {}", off, on);
+    }
+
+    #[test]
+    fn test_parallel_result_independent_of_thread_count() {
+        let run = |threads: usize| {
+            let mut sim = DlaSimulation::new(128, 128);
+            sim.rng_seed = Some(11);
+            sim.num_particles = 1500;
+            sim.reset_with_seed(SeedPattern::Point);
+            while sim.step_parallel(threads) > 0 {}
+            sim
+        };
+        let serial = run(1);
+        let parallel = run(4);
+        assert!(serial.is_complete());
+        assert_eq!(parallel.particles_stuck, serial.particles_stuck);
+        assert_eq!(parallel.grid, serial.grid);
+    }
+
+    #[test]
+    fn test_off_lattice_discs_touch_without_overlap() {
+        let mut sim = DlaSimulation::new(128, 128);
+        sim.rng_seed = Some(3);
+        sim.num_particles = 600;
+        sim.settings.off_lattice = true;
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+        assert!(sim.is_complete());
+        assert_eq!(sim.discs().len(), sim.particles_stuck);
+
+        let discs = sim.discs();
+        for (i, a) in discs.iter().enumerate().skip(1) {
+            let dist = |b: &Disc| ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+            assert!(discs[..i].iter().all(|b| dist(b) > DISC_CONTACT - 0.02));
+            assert!(discs[..i].iter().any(|b| dist(b) < DISC_CONTACT + 0.02));
+        }
+    }
+
+    #[test]
+    fn test_sticky_floor_grows_deposits() {
+        let mut sim = DlaSimulation::new(80, 60);
+        sim.rng_seed = Some(5);
+        sim.num_particles = 200;
+        sim.settings.spawn_mode = SpawnMode::Top;
+        sim.settings.boundary_behavior = BoundaryBehavior::Stick;
+        sim.settings.sticky_edges = crate::settings::StickyEdges::Bottom;
+        sim.reset_with_seed(SeedPattern::Empty);
+        while sim.step() {}
+        assert!(sim.is_complete());
+        assert!(sim.discs().iter().all(|d| d.data.deposit));
+        // Every tree is rooted on the floor row next to the bottom wall
+        assert!(sim.discs().iter().any(|d| d.y as usize == sim.grid_height - 2));
+        assert!(sim.discs().iter().all(|d| d.y >= 1.0));
+
+        // Without sticky walls an empty grid has nothing to grow from
+        sim.settings.boundary_behavior = BoundaryBehavior::Clamp;
+        sim.reset_with_seed(SeedPattern::Empty);
+        assert!(!sim.step());
+    }
+
+    #[test]
+    fn test_deposits_follow_their_parent() {
+        let mut sim = DlaSimulation::new(80, 60);
+        sim.rng_seed = Some(9);
+        sim.num_particles = 400;
+        sim.settings.spawn_mode = SpawnMode::Top;
+        sim.settings.boundary_behavior = BoundaryBehavior::Stick;
+        sim.settings.sticky_edges = crate::settings::StickyEdges::Bottom;
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+
+        let discs = sim.discs();
+        assert!(discs.iter().any(|d| d.data.deposit));
+        assert!(discs.iter().filter(|d| !d.data.deposit).count() > 1);
+        for d in discs {
+            let on_wall = sim.wall_contacts(d.x as usize, d.y as usize) > 0;
+            let parent_deposit = d.data.parent.is_some_and(|p| discs[p as usize].data.deposit);
+            assert_eq!(d.data.deposit, on_wall || parent_deposit);
+        }
+    }
+
+    #[test]
+    fn test_escape_radius_only_lifted_for_sticky_walls_and_wrap() {
+        let mut sim = DlaSimulation::new(80, 60);
+        sim.settings.spawn_mode = SpawnMode::Edges;
+        sim.reset_with_seed(SeedPattern::Point);
+        assert!(sim.escape_dist_sq(20.0).is_finite());
+        sim.settings.boundary_behavior = BoundaryBehavior::Stick;
+        assert_eq!(sim.escape_dist_sq(20.0), f32::INFINITY);
+        sim.settings.boundary_behavior = BoundaryBehavior::Wrap;
+        assert_eq!(sim.escape_dist_sq(20.0), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_wrap_boundary_is_toroidal() {
+        let mut sim = DlaSimulation::new(40, 30);
+        sim.settings.boundary_behavior = BoundaryBehavior::Wrap;
+        sim.reset_with_seed(SeedPattern::Empty);
+        sim.grid[15 * 40] = Some(sim.seed_particle());
+        sim.grid[20] = Some(sim.seed_particle());
+        // A particle in the first column touches walkers in the last one,
+        // and one in the first row touches walkers in the last row
+        assert_eq!(sim.count_neighbors(39, 15), (1, true));
+        assert_eq!(sim.count_neighbors(20, 29), (1, true));
+        assert_eq!(sim.apply_boundary(-0.5, 31.0, 38.0, 28.0), (39.5, 1.0));
+
+        // Seed the first column (then the first row): particles stick to it
+        // from the far side of the seam
+        for vertical in [true, false] {
+            let mut sim = DlaSimulation::new(48, 40);
+            sim.rng_seed = Some(9);
+            sim.settings.boundary_behavior = BoundaryBehavior::Wrap;
+            sim.settings.spawn_mode = SpawnMode::Random;
+            sim.reset_with_seed(SeedPattern::Empty);
+            let len = if vertical { sim.grid_height } else { sim.grid_width };
+            for i in 0..len {
+                let (x, y) = if vertical { (0.5, i as f32 + 0.5) } else { (i as f32 + 0.5, 0.5) };
+                sim.paint(x, y, 0.5, PaintTool::Seed);
+            }
+            sim.num_particles = len + 300;
+            while sim.step() {}
+            assert!(sim.is_complete());
+
+            let discs = sim.discs();
+            let across = discs.iter().filter(|d| {
+                let Some(parent) = d.data.parent.map(|p| &discs[p as usize]) else {
+                    return false;
+                };
+                if vertical {
+                    d.x as usize == sim.grid_width - 1 && parent.x as usize == 0
+                } else {
+                    d.y as usize == sim.grid_height - 1 && parent.y as usize == 0
+                }
+            });
+            assert!(across.count() > 0, "nothing stuck across the seam (vertical: {})", vertical);
+        }
+    }
 
+    #[test]
+    fn test_growth_models_share_the_grid() {
+        let grow = |model: GrowthModelType, off_lattice: bool| {
+            let mut sim = DlaSimulation::new(80, 80);
+            sim.rng_seed = Some(4);
+            sim.num_particles = 300;
+            sim.settings.growth_model = model;
+            sim.settings.off_lattice = off_lattice;
+            sim.reset_with_seed(SeedPattern::Point);
+            while sim.step() {}
+            assert!(sim.is_complete(), "{:?} did not finish", model);
+            assert_eq!(sim.discs().len(), sim.particles_stuck);
+            sim.max_radius
+        };
+        for off_lattice in [false, true] {
+            let dla = grow(GrowthModelType::Dla, off_lattice);
+            grow(GrowthModelType::Ballistic, off_lattice);
+            grow(GrowthModelType::ReactionLimited, off_lattice);
+            // Eden fills in a compact blob, far smaller than a DLA cluster
+            assert!(grow(GrowthModelType::Eden, off_lattice) < dla * 0.8);
+        }
+    }
+
+    #[test]
+    fn test_eden_perimeter_tracks_the_cluster() {
+        let mut sim = DlaSimulation::new(80, 80);
+        sim.rng_seed = Some(6);
+        sim.num_particles = 400;
+        sim.settings.growth_model = GrowthModelType::Eden;
+        sim.settings.neighborhood = NeighborhoodType::VonNeumann;
+        sim.reset_with_seed(SeedPattern::Point);
+        let check = |sim: &mut DlaSimulation| {
+            growth::model(GrowthModelType::Eden).prepare(sim);
+            let mut sites = sim.perimeter.sites.clone();
+            sites.sort_unstable();
+            let (w, h) = (sim.grid_width, sim.grid_height);
+            let expected: Vec<usize> = (0..w * h)
+                .filter(|&i| {
+                    let (ix, iy) = (i % w, i / w);
+                    sim.grid[i].is_none() && sim.in_sticking_range(ix, iy) && sim.count_neighbors(ix, iy).1
+                })
+                .collect();
+            assert_eq!(sites, expected);
+        };
+
+        while sim.step() {}
+        check(&mut sim);
+        // A compact blob: the perimeter grows like the radius, not the mass
+        assert!(sim.perimeter.sites.len() < sim.particles_stuck / 2);
+
+        // Removing particles finds the perimeter afresh
+        sim.truncate_to_age(200);
+        check(&mut sim);
+        sim.num_particles = 420;
+        while sim.step() {}
+        check(&mut sim);
+    }
+
+    #[test]
+    fn test_dielectric_eta_controls_branching() {
+        let grow = |eta: f32| {
+            let mut sim = DlaSimulation::new(120, 120);
+            sim.rng_seed = Some(9);
+            sim.num_particles = 400;
+            sim.settings.growth_model = GrowthModelType::Dielectric;
+            sim.settings.dbm_eta = eta;
+            sim.reset_with_seed(SeedPattern::Point);
+            while sim.step() {}
+            assert!(sim.is_complete(), "eta {} did not finish", eta);
+
+            // The field is updated before each step, so the last particle isn't grounded yet
+            let potential = sim.potential().expect("potential solved");
+            for (i, &phi) in potential.iter().enumerate() {
+                assert!((0.0..=1.0).contains(&phi));
+                if sim.grid[i].is_some_and(|p| p.age + 1 < sim.particles_stuck) {
+                    assert_eq!(phi, 0.0);
+                }
+            }
+            sim.max_radius
+        };
+        // Eta 0 fills a compact blob, eta 2 shoots out sparse needles
+        assert!(grow(0.0) * 1.5 < grow(2.0));
+    }
+
+    #[test]
+    fn test_dielectric_potential_is_local_and_walls_insulate() {
+        // A closed wall box away from the seed
+        let mask = DomainMask::from_fn(80, 80, |x, y| {
+            let edge = x == 5 || x == 20 || y == 5 || y == 20;
+            if edge && (5..=20).contains(&x) && (5..=20).contains(&y) {
+                MaskCell::Wall
+            } else {
+                MaskCell::Open
+            }
+        });
+        let mut sim = DlaSimulation::new(80, 80);
+        sim.rng_seed = Some(4);
+        sim.num_particles = 150;
+        sim.settings.growth_model = GrowthModelType::Dielectric;
+        sim.settings.mask = Some(mask);
+        sim.reset_with_seed(SeedPattern::Point);
+        sim.step();
+        // No field gets through the walls, so the inside stays at the electrode's potential
+        assert_eq!(sim.potential().unwrap()[12 * 80 + 12], 1.0);
+
+        // Between full solves, grounding a particle only relaxes the cells around it
+        let before = sim.potential().unwrap().to_vec();
+        let grounded = *sim.discs().last().unwrap();
+        sim.step();
+        for (i, (&old, &new)) in before.iter().zip(sim.potential().unwrap()).enumerate() {
+            if old != new {
+                let (x, y) = ((i % 80) as f32 + 0.5, (i / 80) as f32 + 0.5);
+                let reach = (x - grounded.x).abs().max((y - grounded.y).abs());
+                assert!(reach <= 9.0, "cell ({}, {}) changed", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cluster_cluster_aggregation_merges_to_one() {
+        let mut sim = DlaSimulation::new(60, 60);
+        sim.rng_seed = Some(5);
+        sim.num_particles = 150;
+        sim.settings.growth_model = GrowthModelType::ClusterCluster;
+        sim.settings.boundary_behavior = BoundaryBehavior::Wrap;
+        sim.reset_with_seed(SeedPattern::Point);
+        assert_eq!(sim.particles_stuck, 150);
+        assert!(sim.cluster_count().unwrap() > 100);
+
+        for _ in 0..2000 {
+            sim.step();
+        }
+        // The disc hash follows the clusters as they move
+        for (i, disc) in sim.discs().iter().enumerate() {
+            assert_eq!(sim.disc_at(disc.x as usize, disc.y as usize), Some(i));
+        }
+
+        while sim.step() {}
+        assert!(sim.is_complete());
+        assert_eq!(sim.cluster_count(), Some(1));
+
+        // Particles moved with their clusters: none lost, and grid and discs agree
+        assert_eq!(sim.grid.iter().filter(|c| c.is_some()).count(), 150);
+        let label = sim.discs()[0].data.cluster;
+        for disc in sim.discs() {
+            assert_eq!(disc.data.cluster, label);
+            let cell = sim.get_particle(disc.x as usize, disc.y as usize);
+            assert_eq!(cell.map(|p| p.cluster), Some(label));
+        }
+    }
+
+    #[test]
+    fn test_stick_matrix_controls_species_contacts() {
+        let mut sim = DlaSimulation::new(100, 100);
+        sim.rng_seed = Some(11);
+        sim.num_particles = 300;
+        sim.settings.species = vec![Species::default(), Species::default()];
+        // Each species sticks only to the other one
+        sim.settings.stick_matrix = vec![0.0, 1.0, 1.0, 0.0];
+        sim.reset_with_seed(SeedPattern::Point);
+        while sim.step() {}
+        assert!(sim.is_complete());
+
+        let counts = sim.discs().iter().fold([0; 2], |mut counts, d| {
+            counts[d.data.species as usize] += 1;
+            counts
+        });
+        assert!(counts[0] > 50 && counts[1] > 50, "{:?}", counts);
+
+        // Every particle touched one of the other species when it stuck
+        let offsets = sim.settings.neighborhood.offsets();
+        for y in 0..sim.grid_height {
+            for x in 0..sim.grid_width {
+                let Some(p) = sim.get_particle(x, y) else { continue };
+                if p.age == 0 {
+                    continue;
+                }
+                let mixed = offsets.iter().any(|&(dx, dy)| {
+                    sim.neighbor_index(x, y, dx, dy)
+                        .and_then(|n| sim.grid[n])
+                        .is_some_and(|q| q.age < p.age && q.species != p.species)
+                });
+                assert!(mixed, "particle {} at ({}, {}) stuck to its own species", p.age, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_initial_clusters_respect_stick_matrix() {
+        let mut sim = DlaSimulation::new(40, 40);
+        sim.rng_seed = Some(6);
+        sim.num_particles = 900;
+        sim.settings.growth_model = GrowthModelType::ClusterCluster;
+        sim.settings.species = vec![Species::default(), Species::default()];
+        // Species never stick to each other
+        sim.settings.stick_matrix = vec![1.0, 0.0, 0.0, 1.0];
+        sim.reset_with_seed(SeedPattern::Empty);
+        sim.step();
+
+        // Dense scattering leaves many mixed pairs touching, but no mixed cluster
+        let offsets = sim.settings.neighborhood.offsets();
+        let mut mixed_contacts = 0;
+        for y in 0..sim.grid_height {
+            for x in 0..sim.grid_width {
+                let Some(p) = sim.get_particle(x, y) else { continue };
+                for &(dx, dy) in offsets {
+                    let Some(q) = sim.neighbor_index(x, y, dx, dy).and_then(|n| sim.grid[n]) else {
+                        continue;
+                    };
+                    if q.species != p.species {
+                        mixed_contacts += 1;
+                        assert_ne!(q.cluster, p.cluster, "mixed cluster at ({}, {})", x, y);
+                    }
+                }
+            }
+        }
+        assert!(mixed_contacts > 0);
+    }
+
+    #[test]
+    fn test_mask_keeps_particles_off_masked_cells() {
+        // Sink on the left, no-stick band along the top, wall on the right
+        let mask = DomainMask::from_fn(100, 100, |x, y| {
+            if x >= 70 {
+                MaskCell::Wall
+            } else if x < 10 {
+                MaskCell::Sink
+            } else if y < 20 {
+                MaskCell::NoStick
+            } else {
+                MaskCell::Open
+            }
+        });
+        for off_lattice in [false, true] {
+            let mut sim = DlaSimulation::new(100, 100);
+            sim.rng_seed = Some(5);
+            sim.num_particles = 300;
+            sim.settings.spawn_mode = SpawnMode::Random;
+            sim.settings.off_lattice = off_lattice;
+            sim.settings.mask = Some(mask.clone());
+            sim.reset_with_seed(SeedPattern::Point);
+            assert!(sim.has_mask());
+            for _ in 0..200_000 {
+                if !sim.step() {
+                    break;
+                }
+            }
+            assert!(sim.is_complete(), "off_lattice={}", off_lattice);
+            for disc in sim.discs().iter().filter(|d| d.data.age > 0) {
+                assert_eq!(
+                    sim.mask_at(disc.x, disc.y),
+                    MaskCell::Open,
+                    "off_lattice={} particle {} at ({}, {})",
+                    off_lattice,
+                    disc.data.age,
+                    disc.x,
+                    disc.y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_seed_sets_radius_from_shape() {
+        let mut sim = DlaSimulation::new(160, 100);
+        sim.reset_with_seed(SeedPattern::Text("Hi!".to_string()));
+        let (cx, cy) = sim.center();
+        let mut count = 0;
+        let mut far: f32 = 0.0;
+        for y in 0..sim.grid_height {
+            for x in 0..sim.grid_width {
+                if sim.get_particle(x, y).is_some() {
+                    count += 1;
+                    far = far.max(((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt());
+                }
+            }
+        }
+        // Three glyphs scaled up to a good share of the grid
+        assert!(count > 100, "only {} seed particles", count);
+        assert_eq!(sim.particles_stuck, count);
+        assert!((sim.max_radius - far).abs() < 1e-3);
+        assert!(sim.spawn_radius() > far);
+
+        // A blank string or unreadable image falls back to a point
+        assert!(SeedPattern::Text("  ".to_string()).validate().is_err());
+        sim.reset_with_seed(SeedPattern::Text(String::new()));
+        assert_eq!(sim.particles_stuck, 1);
+        let missing = SeedPattern::Image("/nonexistent/seed.png".into());
+        assert!(missing.validate().is_err());
+        sim.reset_with_seed(missing);
+        assert_eq!(sim.particles_stuck, 1);
+    }
+
+    #[test]
+    fn test_imported_seed_does_not_use_budget() {
+        let mut sim = DlaSimulation::new(160, 100);
+        sim.rng_seed = Some(2);
+        sim.num_particles = 300;
+        sim.reset_with_seed(SeedPattern::Text("HELLO".to_string()));
+        let seed = sim.seed_particles();
+        assert!(seed > 300, "only {} seed particles", seed);
+        assert_eq!(sim.particles_stuck, seed);
+        while sim.step() {}
+        assert_eq!(sim.particles_stuck, seed + 300);
+        assert!(sim.is_complete());
+
+        // The seed stays out of the dimension fits
+        let grown = crate::analysis::cluster_discs(&sim);
+        assert_eq!(grown.len(), 300);
+        assert!(grown.iter().all(|d| d.data.age >= seed));
+        let sample = crate::analysis::analyze_simulation(&sim);
+        assert_eq!(sample.particles, 300);
+        assert_eq!(sample.rg_dimension, 0.0);
+
+        // Built-in seeds still count against the budget
+        sim.reset_with_seed(SeedPattern::Point);
+        assert_eq!(sim.seed_particles(), 0);
+        assert_eq!(sim.particle_budget(), 300);
+    }
+
+    #[test]
+    fn test_painted_seeds_do_not_use_budget() {
+        let mut sim = DlaSimulation::new(96, 96);
+        sim.rng_seed = Some(4);
+        sim.num_particles = 300;
+        sim.reset_with_seed(SeedPattern::Empty);
+        sim.paint(48.0, 48.0, 6.0, PaintTool::Seed);
+        let painted = sim.particles_stuck;
+        assert!(painted > 100);
+        assert_eq!(sim.seed_particles(), painted);
+
+        // Erasing seeds hands their room back to the seed, not to growth
+        sim.paint(48.0, 48.0, 2.0, PaintTool::Erase);
+        sim.end_paint_stroke();
+        let seed = sim.seed_particles();
+        assert!(seed < painted);
+        assert_eq!(sim.particles_stuck, seed);
+
+        while sim.step() {}
+        assert_eq!(sim.particles_stuck, seed + 300);
+        assert_eq!(crate::analysis::cluster_discs(&sim).len(), 300);
+    }
+
+    #[test]
+    fn test_paint_and_custom_seed_roundtrip() {
+        let mut sim = DlaSimulation::new(80, 80);
+        sim.reset_with_seed(SeedPattern::Empty);
+        assert!(!sim.has_mask());
+
+        // A seed blob, a wall bar, then an eraser through the middle of the blob
+        sim.paint(20.0, 20.0, 3.0, PaintTool::Seed);
+        let painted = sim.particles_stuck;
+        assert!(painted > 20);
+        assert_eq!(sim.discs().len(), painted);
+        sim.paint(60.0, 40.0, 2.0, PaintTool::Wall);
+        assert!(sim.has_mask());
+        assert_eq!(sim.mask_cell(60, 40), MaskCell::Wall);
+        sim.paint(20.0, 20.0, 1.0, PaintTool::Erase);
+        assert!(sim.particles_stuck < painted);
+        assert_eq!(sim.discs().len(), sim.particles_stuck);
+        assert!(sim.get_particle(20, 20).is_none());
+
+        // Seeds can't go on walls, and walls survive a reset through the settings
+        sim.paint(60.0, 40.0, 1.0, PaintTool::Seed);
+        assert!(sim.get_particle(60, 40).is_none());
+        let seed = crate::custom_seed::CustomSeed::from_simulation("blob", &sim);
+        assert!(seed.has_seeds());
+        let stuck = sim.particles_stuck;
+        sim.reset_with_seed(SeedPattern::Custom(seed));
+        assert_eq!(sim.particles_stuck, stuck);
+        assert_eq!(sim.mask_cell(60, 40), MaskCell::Wall);
+        assert!(sim.get_particle(20, 20).is_none());
+        assert!(sim.get_particle(18, 18).is_some());
+
+        // Erasing the last wall drops the mask
+        sim.paint(60.0, 40.0, 4.0, PaintTool::Erase);
+        assert!(!sim.has_mask());
+        assert!(sim.settings.mask.is_none());
+    }
+
+    #[test]
+    fn test_erase_stroke_shrinks_max_radius() {
+        let mut sim = DlaSimulation::new(80, 80);
+        sim.reset_with_seed(SeedPattern::Empty);
+        sim.paint(40.0, 40.0, 3.0, PaintTool::Seed);
+        sim.paint(70.0, 40.0, 2.0, PaintTool::Seed);
+        sim.paint(40.0, 70.0, 2.0, PaintTool::Seed);
+        sim.max_radius = sim.measure_max_radius();
+        assert!(sim.max_radius > 29.0);
+
+        // One drag over both far blobs; the field and radius wait for the stroke to end
+        let stuck = sim.particles_stuck;
+        sim.paint_line((70.0, 40.0), (70.0, 40.0), 3.0, PaintTool::Erase);
+        sim.paint_line((70.0, 40.0), (40.0, 70.0), 3.0, PaintTool::Erase);
+        assert!(sim.particles_stuck < stuck);
+        assert!(sim.max_radius > 29.0);
+        assert_field_never_overstates(&sim);
+        sim.end_paint_stroke();
+        assert!(sim.max_radius < 5.0);
+        assert_field_never_overstates(&sim);
+
+        assert_eq!(sim.discs().len(), sim.particles_stuck);
+        for (i, disc) in sim.discs().iter().enumerate() {
+            let (cx, cy) = (disc.x as usize, disc.y as usize);
+            assert!(sim.disc_at(cx, cy).is_some_and(|first| first <= i));
+            if sim.disc_at(cx, cy) == Some(i) {
+                assert_eq!(sim.get_particle(cx, cy), Some(disc.data));
+            }
+        }
+        assert!(sim.get_particle(70, 40).is_none() && sim.get_particle(55, 55).is_none());
+    }
+
+    #[test]
+    fn test_truncate_to_age_matches_shorter_run() {
+        let mut short = DlaSimulation::new(96, 96);
+        short.rng_seed = Some(42);
+        short.num_particles = 101;
+        short.reset_with_seed(SeedPattern::Point);
+        while short.step() {}
+
+        let mut sim = run_seeded(42, SeedPattern::Point);
+        assert_eq!(sim.latest_age(), 299);
+        assert_eq!(sim.truncate_to_age(100), 199);
+        assert_eq!(sim.particles_stuck, 101);
+        assert_eq!(sim.latest_age(), 100);
+        for y in 0..96 {
+            for x in 0..96 {
+                assert_eq!(sim.get_particle(x, y), short.get_particle(x, y));
+            }
+        }
+
+        // Growth resumes from the cut with ages carrying on
+        while sim.step() {}
+        assert_eq!(sim.particles_stuck, 300);
+        assert_eq!(sim.latest_age(), 299);
+    }
+
+    #[test]
+    fn test_parent_links_form_a_tree() {
+        let mut sim = run_seeded(11, SeedPattern::Point);
+        let reach = sim.settings.neighborhood.reach() + 0.01;
+        for (i, disc) in sim.discs().iter().enumerate().skip(1) {
+            let p = disc.data.parent.expect("grown particle without a parent") as usize;
+            assert!(p < i);
+            let parent = sim.discs()[p];
+            assert!((parent.x - disc.x).hypot(parent.y - disc.y) <= reach);
+        }
+
+        let n = sim.discs().len();
+        let tree = crate::tree::GrowthTree::new(sim.discs());
+        let stats = tree.stats();
+        assert_eq!(stats.roots, 1);
+        assert_eq!(tree.descendants(0) as usize, n - 1);
+        assert!(stats.tips > 1 && stats.tips < n);
+        assert!(stats.longest_path as usize >= (sim.max_radius / reach) as usize);
+        assert!(stats.strahler_order >= 2 && tree.order(0) == stats.strahler_order);
+        assert!(stats.branching_ratio > 1.0);
+        assert!((0..n).all(|i| (0.0..=1.0).contains(&tree.shade(i, crate::settings::ColorMode::Descendants))));
+
+        // Erasing part of the cluster repoints the surviving links
+        let (x, y) = (sim.discs()[n / 2].x, sim.discs()[n / 2].y);
+        sim.paint(x, y, 2.0, PaintTool::Erase);
+        for (i, disc) in sim.discs().iter().enumerate() {
+            assert!(disc.data.parent.is_none_or(|p| (p as usize) < i));
+            let (cx, cy) = (disc.x as usize, disc.y as usize);
+            if sim.disc_at(cx, cy) == Some(i) {
+                assert_eq!(sim.get_particle(cx, cy), Some(disc.data));
+            }
+        }
+    }
 }
@@ -0,0 +1,376 @@
+//! Cluster-cluster aggregation: the grid starts full of free particles, and
+//! whole clusters random-walk and merge when they touch.
+//!
+//! Clusters are tracked with a union-find over particles (indices into the disc
+//! list) and a map from grid cells to the particle in them, kept next to the
+//! grid's `ParticleData`. Every particle also carries its cluster's label for
+//! colouring.
+
+use super::{DlaSimulation, ParticleData};
+use crate::mask::MaskCell;
+use crate::state::StoredClusters;
+use rand::Rng;
+
+/// Owner of an empty grid cell
+const EMPTY: u32 = u32::MAX;
+/// A cluster moves one cell along a lattice axis per step
+const MOVES: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// Tries at finding an empty cell for each free particle scattered at reset
+const SCATTER_ATTEMPTS: usize = 64;
+
+/// Which particles belong together
+#[derive(Debug, Clone, Default)]
+pub(super) struct ClusterSet {
+    /// Union-find parent of each particle
+    parent: Vec<u32>,
+    /// Particles of each cluster, stored at its root (empty elsewhere)
+    members: Vec<Vec<u32>>,
+    /// Roots of the clusters that haven't merged yet
+    roots: Vec<u32>,
+    /// Particle in each grid cell, or EMPTY
+    owner: Vec<u32>,
+    /// Size of the smallest cluster, which moves on every attempt
+    min_size: usize,
+    /// Clusters at the start of the run, for progress
+    initial: usize,
+}
+
+impl ClusterSet {
+    /// Root of a particle's cluster, halving the path on the way
+    fn find(&mut self, mut i: u32) -> u32 {
+        while self.parent[i as usize] != i {
+            let grandparent = self.parent[self.parent[i as usize] as usize];
+            self.parent[i as usize] = grandparent;
+            i = grandparent;
+        }
+        i
+    }
+
+    /// Join two clusters, the smaller into the larger.
+    /// Returns (surviving root, absorbed root), or None if they were already one.
+    fn union(&mut self, a: u32, b: u32) -> Option<(u32, u32)> {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return None;
+        }
+        let (keep, absorb) = if self.members[a as usize].len() >= self.members[b as usize].len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[absorb as usize] = keep;
+        let absorbed = std::mem::take(&mut self.members[absorb as usize]);
+        self.members[keep as usize].extend(absorbed);
+        Some((keep, absorb))
+    }
+
+    fn refresh_min_size(&mut self) {
+        self.min_size = self
+            .roots
+            .iter()
+            .map(|&r| self.members[r as usize].len())
+            .min()
+            .unwrap_or(1);
+    }
+}
+
+impl DlaSimulation {
+    /// Whether clusters move instead of walkers growing onto a fixed cluster
+    pub(super) fn clusters_move(&self) -> bool {
+        self.settings.growth_model == crate::settings::GrowthModelType::ClusterCluster
+    }
+
+    /// Clusters still apart under cluster-cluster aggregation, or None for
+    /// other growth models (or before the first cluster step after switching)
+    pub fn cluster_count(&self) -> Option<usize> {
+        (self.clusters_move() && !self.discs.is_empty() && self.clusters.parent.len() == self.discs.len())
+            .then_some(self.clusters.roots.len())
+    }
+
+    /// Fraction of the initial clusters that have merged (0-1)
+    pub(super) fn cluster_progress(&self) -> Option<f32> {
+        let count = self.cluster_count()?;
+        let initial = self.clusters.initial.max(2);
+        Some(1.0 - (count.saturating_sub(1)) as f32 / (initial - 1) as f32)
+    }
+
+    /// Cluster bookkeeping for a state file, or None until the first cluster
+    /// step has grouped the particles
+    pub(super) fn stored_clusters(&self) -> Option<StoredClusters> {
+        let set = &self.clusters;
+        (!self.discs.is_empty() && set.parent.len() == self.discs.len()).then(|| StoredClusters {
+            parent: set.parent.clone(),
+            roots: set.roots.clone(),
+            members: set.roots.iter().map(|&r| set.members[r as usize].clone()).collect(),
+            min_size: set.min_size,
+            initial: set.initial,
+        })
+    }
+
+    /// Restore saved cluster bookkeeping (after the discs), so a resumed run
+    /// doesn't regroup and re-roll its contacts. Mismatched data is ignored
+    /// and the clusters are rebuilt on the next step instead.
+    pub(super) fn restore_clusters(&mut self, stored: StoredClusters) {
+        let n = self.discs.len();
+        if stored.parent.len() != n
+            || stored.roots.len() != stored.members.len()
+            || stored.roots.iter().any(|&r| r as usize >= n)
+        {
+            return;
+        }
+        let mut members = vec![Vec::new(); n];
+        for (&root, list) in stored.roots.iter().zip(stored.members) {
+            members[root as usize] = list;
+        }
+        let mut owner = vec![EMPTY; self.grid.len()];
+        for i in 0..n as u32 {
+            owner[self.disc_cell(i)] = i;
+        }
+        self.clusters = ClusterSet {
+            parent: stored.parent,
+            members,
+            roots: stored.roots,
+            owner,
+            min_size: stored.min_size,
+            initial: stored.initial,
+        };
+    }
+
+    /// Fill the grid with free particles at random empty cells until it holds
+    /// the particle budget (called at reset, after the seed pattern is placed).
+    /// Each particle's species is drawn by share.
+    pub(super) fn scatter_free_particles(&mut self) {
+        let target = self.particle_budget().min(self.max_particles());
+        let (w, h) = (self.grid_width, self.grid_height);
+        if w < 3 || h < 3 {
+            return;
+        }
+        while self.particles_stuck < target {
+            let mut placed = false;
+            for _ in 0..SCATTER_ATTEMPTS {
+                let ix = self.rng.gen_range(1..w - 1);
+                let iy = self.rng.gen_range(1..h - 1);
+                let idx = iy * w + ix;
+                if self.grid[idx].is_some() || self.mask_cell(ix, iy) != MaskCell::Open {
+                    continue;
+                }
+                let (dx, dy) = self.center_offset(ix as f32 + 0.5, iy as f32 + 0.5);
+                let mut rng = self.rng.clone();
+                let species = self.pick_species(&mut rng);
+                self.rng = rng;
+                self.grid[idx] = Some(ParticleData {
+                    age: self.particles_stuck,
+                    distance: (dx * dx + dy * dy).sqrt(),
+                    direction: dy.atan2(dx),
+                    species,
+                    ..Default::default()
+                });
+                self.particles_stuck += 1;
+                placed = true;
+                break;
+            }
+            if !placed {
+                break;
+            }
+        }
+    }
+
+    /// Group the particles into clusters of touching particles and label them.
+    /// Touching particles join with the same stick roll as clusters that meet
+    /// while moving.
+    pub(super) fn rebuild_clusters(&mut self) {
+        let n = self.discs.len();
+        let mut set = ClusterSet {
+            parent: (0..n as u32).collect(),
+            members: (0..n as u32).map(|i| vec![i]).collect(),
+            owner: vec![EMPTY; self.grid.len()],
+            ..Default::default()
+        };
+        for i in 0..n {
+            let idx = self.disc_cell(i as u32);
+            set.owner[idx] = i as u32;
+        }
+        let offsets = self.settings.neighborhood.offsets();
+        let mut rng = self.rng.clone();
+        for i in 0..n {
+            let idx = self.disc_cell(i as u32);
+            let species = self.discs[i].data.species;
+            for &(dx, dy) in offsets {
+                let Some(nidx) = self.neighbor_index(idx % self.grid_width, idx / self.grid_width, dx, dy) else {
+                    continue;
+                };
+                let other = set.owner[nidx];
+                // Each touching pair is rolled once, from its lower index
+                if other == EMPTY || (other as usize) < i {
+                    continue;
+                }
+                let affinity = self.settings.pair_stickiness(species, self.discs[other as usize].data.species);
+                if rng.gen::<f32>() < self.stickiness * affinity {
+                    set.union(i as u32, other);
+                }
+            }
+        }
+        self.rng = rng;
+        set.roots = (0..n as u32).filter(|&i| set.parent[i as usize] == i).collect();
+        set.initial = set.roots.len();
+        set.refresh_min_size();
+
+        for &root in &set.roots {
+            for &m in &set.members[root as usize] {
+                self.set_cluster_label(m, root);
+            }
+        }
+        self.clusters = set;
+    }
+
+    /// Advance cluster-cluster aggregation by one move attempt: a random cluster
+    /// steps one cell if its mobility roll passes and the cells ahead are free,
+    /// then merges with any cluster it touches (subject to stickiness and the
+    /// stick matrix).
+    /// Returns false once a single cluster is left.
+    pub(super) fn step_clusters(&mut self) -> bool {
+        if self.clusters.parent.len() != self.discs.len() {
+            self.rebuild_clusters();
+        }
+        if self.clusters.roots.len() <= 1 {
+            return false;
+        }
+
+        let mut rng = self.rng.clone();
+        let root = self.clusters.roots[rng.gen_range(0..self.clusters.roots.len())];
+        let size = self.clusters.members[root as usize].len();
+        let mobility = (self.clusters.min_size as f32 / size as f32).powf(self.settings.mobility_exponent);
+        if rng.gen::<f32>() < mobility {
+            let (dx, dy) = MOVES[rng.gen_range(0..MOVES.len())];
+            if let Some(touching) = self.move_cluster(root, dx, dy) {
+                for (other, affinity) in touching {
+                    if rng.gen::<f32>() < self.stickiness * affinity {
+                        self.merge_clusters(root, other);
+                    }
+                }
+            }
+        }
+        self.rng = rng;
+        true
+    }
+
+    /// Shift every particle of a cluster by (dx, dy) if all the cells ahead are
+    /// free (or its own). Returns the roots of the other clusters it now touches,
+    /// each with the best stick-matrix entry over the touching pairs, or None if
+    /// the move was blocked.
+    fn move_cluster(&mut self, root: u32, dx: i32, dy: i32) -> Option<Vec<(u32, f32)>> {
+        let w = self.grid_width;
+        let members = std::mem::take(&mut self.clusters.members[root as usize]);
+        let mut targets = Vec::with_capacity(members.len());
+        for &m in &members {
+            let idx = self.disc_cell(m);
+            let target = self
+                .neighbor_index(idx % w, idx / w, dx, dy)
+                .filter(|&t| self.in_sticking_range(t % w, t / w))
+                .filter(|&t| {
+                    let owner = self.clusters.owner[t];
+                    owner == EMPTY || self.clusters.find(owner) == root
+                });
+            match target {
+                Some(t) => targets.push(t),
+                None => {
+                    self.clusters.members[root as usize] = members;
+                    return None;
+                }
+            }
+        }
+
+        // Lift the whole cluster first so it can move into its own cells
+        let lifted: Vec<Option<ParticleData>> = members
+            .iter()
+            .map(|&m| {
+                let idx = self.disc_cell(m);
+                self.clusters.owner[idx] = EMPTY;
+                self.grid[idx].take()
+            })
+            .collect();
+        for ((&m, &t), data) in members.iter().zip(&targets).zip(lifted) {
+            self.grid[t] = data;
+            self.clusters.owner[t] = m;
+            let disc = &mut self.discs[m as usize];
+            self.disc_hash.remove(m as usize, disc.x, disc.y);
+            disc.x = (t % w) as f32 + 0.5;
+            disc.y = (t / w) as f32 + 0.5;
+            self.disc_hash.insert(m as usize, disc.x, disc.y);
+        }
+        self.clusters.members[root as usize] = members;
+        self.structure_moved = true;
+
+        let mut touching: Vec<(u32, f32)> = Vec::new();
+        for &t in &targets {
+            let mover = self.grid[t].map_or(0, |p| p.species);
+            for &(ndx, ndy) in self.settings.neighborhood.offsets() {
+                let Some(n) = self.neighbor_index(t % w, t / w, ndx, ndy) else {
+                    continue;
+                };
+                let owner = self.clusters.owner[n];
+                if owner == EMPTY {
+                    continue;
+                }
+                let other = self.clusters.find(owner);
+                if other == root {
+                    continue;
+                }
+                let stuck = self.grid[n].map_or(0, |p| p.species);
+                let affinity = self.settings.pair_stickiness(mover, stuck);
+                match touching.iter_mut().find(|(r, _)| *r == other) {
+                    Some((_, best)) => *best = best.max(affinity),
+                    None => touching.push((other, affinity)),
+                }
+            }
+        }
+        Some(touching)
+    }
+
+    /// Merge the clusters of two particles, relabelling the smaller one
+    fn merge_clusters(&mut self, a: u32, b: u32) {
+        let Some((keep, absorb)) = self.clusters.union(a, b) else {
+            return;
+        };
+        let label = self.discs[keep as usize].data.cluster;
+        let members = std::mem::take(&mut self.clusters.members[keep as usize]);
+        for &m in &members {
+            if self.discs[m as usize].data.cluster != label {
+                self.set_cluster_label(m, label);
+            }
+        }
+        self.clusters.members[keep as usize] = members;
+        self.clusters.roots.retain(|&r| r != absorb);
+        self.clusters.refresh_min_size();
+    }
+
+    /// Grid index of the cell holding particle `i`
+    pub(super) fn disc_cell(&self, i: u32) -> usize {
+        let disc = &self.discs[i as usize];
+        let ix = (disc.x.max(0.0) as usize).min(self.grid_width - 1);
+        let iy = (disc.y.max(0.0) as usize).min(self.grid_height - 1);
+        iy * self.grid_width + ix
+    }
+
+    fn set_cluster_label(&mut self, i: u32, label: u32) {
+        self.discs[i as usize].data.cluster = label;
+        let idx = self.disc_cell(i);
+        if let Some(data) = &mut self.grid[idx] {
+            data.cluster = label;
+        }
+    }
+
+    /// After clusters have moved, rebuild everything that assumes a fixed
+    /// structure before another growth model takes over (the disc hash is kept
+    /// up to date as clusters move)
+    pub(super) fn settle_clusters(&mut self) {
+        if !std::mem::take(&mut self.structure_moved) {
+            return;
+        }
+        self.max_radius = self.measure_max_radius();
+        self.rebuild_distance_field();
+        self.potential = Default::default();
+        self.perimeter = Default::default();
+        self.clusters = ClusterSet::default();
+    }
+}
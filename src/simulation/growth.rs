@@ -0,0 +1,662 @@
+//! Growth models: the rule deciding where the next particle joins the cluster.
+//!
+//! Every model reads the shared grid and returns a [`WalkOutcome`], so sticking,
+//! deposits, rendering and analysis work the same whichever one is selected.
+
+use super::{DlaSimulation, WalkOutcome, BOUNDARY_MARGIN, DISC_CONTACT};
+use crate::mask::MaskCell;
+use crate::settings::{BoundaryBehavior, GrowthModelType, NeighborhoodType, SpawnMode};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Sticking chance of a reaction-limited walker, relative to a DLA walker
+const REACTION_STICKING: f32 = 0.05;
+/// Distance a lattice ballistic particle moves between neighbour checks.
+/// Below one cell so diagonal flights can't slip past a neighbour.
+const BALLISTIC_STEP: f32 = 0.5;
+/// Flight segment tested for disc contact at once by off-lattice ballistic particles
+const BALLISTIC_DISC_SEGMENT: f32 = 4.0;
+/// Largest change per relaxation sweep at which the potential counts as solved
+const DBM_TOLERANCE: f32 = 1e-4;
+/// Cap on full relaxation sweeps, as a multiple of the longer grid side
+const DBM_MAX_SWEEPS_PER_CELL: usize = 8;
+/// Half-width of the window relaxed around each newly stuck particle
+const DBM_LOCAL_RADIUS: usize = 8;
+/// Slot of a grid cell that isn't on the Eden perimeter
+const NOT_SITE: u32 = u32::MAX;
+/// Relaxation sweeps over that window per new particle
+const DBM_LOCAL_SWEEPS: usize = 8;
+/// New particles between full re-solves; local updates drift in between
+const DBM_REFRESH_INTERVAL: usize = 64;
+
+/// A rule for growing one particle onto the cluster.
+///
+/// Growing only reads the simulation, so a parallel batch can grow many
+/// particles against the same grid from several threads.
+pub(crate) trait GrowthModel: Sync {
+    /// Bring any state the model keeps on the simulation up to date with the
+    /// grid. Called before every step (or parallel batch).
+    fn prepare(&self, _sim: &mut DlaSimulation) {}
+
+    /// Find where one new particle of `species` sticks, or give up with `Respawn`
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome;
+}
+
+/// The model implementing a growth model setting
+pub(crate) fn model(kind: GrowthModelType) -> &'static dyn GrowthModel {
+    match kind {
+        GrowthModelType::Dla => &Diffusion,
+        GrowthModelType::Eden => &Eden,
+        GrowthModelType::Ballistic => &Ballistic,
+        GrowthModelType::ReactionLimited => &ReactionLimited,
+        GrowthModelType::Dielectric => &Dielectric,
+        GrowthModelType::ClusterCluster => &ClusterCluster,
+    }
+}
+
+/// Diffusion-limited aggregation: a random walker sticks on first contact
+/// (subject to the stickiness settings)
+struct Diffusion;
+
+impl GrowthModel for Diffusion {
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome {
+        sim.walk_diffusive(rng, 1.0, species)
+    }
+}
+
+/// Reaction-limited aggregation: the same random walk, but each contact only
+/// rarely sticks, so walkers explore the surface and fill in the branches
+struct ReactionLimited;
+
+impl GrowthModel for ReactionLimited {
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome {
+        sim.walk_diffusive(rng, REACTION_STICKING, species)
+    }
+}
+
+/// Eden growth: fill a random empty site next to the cluster.
+///
+/// On the lattice every perimeter site (an empty cell touching the cluster or
+/// a sticky wall) is equally likely to be picked, as in the standard Eden
+/// model; the perimeter is kept up to date as particles stick. Off-lattice
+/// there are no discrete sites, so a stuck disc (or sticky wall point) is
+/// picked uniformly and the new disc tries to touch it at a random angle.
+struct Eden;
+
+impl GrowthModel for Eden {
+    fn prepare(&self, sim: &mut DlaSimulation) {
+        if !sim.settings.off_lattice {
+            update_perimeter(sim);
+        }
+    }
+
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome {
+        if !sim.settings.off_lattice {
+            let (w, sites) = (sim.grid_width, &sim.perimeter.sites);
+            if sites.is_empty() {
+                return WalkOutcome::Respawn;
+            }
+            for _ in 0..sim.settings.max_walk_iterations {
+                let i = sites[rng.gen_range(0..sites.len())];
+                let (x, y) = ((i % w) as f32 + 0.5, (i / w) as f32 + 0.5);
+                if let Some(outcome) = try_stick(sim, rng, x, y, species) {
+                    return outcome;
+                }
+            }
+            return WalkOutcome::Respawn;
+        }
+
+        let walls = wall_segments(sim);
+        let wall_cells: usize = walls.iter().map(|w| w.len).sum();
+        let candidates = sim.discs.len() + wall_cells;
+        if candidates == 0 {
+            return WalkOutcome::Respawn;
+        }
+
+        for _ in 0..sim.settings.max_walk_iterations {
+            let k = rng.gen_range(0..candidates);
+            let (x, y) = if k < sim.discs.len() {
+                let parent = &sim.discs[k];
+                let angle = rng.gen_range(0.0..TAU);
+                sim.wrap_point(parent.x + DISC_CONTACT * angle.cos(), parent.y + DISC_CONTACT * angle.sin())
+            } else {
+                wall_point(sim, &walls, k - sim.discs.len(), rng.gen())
+            };
+            if let Some(outcome) = try_stick(sim, rng, x, y, species) {
+                return outcome;
+            }
+        }
+        WalkOutcome::Respawn
+    }
+}
+
+/// Empty sites next to the cluster (or a sticky wall) for lattice Eden
+/// growth, kept in step with the grid as particles stick
+#[derive(Debug, Clone, Default)]
+pub(super) struct Perimeter {
+    /// Grid index of every perimeter site, in no particular order
+    pub(super) sites: Vec<usize>,
+    /// Position of each grid cell in `sites`, or NOT_SITE
+    slot: Vec<u32>,
+    /// Discs already accounted for
+    synced: usize,
+    /// Neighbourhood the sites were found for
+    neighborhood: NeighborhoodType,
+    /// Boundary the sites were found for
+    boundary: BoundaryBehavior,
+    /// Sticky walls the sites were found for
+    walls: Option<(bool, bool, bool, bool)>,
+}
+
+impl Perimeter {
+    fn insert(&mut self, i: usize) {
+        if self.slot[i] == NOT_SITE {
+            self.slot[i] = self.sites.len() as u32;
+            self.sites.push(i);
+        }
+    }
+
+    fn remove(&mut self, i: usize) {
+        let slot = std::mem::replace(&mut self.slot[i], NOT_SITE);
+        if slot == NOT_SITE {
+            return;
+        }
+        self.sites.swap_remove(slot as usize);
+        if let Some(&moved) = self.sites.get(slot as usize) {
+            self.slot[moved] = slot;
+        }
+    }
+}
+
+/// Whether grid cell `i` is an empty site a particle could stick to
+fn is_perimeter_site(sim: &DlaSimulation, i: usize) -> bool {
+    let (ix, iy) = (i % sim.grid_width, i / sim.grid_width);
+    sim.grid[i].is_none() && sim.in_sticking_range(ix, iy) && sim.count_neighbors(ix, iy).1
+}
+
+/// Take newly stuck particles off the perimeter and add the empty cells
+/// around them, finding the whole perimeter again when the grid, neighbourhood,
+/// boundary or cluster changed underneath
+fn update_perimeter(sim: &mut DlaSimulation) {
+    let mut perimeter = std::mem::take(&mut sim.perimeter);
+    let stale = perimeter.slot.len() != sim.grid.len()
+        || perimeter.neighborhood != sim.settings.neighborhood
+        || perimeter.boundary != sim.settings.boundary_behavior
+        || perimeter.walls != sim.sticky_walls()
+        || perimeter.synced > sim.discs.len();
+    if stale {
+        perimeter = Perimeter {
+            sites: Vec::new(),
+            slot: vec![NOT_SITE; sim.grid.len()],
+            synced: sim.discs.len(),
+            neighborhood: sim.settings.neighborhood,
+            boundary: sim.settings.boundary_behavior,
+            walls: sim.sticky_walls(),
+        };
+        for i in 0..sim.grid.len() {
+            if is_perimeter_site(sim, i) {
+                perimeter.insert(i);
+            }
+        }
+    }
+
+    let w = sim.grid_width;
+    for k in perimeter.synced..sim.discs.len() {
+        let cell = sim.disc_cell(k as u32);
+        perimeter.remove(cell);
+        // Neighbourhoods are symmetric, so the cells this particle touches are
+        // the ones that now touch it
+        for &(dx, dy) in sim.settings.neighborhood.offsets() {
+            if let Some(n) = sim.neighbor_index(cell % w, cell / w, dx, dy) {
+                if is_perimeter_site(sim, n) {
+                    perimeter.insert(n);
+                }
+            }
+        }
+    }
+    perimeter.synced = sim.discs.len();
+    sim.perimeter = perimeter;
+}
+
+/// Ballistic aggregation: particles fly in straight lines from the spawn
+/// source and stick where they first touch the cluster.
+///
+/// Single-edge spawns fly straight across the grid; other spawn modes aim at a
+/// random point within the cluster radius. A particle that fails the stickiness
+/// roll keeps flying, and is dropped if it runs into the cluster or off the grid.
+struct Ballistic;
+
+impl GrowthModel for Ballistic {
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome {
+        let spawn_mode = sim.settings.species_def(species).spawn_mode;
+        let (center_x, center_y) = sim.center();
+        let spawn_radius = sim.spawn_radius();
+        // Straight flights from an edge end at the far side instead
+        let escape_dist_sq = if matches!(spawn_mode, SpawnMode::Circle) {
+            sim.escape_dist_sq(spawn_radius)
+        } else {
+            f32::INFINITY
+        };
+        let Some((mut x, mut y)) = sim.spawn_walker(rng, spawn_mode, center_x, center_y, spawn_radius) else {
+            return WalkOutcome::Respawn;
+        };
+
+        let heading = match spawn_mode {
+            SpawnMode::Top => FRAC_PI_2,
+            SpawnMode::Bottom => -FRAC_PI_2,
+            SpawnMode::Left => 0.0,
+            SpawnMode::Right => PI,
+            _ => {
+                let r = sim.max_radius.max(1.0) * rng.gen::<f32>().sqrt();
+                let a = rng.gen_range(0.0..TAU);
+                let (dx, dy) = sim.displacement(x, y, center_x + r * a.cos(), center_y + r * a.sin());
+                dy.atan2(dx)
+            }
+        };
+        let (ux, uy) = (heading.cos(), heading.sin());
+        let x_max = sim.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+        let y_max = sim.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+
+        for _ in 0..sim.settings.max_walk_iterations {
+            let (dx, dy) = sim.center_offset(x, y);
+            if dx * dx + dy * dy > escape_dist_sq {
+                return WalkOutcome::Respawn;
+            }
+
+            if sim.settings.off_lattice {
+                // Fly to the next contact, the edge of the grid or the end of the segment
+                let exit = if sim.is_periodic() {
+                    f32::INFINITY
+                } else {
+                    exit_distance(x, y, ux, uy, x_max, y_max)
+                };
+                let len = BALLISTIC_DISC_SEGMENT.min(exit);
+                if sim.mask_on_path(x, y, x + ux * len, y + uy * len) != MaskCell::Open {
+                    return WalkOutcome::Respawn;
+                }
+                if let Some(t) = sim.disc_contact(x, y, ux, uy, len) {
+                    let (px, py) = sim.wrap_point(x + ux * t, y + uy * t);
+                    return try_stick(sim, rng, px, py, species).unwrap_or(WalkOutcome::Respawn);
+                }
+                (x, y) = sim.wrap_point(x + ux * len, y + uy * len);
+                if len >= exit {
+                    // At the edge: only a sticky wall can hold the particle
+                    x = x.clamp(BOUNDARY_MARGIN, x_max);
+                    y = y.clamp(BOUNDARY_MARGIN, y_max);
+                    return try_stick(sim, rng, x, y, species).unwrap_or(WalkOutcome::Respawn);
+                }
+            } else {
+                if x < 0.0 || y < 0.0 {
+                    return WalkOutcome::Respawn;
+                }
+                let (ix, iy) = (x as usize, y as usize);
+                if ix >= sim.grid_width
+                    || iy >= sim.grid_height
+                    || !sim.in_walk_range(ix, iy)
+                    || matches!(sim.mask_cell(ix, iy), MaskCell::Wall | MaskCell::Sink)
+                    || sim.grid[iy * sim.grid_width + ix].is_some()
+                {
+                    return WalkOutcome::Respawn;
+                }
+                if let Some(outcome) = try_stick(sim, rng, x, y, species) {
+                    return outcome;
+                }
+                (x, y) = sim.wrap_point(x + ux * BALLISTIC_STEP, y + uy * BALLISTIC_STEP);
+            }
+        }
+        WalkOutcome::Respawn
+    }
+}
+
+/// Dielectric breakdown: the cluster is a grounded electrode (potential 0)
+/// inside an outer electrode at potential 1, and the potential in between
+/// solves the Laplace equation.
+///
+/// Each empty site touching the cluster grows with probability proportional to
+/// the field strength there raised to the power eta. With the cluster at 0 the
+/// field at a perimeter site is its potential, so that is what gets weighed.
+/// Sticky walls are grounded too unless every wall is sticky. Growth stops once
+/// the cluster bridges the gap to the outer electrode. Growth is always on the
+/// lattice, and a periodic grid is treated as bounded.
+struct Dielectric;
+
+impl GrowthModel for Dielectric {
+    fn prepare(&self, sim: &mut DlaSimulation) {
+        update_potential(sim);
+    }
+
+    fn grow(&self, sim: &DlaSimulation, rng: &mut ChaCha8Rng, species: u8) -> WalkOutcome {
+        let (w, h) = (sim.grid_width, sim.grid_height);
+        let potential = &sim.potential.values;
+        if w < 3 || h < 3 || potential.len() != sim.grid.len() {
+            return WalkOutcome::Respawn;
+        }
+
+        // Only cells within reach of the cluster (or of a sticky wall) can be on the perimeter
+        let reach = sim.settings.neighborhood.reach();
+        let walls = sim.sticky_walls().is_some();
+        let (x0, y0, x1, y1) = if walls || sim.is_periodic() {
+            (1, 1, w - 2, h - 2)
+        } else {
+            let (cx, cy) = sim.center();
+            let r = sim.bound_radius + reach + 1.0;
+            let clamp = |v: f32, max: usize| (v.max(1.0) as usize).min(max);
+            (clamp(cx - r, w - 2), clamp(cy - r, h - 2), clamp(cx + r, w - 2), clamp(cy + r, h - 2))
+        };
+
+        let eta = sim.settings.dbm_eta;
+        let min_contacts = (sim.settings.multi_contact_min as usize).max(1);
+        let mut sites = Vec::new();
+        let mut total = 0.0f64;
+        for iy in y0..=y1 {
+            for ix in x0..=x1 {
+                let i = iy * w + ix;
+                let near_wall = walls && (ix <= 2 || iy <= 2 || ix >= w - 3 || iy >= h - 3);
+                if sim.grid[i].is_some()
+                    || (sim.distance_field[i] as f32 > reach && !near_wall)
+                    || sim.mask_cell(ix, iy) != MaskCell::Open
+                {
+                    continue;
+                }
+                let (neighbor_count, _) = sim.count_neighbors(ix, iy);
+                if neighbor_count < min_contacts {
+                    continue;
+                }
+                let (x, y) = (ix as f32 + 0.5, iy as f32 + 0.5);
+                let (dx, dy) = sim.center_offset(x, y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let weight = potential[i].max(0.0).powf(eta)
+                    * sim.sticking_chance(x, y, neighbor_count, distance, species);
+                if weight > 0.0 {
+                    total += weight as f64;
+                    sites.push((total, ix, iy, neighbor_count));
+                }
+            }
+        }
+        if sites.is_empty() {
+            return WalkOutcome::Respawn;
+        }
+
+        let target = rng.gen::<f64>() * total;
+        let k = sites.partition_point(|&(cumulative, ..)| cumulative <= target).min(sites.len() - 1);
+        let (_, ix, iy, neighbor_count) = sites[k];
+        let (dx, dy) = sim.center_offset(ix as f32 + 0.5, iy as f32 + 0.5);
+        WalkOutcome::Stick {
+            ix,
+            iy,
+            distance: (dx * dx + dy * dy).sqrt(),
+            direction: dy.atan2(dx),
+            neighbor_count,
+            species,
+        }
+    }
+}
+
+/// Electric potential for the dielectric breakdown model, kept in step with
+/// the grid as particles stick
+#[derive(Debug, Clone, Default)]
+pub(super) struct PotentialField {
+    /// Potential per grid cell (0 on the cluster, 1 on the outer electrode)
+    pub(super) values: Vec<f32>,
+    /// Discs already grounded in `values`
+    synced: usize,
+    /// Disc count at the last full solve
+    solved_at: usize,
+    /// The cluster reached the outer electrode, so there is nothing left to break down
+    pub(super) bridged: bool,
+    /// Grid size the boundary was set up for
+    size: (usize, usize),
+    /// Sticky walls the boundary was set up for
+    walls: Option<(bool, bool, bool, bool)>,
+}
+
+/// Ground newly stuck particles in the potential and relax around them,
+/// re-solving from scratch when the grid, walls or cluster changed underneath
+fn update_potential(sim: &mut DlaSimulation) {
+    let (w, h) = (sim.grid_width, sim.grid_height);
+    if w < 3 || h < 3 {
+        return;
+    }
+    let stale = sim.potential.size != (w, h)
+        || sim.potential.walls != sim.sticky_walls()
+        || sim.potential.values.len() != sim.grid.len()
+        || sim.potential.synced > sim.discs.len();
+
+    // Sticky walls are grounded; the other sides form the electrode. With every
+    // wall sticky the whole ring is the electrode, and growth starts from it.
+    let (left, right, top, bottom) = sim.sticky_walls().unwrap_or_default();
+    let all_sticky = left && right && top && bottom;
+    let grounded = |ix: usize, iy: usize| {
+        !all_sticky
+            && ((left && ix == 0) || (right && ix == w - 1) || (top && iy == 0) || (bottom && iy == h - 1))
+    };
+    let touches_electrode = |ix: usize, iy: usize| {
+        (!left && ix <= 1) || (!right && ix >= w - 2) || (!top && iy <= 1) || (!bottom && iy >= h - 2)
+    };
+
+    if stale {
+        let mut values = vec![1.0; w * h];
+        for iy in 0..h {
+            for ix in 0..w {
+                if grounded(ix, iy) || sim.grid[iy * w + ix].is_some() {
+                    values[iy * w + ix] = 0.0;
+                }
+            }
+        }
+        sim.potential = PotentialField {
+            values,
+            synced: 0,
+            solved_at: 0,
+            bridged: false,
+            size: (w, h),
+            walls: sim.sticky_walls(),
+        };
+    }
+
+    let local_omega = sor_omega(2 * DBM_LOCAL_RADIUS + 1);
+    for k in sim.potential.synced..sim.discs.len() {
+        let ix = (sim.discs[k].x.max(0.0) as usize).min(w - 1);
+        let iy = (sim.discs[k].y.max(0.0) as usize).min(h - 1);
+        sim.potential.bridged |= touches_electrode(ix, iy);
+        if stale {
+            continue;
+        }
+        sim.potential.values[iy * w + ix] = 0.0;
+        let window = (
+            ix.saturating_sub(DBM_LOCAL_RADIUS),
+            iy.saturating_sub(DBM_LOCAL_RADIUS),
+            ix + DBM_LOCAL_RADIUS,
+            iy + DBM_LOCAL_RADIUS,
+        );
+        for _ in 0..DBM_LOCAL_SWEEPS {
+            relax(sim, window, local_omega);
+        }
+    }
+    sim.potential.synced = sim.discs.len();
+
+    // Between full solves only the windows around new particles are relaxed
+    if stale || sim.discs.len() >= sim.potential.solved_at + DBM_REFRESH_INTERVAL {
+        let omega = sor_omega(w.max(h));
+        for _ in 0..DBM_MAX_SWEEPS_PER_CELL * w.max(h) {
+            if relax(sim, (0, 0, w, h), omega) < DBM_TOLERANCE {
+                break;
+            }
+        }
+        sim.potential.solved_at = sim.discs.len();
+    }
+}
+
+/// Over-relaxation factor that converges fastest on a grid `n` cells across
+fn sor_omega(n: usize) -> f32 {
+    2.0 / (1.0 + (PI / n.max(2) as f32).sin())
+}
+
+/// One Gauss-Seidel over-relaxation sweep of the free cells in the window
+/// (x0, y0)-(x1, y1), clipped to the grid interior. The outer ring and the
+/// cluster hold their values. Mask walls are insulators: they aren't relaxed,
+/// and cells next to them average over their other neighbours only (no flux
+/// through the wall). Returns the largest change.
+fn relax(sim: &mut DlaSimulation, (x0, y0, x1, y1): (usize, usize, usize, usize), omega: f32) -> f32 {
+    let w = sim.grid_width;
+    let (x0, y0) = (x0.max(1), y0.max(1));
+    let (x1, y1) = (x1.min(w - 2), y1.min(sim.grid_height - 2));
+    let grid = &sim.grid;
+    let mask = &sim.mask;
+    let is_wall = |i: usize| mask.get(i) == Some(&MaskCell::Wall);
+    let values = &mut sim.potential.values;
+    let mut max_change = 0.0f32;
+    for iy in y0..=y1 {
+        for ix in x0..=x1 {
+            let i = iy * w + ix;
+            if grid[i].is_some() || is_wall(i) {
+                continue;
+            }
+            let average = if mask.is_empty() {
+                0.25 * (values[i - 1] + values[i + 1] + values[i - w] + values[i + w])
+            } else {
+                let (sum, count) = [i - 1, i + 1, i - w, i + w]
+                    .into_iter()
+                    .filter(|&n| !is_wall(n))
+                    .fold((0.0, 0), |(sum, count), n| (sum + values[n], count + 1));
+                if count == 0 {
+                    continue;
+                }
+                sum / count as f32
+            };
+            // Over-relaxation can overshoot; the true solution stays between the electrodes
+            let relaxed = (values[i] + omega * (average - values[i])).clamp(0.0, 1.0);
+            max_change = max_change.max((relaxed - values[i]).abs());
+            values[i] = relaxed;
+        }
+    }
+    max_change
+}
+
+/// Cluster-cluster aggregation moves whole clusters (see `clusters.rs`)
+/// instead of growing walkers onto a fixed one, so there is nothing to grow
+struct ClusterCluster;
+
+impl GrowthModel for ClusterCluster {
+    fn grow(&self, _sim: &DlaSimulation, _rng: &mut ChaCha8Rng, _species: u8) -> WalkOutcome {
+        WalkOutcome::Respawn
+    }
+}
+
+/// Stick a particle of `species` at (x, y) if the site is free, touches enough
+/// of the cluster (or a sticky wall) and passes the stickiness roll
+fn try_stick(sim: &DlaSimulation, rng: &mut ChaCha8Rng, x: f32, y: f32, species: u8) -> Option<WalkOutcome> {
+    let (dx, dy) = sim.center_offset(x, y);
+    let distance = (dx * dx + dy * dy).sqrt();
+    let direction = dy.atan2(dx);
+    let min_contacts = (sim.settings.multi_contact_min as usize).max(1);
+
+    if sim.settings.off_lattice {
+        let x_max = sim.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+        let y_max = sim.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+        if !sim.is_periodic() && (x < BOUNDARY_MARGIN || x > x_max || y < BOUNDARY_MARGIN || y > y_max) {
+            return None;
+        }
+        let neighbor_count = sim.count_disc_neighbors(x, y) + sim.disc_wall_contacts(x, y);
+        if neighbor_count < min_contacts || sim.disc_overlaps(x, y) || sim.mask_at(x, y) != MaskCell::Open {
+            return None;
+        }
+        let effective_stickiness = sim.sticking_chance(x, y, neighbor_count, distance, species);
+        return (rng.gen::<f32>() < effective_stickiness).then_some(WalkOutcome::StickDisc {
+            x,
+            y,
+            distance,
+            direction,
+            neighbor_count,
+            species,
+        });
+    }
+
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let (ix, iy) = (x as usize, y as usize);
+    if ix >= sim.grid_width
+        || iy >= sim.grid_height
+        || !sim.in_sticking_range(ix, iy)
+        || sim.grid[iy * sim.grid_width + ix].is_some()
+    {
+        return None;
+    }
+    let (neighbor_count, _) = sim.count_neighbors(ix, iy);
+    if neighbor_count < min_contacts {
+        return None;
+    }
+    let effective_stickiness = sim.sticking_chance(x, y, neighbor_count, distance, species);
+    (rng.gen::<f32>() < effective_stickiness).then_some(WalkOutcome::Stick {
+        ix,
+        iy,
+        distance,
+        direction,
+        neighbor_count,
+        species,
+    })
+}
+
+/// A run of sites along one sticky wall: `len` cells from (x0, y0) in direction (dx, dy)
+struct WallSegment {
+    x0: f32,
+    y0: f32,
+    dx: f32,
+    dy: f32,
+    len: usize,
+}
+
+/// Sites where a particle touches a sticky wall. Walkers never enter the
+/// outermost ring of cells, so these are the cells (or disc centres) just inside it.
+fn wall_segments(sim: &DlaSimulation) -> Vec<WallSegment> {
+    let Some((left, right, top, bottom)) = sim.sticky_walls() else {
+        return Vec::new();
+    };
+    let near = BOUNDARY_MARGIN;
+    let far_x = sim.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+    let far_y = sim.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+    let cols = sim.grid_width.saturating_sub(2);
+    let rows = sim.grid_height.saturating_sub(2);
+    [
+        (left, near, near, 0.0, 1.0, rows),
+        (right, far_x, near, 0.0, 1.0, rows),
+        (top, near, near, 1.0, 0.0, cols),
+        (bottom, near, far_y, 1.0, 0.0, cols),
+    ]
+    .into_iter()
+    .filter(|&(sticky, ..)| sticky)
+    .map(|(_, x0, y0, dx, dy, len)| WallSegment { x0, y0, dx, dy, len })
+    .collect()
+}
+
+/// The `k`-th wall site across all segments, offset by `jitter` (0-1) along the wall
+fn wall_point(sim: &DlaSimulation, walls: &[WallSegment], mut k: usize, jitter: f32) -> (f32, f32) {
+    let x_max = sim.grid_width as f32 - BOUNDARY_MARGIN - 1.0;
+    let y_max = sim.grid_height as f32 - BOUNDARY_MARGIN - 1.0;
+    for wall in walls {
+        if k < wall.len {
+            let t = k as f32 + jitter;
+            return (
+                (wall.x0 + wall.dx * t).min(x_max),
+                (wall.y0 + wall.dy * t).min(y_max),
+            );
+        }
+        k -= wall.len;
+    }
+    (BOUNDARY_MARGIN, BOUNDARY_MARGIN)
+}
+
+/// Distance along (ux, uy) from (x, y) to the edge of the walkable area
+fn exit_distance(x: f32, y: f32, ux: f32, uy: f32, x_max: f32, y_max: f32) -> f32 {
+    let axis = |p: f32, u: f32, max: f32| {
+        if u > 1e-6 {
+            (max - p) / u
+        } else if u < -1e-6 {
+            (BOUNDARY_MARGIN - p) / u
+        } else {
+            f32::INFINITY
+        }
+    };
+    axis(x, ux, x_max).min(axis(y, uy, y_max)).max(0.0)
+}
@@ -0,0 +1,249 @@
+//! Seeds imported from outside the simulation: a thresholded image, a
+//! string rasterized with a built-in 5x7 bitmap font, or a drawing saved
+//! from the canvas.
+//!
+//! Images and text are turned into a `Bitmap` at its own resolution, scaled
+//! to fit the middle of the grid and stamped on as seed particles. Drawings
+//! are stretched over the whole grid.
+
+use super::DlaSimulation;
+use crate::custom_seed::{CustomSeed, SketchCell};
+use crate::mask::MaskCell;
+
+/// Share of each grid dimension an image seed may cover
+const IMAGE_SEED_FRACTION: f32 = 0.6;
+/// Share of the grid width a text seed may cover
+const TEXT_SEED_WIDTH_FRACTION: f32 = 0.8;
+/// Share of the grid height a text seed may cover
+const TEXT_SEED_HEIGHT_FRACTION: f32 = 0.4;
+/// Luma below which an opaque image pixel becomes a seed
+#[cfg(feature = "render")]
+const IMAGE_SEED_THRESHOLD: u8 = 128;
+
+/// Glyph size of the built-in font, in font pixels
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Blank font pixels between characters and between lines
+const GLYPH_SPACING: usize = 1;
+
+/// A black-and-white picture: `true` pixels become seed particles
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Threshold an image file: opaque pixels darker than mid-grey are set
+    #[cfg(feature = "render")]
+    pub(super) fn load_image(path: &std::path::Path) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("Failed to read seed image: {}", e))?
+            .to_luma_alpha8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels = image
+            .pixels()
+            .map(|p| p.0[1] >= 128 && p.0[0] < IMAGE_SEED_THRESHOLD)
+            .collect();
+        Ok(Self { width, height, pixels })
+    }
+
+    #[cfg(not(feature = "render"))]
+    pub(super) fn load_image(_path: &std::path::Path) -> Result<Self, String> {
+        Err("Image seeds need the `render` feature".to_string())
+    }
+
+    /// Rasterize text with the built-in font, one font pixel per bitmap pixel.
+    /// Lines are split on '\n' and centred; letters are drawn in upper case.
+    pub(super) fn render_text(text: &str) -> Self {
+        let lines: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        let columns = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let advance_x = GLYPH_WIDTH + GLYPH_SPACING;
+        let advance_y = GLYPH_HEIGHT + GLYPH_SPACING;
+        let width = (columns * advance_x).saturating_sub(GLYPH_SPACING).max(1);
+        let height = (lines.len() * advance_y).saturating_sub(GLYPH_SPACING).max(1);
+        let mut pixels = vec![false; width * height];
+
+        for (row, line) in lines.iter().enumerate() {
+            let indent = (columns - line.len()) * advance_x / 2;
+            for (col, &c) in line.iter().enumerate() {
+                let rows = glyph(c);
+                for (gy, bits) in rows.iter().enumerate() {
+                    for gx in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - gx)) != 0 {
+                            let x = indent + col * advance_x + gx;
+                            let y = row * advance_y + gy;
+                            pixels[y * width + x] = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { width, height, pixels }
+    }
+}
+
+impl DlaSimulation {
+    /// Stamp a bitmap onto the middle of the grid as seed particles, scaled to
+    /// fit within the given share of each dimension (nearest pixel). Integer
+    /// scaling keeps font strokes even. Returns false if nothing was placed.
+    pub(super) fn seed_bitmap(&mut self, bitmap: &Bitmap, fraction_x: f32, fraction_y: f32, integer_scale: bool) -> bool {
+        let (w, h) = (self.grid_width, self.grid_height);
+        if w < 3 || h < 3 {
+            return false;
+        }
+        let mut scale = (w as f32 * fraction_x / bitmap.width as f32).min(h as f32 * fraction_y / bitmap.height as f32);
+        if integer_scale && scale >= 1.0 {
+            scale = scale.floor();
+        }
+        let out_w = ((bitmap.width as f32 * scale) as usize).clamp(1, w - 2);
+        let out_h = ((bitmap.height as f32 * scale) as usize).clamp(1, h - 2);
+        let x0 = (w - out_w) / 2;
+        let y0 = (h - out_h) / 2;
+
+        let (cx, cy) = self.center();
+        let seed_data = self.seed_particle();
+        let mut count = 0;
+        let mut max_dist: f32 = 1.0;
+        for oy in 0..out_h {
+            let by = (((oy as f32 + 0.5) / scale) as usize).min(bitmap.height - 1);
+            for ox in 0..out_w {
+                let bx = (((ox as f32 + 0.5) / scale) as usize).min(bitmap.width - 1);
+                if !bitmap.get(bx, by) {
+                    continue;
+                }
+                let (x, y) = (x0 + ox, y0 + oy);
+                let idx = y * w + x;
+                if self.grid[idx].is_none() {
+                    self.grid[idx] = Some(seed_data);
+                    count += 1;
+                    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                    max_dist = max_dist.max((dx * dx + dy * dy).sqrt());
+                }
+            }
+        }
+
+        if count == 0 {
+            return false;
+        }
+        self.particles_stuck = count;
+        self.seed_particles = count;
+        self.max_radius = max_dist;
+        true
+    }
+
+    /// Seed from a thresholded image file, or a point if it can't be read
+    pub(super) fn seed_image(&mut self, path: &std::path::Path) {
+        let placed = Bitmap::load_image(path)
+            .map(|bitmap| self.seed_bitmap(&bitmap, IMAGE_SEED_FRACTION, IMAGE_SEED_FRACTION, false))
+            .unwrap_or(false);
+        if !placed {
+            self.seed_point();
+        }
+    }
+
+    /// Seed from a saved drawing: its seed particles, plus its walls laid over
+    /// the domain mask. A drawing without seeds starts empty.
+    pub(super) fn seed_custom(&mut self, seed: &CustomSeed) {
+        let (w, h) = (self.grid_width, self.grid_height);
+        let (cx, cy) = self.center();
+        let seed_data = self.seed_particle();
+        let mut count = 0;
+        let mut max_dist: f32 = 0.0;
+        for (idx, cell) in seed.resample(w, h).into_iter().enumerate() {
+            match cell {
+                SketchCell::Seed => {
+                    self.grid[idx] = Some(seed_data);
+                    count += 1;
+                    let (dx, dy) = ((idx % w) as f32 + 0.5 - cx, (idx / w) as f32 + 0.5 - cy);
+                    max_dist = max_dist.max((dx * dx + dy * dy).sqrt());
+                }
+                SketchCell::Wall => {
+                    if self.mask.is_empty() {
+                        self.mask = vec![MaskCell::Open; w * h];
+                    }
+                    self.mask[idx] = MaskCell::Wall;
+                }
+                SketchCell::Empty => {}
+            }
+        }
+        self.particles_stuck = count;
+        self.seed_particles = count;
+        self.max_radius = if count > 0 { max_dist.max(1.0) } else { 0.0 };
+    }
+
+    /// Seed from text in the built-in font, or a point if the text is blank
+    pub(super) fn seed_text(&mut self, text: &str) {
+        let bitmap = Bitmap::render_text(text);
+        if !self.seed_bitmap(&bitmap, TEXT_SEED_WIDTH_FRACTION, TEXT_SEED_HEIGHT_FRACTION, true) {
+            self.seed_point();
+        }
+    }
+}
+
+/// Rows of a 5x7 glyph, top first, most significant of the low five bits on
+/// the left. Letters are upper case; unknown characters draw as '?'.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '\'' => [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '&' => [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '*' => [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00],
+        '<' => [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+        '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
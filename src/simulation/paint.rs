@@ -0,0 +1,166 @@
+//! Interactive editing of a running simulation: painting seed particles,
+//! erasing particles and drawing walls with a round brush.
+//!
+//! Walls go into the domain mask, and `settings.mask` is replaced by the
+//! edited mask at grid resolution so they survive a reset.
+
+use super::{growth, DlaSimulation, Disc};
+use crate::mask::{DomainMask, MaskCell};
+
+/// What a brush stroke does to the cells under it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaintTool {
+    /// Place seed particles on empty cells
+    #[default]
+    Seed,
+    /// Remove particles and walls
+    Erase,
+    /// Draw walls, removing any particles under them
+    Wall,
+}
+
+impl PaintTool {
+    pub fn name(&self) -> &str {
+        match self {
+            PaintTool::Seed => "Seed",
+            PaintTool::Erase => "Erase",
+            PaintTool::Wall => "Wall",
+        }
+    }
+
+    pub fn next(&self) -> PaintTool {
+        match self {
+            PaintTool::Seed => PaintTool::Erase,
+            PaintTool::Erase => PaintTool::Wall,
+            PaintTool::Wall => PaintTool::Seed,
+        }
+    }
+}
+
+impl DlaSimulation {
+    /// Apply a tool to every cell whose centre lies within `radius` of (x, y)
+    pub fn paint(&mut self, x: f32, y: f32, radius: f32, tool: PaintTool) {
+        let cells = self.brush_cells(x, y, radius);
+        self.apply_tool(&cells, tool);
+    }
+
+    /// Apply a tool along the line from (x0, y0) to (x1, y1), stamping the brush
+    /// often enough that fast strokes don't leave gaps. All stamps are applied
+    /// at once.
+    pub fn paint_line(&mut self, (x0, y0): (f32, f32), (x1, y1): (f32, f32), radius: f32, tool: PaintTool) {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let stamps = ((dx * dx + dy * dy).sqrt() / radius.max(0.5)).ceil().max(1.0) as usize;
+        let mut cells: Vec<usize> = (1..=stamps)
+            .flat_map(|i| {
+                let t = i as f32 / stamps as f32;
+                self.brush_cells(x0 + t * dx, y0 + t * dy, radius)
+            })
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        self.apply_tool(&cells, tool);
+    }
+
+    /// Finish a paint stroke: erasing leaves the distance field and max_radius
+    /// behind until now, so a drag doesn't pay for a full rebuild on every move
+    pub fn end_paint_stroke(&mut self) {
+        if std::mem::take(&mut self.erase_pending) {
+            self.rebuild_distance_field();
+            self.max_radius = self.measure_max_radius().max(1.0);
+        }
+    }
+
+    /// Apply a tool to `cells`, given in increasing order
+    fn apply_tool(&mut self, cells: &[usize], tool: PaintTool) {
+        if cells.is_empty() {
+            return;
+        }
+        match tool {
+            PaintTool::Seed => self.paint_seeds(cells),
+            PaintTool::Erase => {
+                self.paint_mask(cells, MaskCell::Open);
+                self.erase_cells(cells);
+            }
+            PaintTool::Wall => {
+                self.paint_mask(cells, MaskCell::Wall);
+                self.erase_cells(cells);
+            }
+        }
+        self.potential = growth::PotentialField::default();
+        self.perimeter = growth::Perimeter::default();
+        if self.clusters_move() {
+            self.rebuild_clusters();
+        }
+    }
+
+    /// Grid indices of the cells under a round brush, in increasing order
+    fn brush_cells(&self, x: f32, y: f32, radius: f32) -> Vec<usize> {
+        let (w, h) = (self.grid_width as i32, self.grid_height as i32);
+        let r = radius.max(0.5);
+        let (x0, x1) = (((x - r).floor() as i32).max(0), ((x + r).ceil() as i32).min(w - 1));
+        let (y0, y1) = (((y - r).floor() as i32).max(0), ((y + r).ceil() as i32).min(h - 1));
+        let mut cells = Vec::new();
+        for iy in y0..=y1 {
+            for ix in x0..=x1 {
+                let (dx, dy) = (ix as f32 + 0.5 - x, iy as f32 + 0.5 - y);
+                if dx * dx + dy * dy <= r * r {
+                    cells.push(iy as usize * self.grid_width + ix as usize);
+                }
+            }
+        }
+        cells
+    }
+
+    /// Put seed particles on the empty, unwalled cells among `cells`
+    fn paint_seeds(&mut self, cells: &[usize]) {
+        let seed_data = self.seed_particle();
+        for &idx in cells {
+            let (ix, iy) = (idx % self.grid_width, idx / self.grid_width);
+            if self.grid[idx].is_some() || self.mask_cell(ix, iy) == MaskCell::Wall {
+                continue;
+            }
+            self.grid[idx] = Some(seed_data);
+            self.push_disc(Disc {
+                x: ix as f32 + 0.5,
+                y: iy as f32 + 0.5,
+                data: seed_data,
+            });
+            self.particles_stuck += 1;
+            self.seed_particles += 1;
+            let (dx, dy) = self.center_offset(ix as f32 + 0.5, iy as f32 + 0.5);
+            self.max_radius = self.max_radius.max((dx * dx + dy * dy).sqrt());
+            self.stamp_distance(ix, iy);
+        }
+    }
+
+    /// Remove every particle whose disc centre lies in one of `cells`. The
+    /// distance field and max_radius catch up in `end_paint_stroke`.
+    fn erase_cells(&mut self, cells: &[usize]) {
+        let w = self.grid_width;
+        if self.remove_discs(|d| cells.binary_search(&(d.y as usize * w + d.x as usize)).is_err()) > 0 {
+            self.erase_pending = true;
+        }
+    }
+
+    /// Set the mask type of `cells` and store the edited mask in the settings.
+    /// A mask left with no masked cells is dropped.
+    fn paint_mask(&mut self, cells: &[usize], kind: MaskCell) {
+        if self.mask.is_empty() {
+            if kind == MaskCell::Open {
+                return;
+            }
+            self.mask = vec![MaskCell::Open; self.grid_width * self.grid_height];
+        }
+        for &idx in cells {
+            self.mask[idx] = kind;
+        }
+        if self.mask.iter().all(|&c| c == MaskCell::Open) {
+            self.mask.clear();
+            self.settings.mask = None;
+            return;
+        }
+        let mask = &self.mask;
+        let w = self.grid_width;
+        self.settings.mask = Some(DomainMask::from_fn(w, self.grid_height, |x, y| mask[y * w + x]));
+    }
+}
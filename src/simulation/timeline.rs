@@ -0,0 +1,29 @@
+//! Growth history: every particle keeps the age it stuck at, so the run can
+//! be viewed as it was at any earlier age and regrown from there.
+
+use super::{growth, DlaSimulation};
+
+impl DlaSimulation {
+    /// Age of the most recently stuck particle (0 with only seeds)
+    pub fn latest_age(&self) -> usize {
+        self.discs.iter().map(|d| d.data.age).max().unwrap_or(0)
+    }
+
+    /// Roll the run back to just after the particle of the given age stuck,
+    /// removing every later particle. The RNG carries on from where it is, so
+    /// the regrown cluster differs from the one removed even with unchanged
+    /// settings. Returns the number of particles removed.
+    pub fn truncate_to_age(&mut self, age: usize) -> usize {
+        let removed = self.retain_discs(|d| d.data.age <= age);
+        if removed == 0 {
+            return 0;
+        }
+        self.max_radius = self.measure_max_radius().max(1.0);
+        self.potential = growth::PotentialField::default();
+        self.perimeter = growth::Perimeter::default();
+        if self.clusters_move() {
+            self.rebuild_clusters();
+        }
+        removed
+    }
+}
@@ -0,0 +1,66 @@
+//! Uniform-bucket spatial hash for off-lattice disc lookups
+
+/// Side length of a bucket in grid cells. Twice the disc contact distance, so a
+/// contact query around a single point touches at most 2x2 buckets.
+const BUCKET_SIZE: f32 = 2.0;
+
+/// Buckets of disc indices covering the simulation grid
+#[derive(Debug, Clone, Default)]
+pub struct SpatialHash {
+    cols: usize,
+    rows: usize,
+    buckets: Vec<Vec<u32>>,
+}
+
+impl SpatialHash {
+    pub fn new(width: usize, height: usize) -> Self {
+        let cols = (width as f32 / BUCKET_SIZE).ceil().max(1.0) as usize;
+        let rows = (height as f32 / BUCKET_SIZE).ceil().max(1.0) as usize;
+        Self {
+            cols,
+            rows,
+            buckets: vec![Vec::new(); cols * rows],
+        }
+    }
+
+    /// Bucket column/row for a point, clamped to the grid
+    fn bucket_of(&self, x: f32, y: f32) -> (usize, usize) {
+        let bx = ((x / BUCKET_SIZE).max(0.0) as usize).min(self.cols - 1);
+        let by = ((y / BUCKET_SIZE).max(0.0) as usize).min(self.rows - 1);
+        (bx, by)
+    }
+
+    pub fn insert(&mut self, index: usize, x: f32, y: f32) {
+        let (bx, by) = self.bucket_of(x, y);
+        self.buckets[by * self.cols + bx].push(index as u32);
+    }
+
+    /// Remove every entry, keeping the buckets' capacity
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+    }
+
+    /// Remove an entry inserted at (x, y)
+    pub fn remove(&mut self, index: usize, x: f32, y: f32) {
+        let (bx, by) = self.bucket_of(x, y);
+        let bucket = &mut self.buckets[by * self.cols + bx];
+        if let Some(pos) = bucket.iter().position(|&i| i as usize == index) {
+            bucket.swap_remove(pos);
+        }
+    }
+
+    /// Call `f` with the index of every entry in buckets overlapping the rectangle
+    pub fn for_each_in(&self, x0: f32, y0: f32, x1: f32, y1: f32, mut f: impl FnMut(usize)) {
+        let (bx0, by0) = self.bucket_of(x0.min(x1), y0.min(y1));
+        let (bx1, by1) = self.bucket_of(x0.max(x1), y0.max(y1));
+        for by in by0..=by1 {
+            for bx in bx0..=bx1 {
+                for &i in &self.buckets[by * self.cols + bx] {
+                    f(i as usize);
+                }
+            }
+        }
+    }
+}
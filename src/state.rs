@@ -0,0 +1,242 @@
+//! Full simulation state snapshots for save/resume.
+//!
+//! Unlike `AppConfig`, which only stores parameters, a state file captures the
+//! grown cluster and the RNG position so a run continues exactly where it stopped.
+
+use crate::settings::SimulationSettings;
+use crate::simulation::{Disc, ParticleData, SeedPattern};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Current state file format version. Version 2 added discs, seed counts,
+/// cluster bookkeeping and the per-particle deposit, cluster, species and
+/// parent fields.
+pub const STATE_VERSION: u32 = 2;
+/// Oldest version still loaded; fields added since then take their defaults
+pub const OLDEST_STATE_VERSION: u32 = 1;
+
+/// A stuck particle and its grid position
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StoredParticle {
+    pub x: usize,
+    pub y: usize,
+    #[serde(flatten)]
+    pub data: ParticleData,
+}
+
+/// Cluster bookkeeping of a cluster-cluster aggregation run. Particles are
+/// indices into the disc list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredClusters {
+    /// Union-find parent of each particle
+    pub parent: Vec<u32>,
+    /// Roots of the clusters that haven't merged yet, in pick order
+    pub roots: Vec<u32>,
+    /// Particles of each root's cluster, in move order
+    pub members: Vec<Vec<u32>>,
+    /// Size of the smallest cluster
+    pub min_size: usize,
+    /// Clusters at the start of the run
+    pub initial: usize,
+}
+
+/// Serialized form of a `DlaSimulation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationState {
+    /// Format version, checked on load
+    pub version: u32,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub num_particles: usize,
+    pub stickiness: f32,
+    pub particles_stuck: usize,
+    /// Particles placed by an imported seed, on top of num_particles
+    #[serde(default)]
+    pub seed_particles: usize,
+    pub max_radius: f32,
+    pub paused: bool,
+    pub seed_pattern: SeedPattern,
+    pub settings: SimulationSettings,
+    /// Fixed RNG seed, if the run was pinned to one
+    pub rng_seed: Option<u64>,
+    /// Seed the current run started from
+    pub active_seed: u64,
+    /// RNG position at the time of the snapshot
+    pub rng: ChaCha8Rng,
+    /// Every occupied grid cell, in row-major order
+    pub particles: Vec<StoredParticle>,
+    /// Every stuck particle as a disc, in stick order. Rebuilt from `particles`
+    /// when missing.
+    #[serde(default)]
+    pub discs: Vec<Disc>,
+    /// Clusters of a cluster-cluster run, once its first step has grouped them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clusters: Option<StoredClusters>,
+}
+
+impl SimulationState {
+    /// Write the state to a JSON file
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write state file: {}", e))
+    }
+
+    /// Read a state from a JSON file, rejecting unknown versions and inconsistent grids
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read state file: {}", e))?;
+        let state: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse state file: {}", e))?;
+        state.validate()?;
+        Ok(state)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(OLDEST_STATE_VERSION..=STATE_VERSION).contains(&self.version) {
+            return Err(format!(
+                "Unsupported state version {} (expected {} to {})",
+                self.version, OLDEST_STATE_VERSION, STATE_VERSION
+            ));
+        }
+        if self.grid_width == 0 || self.grid_height == 0 {
+            return Err("State has an empty grid".to_string());
+        }
+        if let Some(p) = self
+            .particles
+            .iter()
+            .find(|p| p.x >= self.grid_width || p.y >= self.grid_height)
+        {
+            return Err(format!(
+                "Particle at ({}, {}) lies outside the {}x{} grid",
+                p.x, p.y, self.grid_width, self.grid_height
+            ));
+        }
+        if let Some(d) = self.discs.iter().find(|d| {
+            !(0.0..=self.grid_width as f32).contains(&d.x)
+                || !(0.0..=self.grid_height as f32).contains(&d.y)
+        }) {
+            return Err(format!(
+                "Disc at ({:.2}, {:.2}) lies outside the {}x{} grid",
+                d.x, d.y, self.grid_width, self.grid_height
+            ));
+        }
+        if let Some(c) = &self.clusters {
+            let n = c.parent.len();
+            if n != self.discs.len() {
+                return Err(format!("Cluster state covers {} particles but the state has {} discs", n, self.discs.len()));
+            }
+            let in_range = |&i: &u32| (i as usize) < n;
+            if c.roots.len() != c.members.len()
+                || !c.parent.iter().all(in_range)
+                || !c.roots.iter().all(in_range)
+                || !c.members.iter().flatten().all(in_range)
+            {
+                return Err("Cluster state refers to particles that don't exist".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::DlaSimulation;
+
+    #[test]
+    fn test_resumed_run_matches_uninterrupted_run() {
+        let mut sim = DlaSimulation::new(96, 96);
+        sim.num_particles = 400;
+        sim.reset_with_seed(SeedPattern::Point);
+        for _ in 0..150 {
+            sim.step();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        sim.to_state().save_to_file(&path).unwrap();
+        let mut resumed = DlaSimulation::from_state(SimulationState::load_from_file(&path).unwrap());
+        assert_eq!(resumed.particles_stuck, sim.particles_stuck);
+
+        while sim.step() {}
+        while resumed.step() {}
+        assert_eq!(resumed.particles_stuck, sim.particles_stuck);
+        assert_eq!(resumed.max_radius, sim.max_radius);
+        assert_eq!(resumed.to_state().particles, sim.to_state().particles);
+    }
+
+    #[test]
+    fn test_resumed_cluster_run_matches_uninterrupted_run() {
+        let mut sim = DlaSimulation::new(64, 64);
+        sim.num_particles = 300;
+        sim.rng_seed = Some(5);
+        sim.settings.growth_model = crate::settings::GrowthModelType::ClusterCluster;
+        sim.reset_with_seed(SeedPattern::Point);
+        for _ in 0..2000 {
+            sim.step();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        sim.to_state().save_to_file(&path).unwrap();
+        let mut resumed = DlaSimulation::from_state(SimulationState::load_from_file(&path).unwrap());
+        assert!(resumed.cluster_count().is_some_and(|n| n > 1));
+        assert_eq!(resumed.cluster_count(), sim.cluster_count());
+        assert_eq!(resumed.progress(), sim.progress());
+
+        for _ in 0..2000 {
+            sim.step();
+            resumed.step();
+        }
+        assert_eq!(resumed.cluster_count(), sim.cluster_count());
+        assert_eq!(resumed.to_state().particles, sim.to_state().particles);
+        assert_eq!(resumed.to_state().clusters, sim.to_state().clusters);
+    }
+
+    #[test]
+    fn test_loads_version_1_state() {
+        let mut sim = DlaSimulation::new(64, 64);
+        sim.num_particles = 200;
+        sim.reset_with_seed(SeedPattern::Point);
+        for _ in 0..50 {
+            sim.step();
+        }
+
+        // A version 1 file has no discs or seed count, and bare particles
+        let mut json = serde_json::to_value(sim.to_state()).unwrap();
+        json["version"] = 1.into();
+        let state = json.as_object_mut().unwrap();
+        state.remove("discs");
+        state.remove("seed_particles");
+        for p in state["particles"].as_array_mut().unwrap() {
+            let p = p.as_object_mut().unwrap();
+            for field in ["deposit", "cluster", "species", "parent"] {
+                p.remove(field);
+            }
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let resumed = DlaSimulation::from_state(SimulationState::load_from_file(&path).unwrap());
+        assert_eq!(resumed.particles_stuck, sim.particles_stuck);
+        assert_eq!(resumed.discs().len(), sim.particles_stuck);
+        assert_eq!(resumed.seed_particles(), 0);
+        assert!(resumed.discs().iter().all(|d| sim.get_particle(d.x as usize, d.y as usize).is_some()));
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        for version in [OLDEST_STATE_VERSION - 1, STATE_VERSION + 1] {
+            let mut state = DlaSimulation::new(32, 32).to_state();
+            state.version = version;
+            state.save_to_file(&path).unwrap();
+            assert!(SimulationState::load_from_file(&path).is_err());
+        }
+    }
+}
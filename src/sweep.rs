@@ -0,0 +1,481 @@
+//! Parameter sweeps: run every combination of a set of parameter values
+//! headlessly, with replicate seeds, and tabulate the results.
+
+use crate::headless::{self, RunStats};
+use dla_sim_tui::config::AppConfig;
+use dla_sim_tui::settings;
+use dla_sim_tui::snapshot;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Largest number of runs a sweep will expand to
+const MAX_SWEEP_RUNS: usize = 100_000;
+
+/// A swept parameter and the values it takes, parsed from `NAME=VALUES`.
+///
+/// VALUES is either a comma-separated list (`neighborhood=VonNeumann,Moore`)
+/// or an inclusive numeric range `START:END:STEP` (`tip_stickiness=0.2:1.0:0.2`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepParam {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+impl FromStr for SweepParam {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, spec) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid sweep parameter '{}': expected NAME=VALUES", s))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("Invalid sweep parameter '{}': missing name", s));
+        }
+        let values = if spec.contains(':') {
+            parse_range(spec).map_err(|e| format!("Invalid range for {}: {}", name, e))?
+        } else {
+            spec.split(',').map(|v| parse_value(v.trim())).collect()
+        };
+        if values.is_empty() {
+            return Err(format!("Sweep parameter {} has no values", name));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            values,
+        })
+    }
+}
+
+/// Parse a list entry as JSON (numbers, booleans), falling back to a plain string
+fn parse_value(s: &str) -> Value {
+    serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+}
+
+/// Expand `START:END:STEP` into its values, END included.
+/// Integer ranges produce integers so they fit integer fields.
+fn parse_range(spec: &str) -> Result<Vec<Value>, String> {
+    let parts: Vec<&str> = spec.split(':').map(str::trim).collect();
+    let [start, end, step] = parts[..] else {
+        return Err(format!("'{}' is not START:END:STEP", spec));
+    };
+    let num = |s: &str| s.parse::<f64>().map_err(|e| format!("'{}': {}", s, e));
+    let (start, end, step) = (num(start)?, num(end)?, num(step)?);
+    if step <= 0.0 || !step.is_finite() {
+        return Err("STEP must be positive".to_string());
+    }
+    if end < start {
+        return Err("END is below START".to_string());
+    }
+
+    let integral = [start, end, step].iter().all(|v| v.fract() == 0.0);
+    let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+    if count > MAX_SWEEP_RUNS {
+        return Err(format!("{} values is too many", count));
+    }
+    Ok((0..count)
+        .map(|i| {
+            let v = start + i as f64 * step;
+            if integral {
+                Value::from(v as i64)
+            } else {
+                // Round away float noise like 0.30000000000000004
+                Value::from((v * 1e9).round() / 1e9)
+            }
+        })
+        .collect())
+}
+
+/// Allowed range of each numeric parameter, the same bounds the CLI flags
+/// are clamped to in `build_config`
+const PARAM_RANGES: &[(&str, f64, f64)] = &[
+    ("num_particles", 100.0, f64::INFINITY),
+    ("stickiness", 0.1, 1.0),
+    ("steps_per_frame", 1.0, 100.0),
+    ("dbm_eta", 0.0, 4.0),
+    ("mobility_exponent", 0.0, 2.0),
+    ("walk_step_size", 0.5, 5.0),
+    ("walk_bias_angle", 0.0, 360.0),
+    ("walk_bias_strength", 0.0, 0.5),
+    ("radial_bias", -0.3, 0.3),
+    ("multi_contact_min", 1.0, 4.0),
+    ("tip_stickiness", 0.1, 1.0),
+    ("side_stickiness", 0.1, 1.0),
+    ("stickiness_gradient", -0.5, 0.5),
+    ("spawn_radius_offset", 5.0, 50.0),
+    ("escape_multiplier", 2.0, 6.0),
+    ("min_spawn_radius", 20.0, 100.0),
+    ("max_walk_iterations", 1000.0, 50000.0),
+    ("highlight_recent", 0.0, 50.0),
+];
+
+/// Return a copy of `base` with the named fields replaced.
+/// Names are looked up in the simulation settings first, then in the
+/// top-level config (`stickiness`, `num_particles`, `seed_pattern`, ...).
+/// Values the CLI would clamp or reject are errors here, so a sweep never
+/// runs a configuration the command line can't express.
+pub fn apply_params(base: &AppConfig, assignments: &[(&str, &Value)]) -> Result<AppConfig, String> {
+    let mut json =
+        serde_json::to_value(base).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    for &(name, value) in assignments {
+        if let Some(&(_, min, max)) = PARAM_RANGES.iter().find(|(n, _, _)| *n == name) {
+            if value.as_f64().is_some_and(|v| v < min || v > max) {
+                return Err(format!("Sweep value {}={} is outside {}..={}", name, value, min, max));
+            }
+        }
+        let target = if json["settings"].get(name).is_some() {
+            &mut json["settings"][name]
+        } else if json.get(name).is_some() && name != "settings" && name != "version" {
+            &mut json[name]
+        } else {
+            return Err(format!("Unknown sweep parameter: {}", name));
+        };
+        *target = value.clone();
+    }
+    let config: AppConfig =
+        serde_json::from_value(json).map_err(|e| format!("Invalid sweep value: {}", e))?;
+
+    if config.settings.species.len() > settings::MAX_SPECIES {
+        return Err(format!("Sweep value has more than {} species", settings::MAX_SPECIES));
+    }
+    config.settings.validate_stick_matrix().map_err(|e| format!("Invalid sweep value: {}", e))?;
+    config.seed_pattern.validate().map_err(|e| format!("Invalid sweep value: {}", e))?;
+    Ok(config)
+}
+
+/// Options controlling a sweep
+#[derive(Debug, Clone)]
+pub struct SweepOptions {
+    /// Grid width in cells
+    pub width: usize,
+    /// Grid height in cells
+    pub height: usize,
+    /// Maximum number of walker steps per run (0 = unlimited)
+    pub max_steps: usize,
+    /// Worker threads for particle walking within each run
+    pub threads: usize,
+    /// Runs executed concurrently
+    pub jobs: usize,
+    /// Seeds per parameter combination
+    pub replicates: usize,
+    /// Seed of the first replicate; replicate i uses `base_seed + i`
+    pub base_seed: u64,
+    /// Results file, CSV unless it ends in `.json`
+    pub out_path: String,
+    /// Directory for one PNG snapshot per run, if any
+    pub png_dir: Option<String>,
+    /// Image pixels per simulation cell for the snapshots
+    pub snapshot_scale: u32,
+    /// Copies per side in each snapshot (Wrap runs tile seamlessly)
+    pub snapshot_tiles: u32,
+}
+
+/// One finished run of a sweep
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepRow {
+    pub run: usize,
+    pub replicate: usize,
+    pub params: serde_json::Map<String, Value>,
+    #[serde(flatten)]
+    pub stats: RunStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+}
+
+/// A run waiting to be executed
+struct SweepRun {
+    run: usize,
+    replicate: usize,
+    params: serde_json::Map<String, Value>,
+    config: AppConfig,
+}
+
+/// Expand the parameter grid into runs. The last parameter varies fastest,
+/// and every combination gets the same replicate seeds.
+fn plan_runs(
+    base: &AppConfig,
+    params: &[SweepParam],
+    replicates: usize,
+    base_seed: u64,
+) -> Result<Vec<SweepRun>, String> {
+    let combinations = params
+        .iter()
+        .try_fold(1usize, |n, p| n.checked_mul(p.values.len()))
+        .and_then(|n| n.checked_mul(replicates))
+        .filter(|&n| n <= MAX_SWEEP_RUNS)
+        .ok_or_else(|| format!("Sweep expands to more than {} runs", MAX_SWEEP_RUNS))?
+        / replicates.max(1);
+
+    let mut runs = Vec::new();
+    for combo in 0..combinations {
+        // Mixed-radix decode of the combination index
+        let mut rest = combo;
+        let mut assignments = vec![(String::new(), Value::Null); params.len()];
+        for (slot, p) in assignments.iter_mut().zip(params).rev() {
+            *slot = (p.name.clone(), p.values[rest % p.values.len()].clone());
+            rest /= p.values.len();
+        }
+        let refs: Vec<(&str, &Value)> = assignments.iter().map(|(n, v)| (n.as_str(), v)).collect();
+        let config = apply_params(base, &refs)?;
+
+        for replicate in 0..replicates {
+            runs.push(SweepRun {
+                run: runs.len(),
+                replicate,
+                params: assignments.iter().cloned().collect(),
+                config: AppConfig {
+                    rng_seed: Some(base_seed.wrapping_add(replicate as u64)),
+                    ..config.clone()
+                },
+            });
+        }
+    }
+    Ok(runs)
+}
+
+/// Run every combination of `params` over `base` and write the results table.
+/// All combinations are validated before the first run starts.
+pub fn run(base: &AppConfig, params: &[SweepParam], options: &SweepOptions) -> Result<Vec<SweepRow>, String> {
+    let replicates = options.replicates.max(1);
+    let runs = plan_runs(base, params, replicates, options.base_seed)?;
+    let png_dir = options.png_dir.as_ref().map(PathBuf::from);
+    if let Some(dir) = &png_dir {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create PNG directory: {}", e))?;
+    }
+    eprintln!(
+        "Sweeping {} run(s): {} combination(s) x {} replicate(s), {}x{} grid, {} job(s)",
+        runs.len(),
+        runs.len() / replicates,
+        replicates,
+        options.width,
+        options.height,
+        options.jobs.max(1)
+    );
+
+    // Workers pull runs off a shared counter; rows are sorted back into run order
+    let next = AtomicUsize::new(0);
+    let rows = Mutex::new(Vec::with_capacity(runs.len()));
+    let first_error = Mutex::new(None);
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.clamp(1, runs.len().max(1)) {
+            scope.spawn(|| loop {
+                if first_error.lock().unwrap().is_some() {
+                    break;
+                }
+                let Some(run) = runs.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                    break;
+                };
+                match execute_run(run, options, png_dir.as_deref()) {
+                    Ok(row) => {
+                        eprintln!(
+                            "Run {}/{}: {} particles, D_f = {:.3}, {:.2}s",
+                            run.run + 1,
+                            runs.len(),
+                            row.stats.particles_stuck,
+                            row.stats.fractal_dimension,
+                            row.stats.elapsed_secs
+                        );
+                        rows.lock().unwrap().push(row);
+                    }
+                    Err(e) => {
+                        first_error.lock().unwrap().get_or_insert(e);
+                    }
+                }
+            });
+        }
+    });
+    if let Some(e) = first_error.into_inner().unwrap() {
+        return Err(e);
+    }
+
+    let mut rows = rows.into_inner().unwrap();
+    rows.sort_by_key(|r| r.run);
+    let names: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+    let path = Path::new(&options.out_path);
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+        write_json(&rows, path)?;
+    } else {
+        write_csv(&rows, &names, path)?;
+    }
+    eprintln!("Wrote {} rows to {}", rows.len(), options.out_path);
+    Ok(rows)
+}
+
+/// Grow one cluster and collect its stats, plus a snapshot when requested
+fn execute_run(run: &SweepRun, options: &SweepOptions, png_dir: Option<&Path>) -> Result<SweepRow, String> {
+    let mut sim = headless::simulation_from_config(&run.config, options.width, options.height);
+    sim.paused = false;
+    let start = Instant::now();
+    let steps = headless::run_to_completion(&mut sim, options.max_steps, options.threads, false);
+    let stats = RunStats::from_simulation(&sim, steps, options.threads, start.elapsed().as_secs_f64());
+
+    let snapshot = match png_dir {
+        Some(dir) => {
+            let path = dir.join(format!("run-{:04}.png", run.run));
+            let theme = run.config.theme.theme();
+            snapshot::save_snapshot(
+                &path,
+                &sim,
+                &theme.color_scheme,
+                run.config.color_by_age,
+                theme.background,
+                options.snapshot_scale,
+                options.snapshot_tiles,
+            )?;
+            Some(path.to_string_lossy().into_owned())
+        }
+        None => None,
+    };
+
+    Ok(SweepRow {
+        run: run.run,
+        replicate: run.replicate,
+        params: run.params.clone(),
+        stats,
+        snapshot,
+    })
+}
+
+/// Format a parameter value for a CSV cell, quoting strings with commas
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::String(s) if s.contains(',') || s.contains('"') => format!("\"{}\"", s.replace('"', "\"\"")),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Write the sweep results as CSV, one row per run
+pub fn write_csv(rows: &[SweepRow], param_names: &[&str], path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create sweep results: {}", e))?;
+    let mut out = BufWriter::new(file);
+    let write_err = |e: std::io::Error| format!("Failed to write sweep results: {}", e);
+
+    let mut header = vec!["run", "replicate"];
+    header.extend_from_slice(param_names);
+    header.extend_from_slice(&[
+        "rng_seed",
+        "particles_stuck",
+        "completed",
+        "max_radius",
+        "fractal_dimension",
+        "fractal_r_squared",
+        "elapsed_secs",
+    ]);
+    let with_snapshots = rows.iter().any(|r| r.snapshot.is_some());
+    if with_snapshots {
+        header.push("snapshot");
+    }
+    writeln!(out, "{}", header.join(",")).map_err(write_err)?;
+
+    for row in rows {
+        let mut cells = vec![row.run.to_string(), row.replicate.to_string()];
+        cells.extend(param_names.iter().map(|name| csv_cell(&row.params[*name])));
+        let s = &row.stats;
+        cells.extend([
+            s.rng_seed.to_string(),
+            s.particles_stuck.to_string(),
+            s.completed.to_string(),
+            format!("{:.3}", s.max_radius),
+            format!("{:.4}", s.fractal_dimension),
+            format!("{:.4}", s.fractal_r_squared),
+            format!("{:.3}", s.elapsed_secs),
+        ]);
+        if with_snapshots {
+            cells.push(csv_cell(&Value::String(row.snapshot.clone().unwrap_or_default())));
+        }
+        writeln!(out, "{}", cells.join(",")).map_err(write_err)?;
+    }
+    out.flush().map_err(write_err)
+}
+
+/// Write the sweep results as a pretty-printed JSON array
+pub fn write_json(rows: &[SweepRow], path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(rows)
+        .map_err(|e| format!("Failed to serialize sweep results: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write sweep results: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_param_parsing() {
+        let p: SweepParam = "tip_stickiness=0.2:1.0:0.2".parse().unwrap();
+        assert_eq!(p.values, vec![0.2, 0.4, 0.6, 0.8, 1.0].into_iter().map(Value::from).collect::<Vec<_>>());
+
+        let p: SweepParam = "multi_contact_min=1:3:1".parse().unwrap();
+        assert_eq!(p.values, vec![Value::from(1), Value::from(2), Value::from(3)]);
+
+        let p: SweepParam = "neighborhood=VonNeumann,Moore".parse().unwrap();
+        let config = apply_params(&AppConfig::default(), &[(&p.name, &p.values[0])]).unwrap();
+        assert_eq!(config.settings.neighborhood, dla_sim_tui::settings::NeighborhoodType::VonNeumann);
+
+        assert!("no_such_field=1,2".parse::<SweepParam>().is_ok_and(|p| {
+            apply_params(&AppConfig::default(), &[(&p.name, &p.values[0])]).is_err()
+        }));
+        assert!("tip_stickiness".parse::<SweepParam>().is_err());
+    }
+
+    #[test]
+    fn test_sweep_values_follow_cli_bounds() {
+        let apply = |spec: &str| {
+            let p: SweepParam = spec.parse().unwrap();
+            p.values.iter().map(|v| apply_params(&AppConfig::default(), &[(&p.name, v)])).collect::<Vec<_>>()
+        };
+
+        // Range ends are allowed, anything the CLI would clamp is not
+        assert!(apply("tip_stickiness=0.1,1.0").iter().all(Result::is_ok));
+        assert!(apply("tip_stickiness=0.0:0.1:0.1")[0].as_ref().is_err_and(|e| e.contains("outside")));
+        assert!(apply("num_particles=99")[0].is_err());
+        assert!(apply("max_walk_iterations=50001")[0].is_err());
+        assert!(apply("radial_bias=-0.3")[0].is_ok());
+
+        // Whole-config validation still runs after the values are applied
+        let matrix = serde_json::json!([0.5, 0.5]);
+        assert!(apply_params(&AppConfig::default(), &[("stick_matrix", &matrix)])
+            .is_err_and(|e| e.contains("stick matrix")));
+    }
+
+    #[test]
+    fn test_sweep_writes_one_row_per_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("results.csv");
+        let base = AppConfig {
+            num_particles: 60,
+            ..Default::default()
+        };
+        let params = vec!["side_stickiness=0.5,1.0".parse().unwrap()];
+        let options = SweepOptions {
+            width: 60,
+            height: 60,
+            max_steps: 0,
+            threads: 1,
+            jobs: 2,
+            replicates: 2,
+            base_seed: 11,
+            out_path: out.to_string_lossy().into_owned(),
+            png_dir: None,
+            snapshot_scale: 1,
+            snapshot_tiles: 1,
+        };
+
+        let rows = run(&base, &params, &options).unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[2].params["side_stickiness"], Value::from(1.0));
+        assert_eq!(rows[3].stats.rng_seed, 12);
+
+        let csv = std::fs::read_to_string(&out).unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("run,replicate,side_stickiness,rng_seed,"));
+        assert_eq!(lines.count(), 4);
+    }
+}
@@ -1,6 +1,8 @@
+#[cfg(feature = "tui")]
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "tui")]
 use crate::color::ColorScheme;
 
 /// Background mode for themes
@@ -14,6 +16,7 @@ pub enum BackgroundMode {
 }
 
 /// Complete theme configuration
+#[cfg(feature = "tui")]
 #[derive(Debug, Clone)]
 pub struct Theme {
     /// Human-readable theme name (kept for debugging and potential future UI display)
@@ -106,6 +109,7 @@ impl ThemeId {
     }
 
     /// Get the full theme configuration for this theme ID
+    #[cfg(feature = "tui")]
     pub fn theme(&self) -> Theme {
         match self {
             ThemeId::Default => Theme {
@@ -0,0 +1,211 @@
+//! The growth tree: each stuck particle links to the particle it attached to
+//! (`ParticleData::parent`), so a run is a forest rooted at its seed particles.
+//!
+//! Parents always stuck before their children, so every measure here is a
+//! single pass over the discs in stick order (or in reverse).
+
+use crate::settings::ColorMode;
+use crate::simulation::Disc;
+use serde::Serialize;
+
+/// Per-particle measures of the growth tree, indexed like `DlaSimulation::discs`
+#[derive(Debug, Clone, Default)]
+pub struct GrowthTree {
+    parent: Vec<Option<u32>>,
+    /// Links between the particle and its root
+    depth: Vec<u32>,
+    /// Strahler order (tips are 1)
+    order: Vec<u8>,
+    /// Particles in the subtree below, not counting the particle itself
+    descendants: Vec<u32>,
+    children: Vec<u32>,
+    max_depth: u32,
+    max_order: u8,
+    max_descendants: u32,
+}
+
+impl GrowthTree {
+    /// Build the tree from discs in stick order. A parent link that doesn't
+    /// point to an earlier disc is treated as missing.
+    pub fn new(discs: &[Disc]) -> Self {
+        let n = discs.len();
+        let parent: Vec<Option<u32>> = (0..n)
+            .map(|i| discs[i].data.parent.filter(|&p| (p as usize) < i))
+            .collect();
+
+        let mut depth = vec![0u32; n];
+        for i in 0..n {
+            if let Some(p) = parent[i] {
+                depth[i] = depth[p as usize] + 1;
+            }
+        }
+
+        // Children stick after their parent, so walking backwards finishes every
+        // subtree before its root. Strahler: the highest child order, plus one
+        // when at least two children share it.
+        let mut descendants = vec![0u32; n];
+        let mut children = vec![0u32; n];
+        let mut order = vec![1u8; n];
+        let mut top_child = vec![(0u8, 0u32); n];
+        for i in (0..n).rev() {
+            let (top, count) = top_child[i];
+            order[i] = match count {
+                0 => 1,
+                1 => top,
+                _ => top.saturating_add(1),
+            };
+            if let Some(p) = parent[i].map(|p| p as usize) {
+                descendants[p] += descendants[i] + 1;
+                children[p] += 1;
+                let (top, count) = &mut top_child[p];
+                if order[i] > *top {
+                    (*top, *count) = (order[i], 1);
+                } else if order[i] == *top {
+                    *count += 1;
+                }
+            }
+        }
+
+        Self {
+            max_depth: depth.iter().copied().max().unwrap_or(0),
+            max_order: order.iter().copied().max().unwrap_or(0),
+            max_descendants: descendants.iter().copied().max().unwrap_or(0),
+            parent,
+            depth,
+            order,
+            descendants,
+            children,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.depth.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth.is_empty()
+    }
+
+    /// Chemical distance of particle `i` from its root, in links
+    pub fn depth(&self, i: usize) -> u32 {
+        self.depth[i]
+    }
+
+    /// Strahler order of particle `i`
+    pub fn order(&self, i: usize) -> u8 {
+        self.order[i]
+    }
+
+    /// Particles that grew from particle `i`, directly or not
+    pub fn descendants(&self, i: usize) -> u32 {
+        self.descendants[i]
+    }
+
+    /// Colour value (0-1) of particle `i` for a tree colour mode (0 for other modes)
+    pub fn shade(&self, i: usize, mode: ColorMode) -> f32 {
+        match mode {
+            ColorMode::TreeDepth => self.depth[i] as f32 / self.max_depth.max(1) as f32,
+            ColorMode::Strahler => {
+                (self.order[i] - 1) as f32 / self.max_order.saturating_sub(1).max(1) as f32
+            }
+            // Subtree sizes span orders of magnitude, so a linear scale would
+            // leave everything but the trunk dark
+            ColorMode::Descendants => {
+                (self.descendants[i] as f32).ln_1p() / (self.max_descendants.max(1) as f32).ln_1p()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Summary measures of the whole forest
+    pub fn stats(&self) -> TreeStats {
+        let n = self.len();
+        let roots = self.parent.iter().filter(|p| p.is_none()).count();
+        let tips = self.children.iter().filter(|&&c| c == 0).count();
+        let branch_points = self.children.iter().filter(|&&c| c >= 2).count();
+
+        // Horton's bifurcation ratio from the number of Strahler streams of the
+        // lowest and highest order: R = (N_1 / N_max)^(1 / (max - 1)).
+        // A stream is a run of same-order particles, counted at its lower end.
+        let branching_ratio = if self.max_order >= 2 {
+            let mut streams = vec![0usize; self.max_order as usize + 1];
+            for i in 0..n {
+                let order = self.order[i];
+                if self.parent[i].is_none_or(|p| self.order[p as usize] != order) {
+                    streams[order as usize] += 1;
+                }
+            }
+            let top = streams[self.max_order as usize].max(1) as f32;
+            (streams[1] as f32 / top).powf(1.0 / (self.max_order - 1) as f32)
+        } else {
+            0.0
+        };
+
+        TreeStats {
+            roots,
+            tips,
+            branch_points,
+            longest_path: self.max_depth,
+            mean_depth: if n > 0 {
+                self.depth.iter().map(|&d| d as f32).sum::<f32>() / n as f32
+            } else {
+                0.0
+            },
+            strahler_order: self.max_order,
+            branching_ratio,
+        }
+    }
+}
+
+/// Whole-tree measures for comparing how dendritic or bushy a cluster is
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct TreeStats {
+    /// Particles without a parent (seeds and wall-only deposits)
+    pub roots: usize,
+    /// Particles nothing grew from
+    pub tips: usize,
+    /// Particles with two or more children
+    pub branch_points: usize,
+    /// Most links from a root to any particle
+    pub longest_path: u32,
+    pub mean_depth: f32,
+    /// Highest Strahler order in the forest
+    pub strahler_order: u8,
+    /// Horton bifurcation ratio of the Strahler streams (0 below order 2)
+    pub branching_ratio: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::ParticleData;
+
+    fn disc(parent: Option<u32>) -> Disc {
+        Disc {
+            x: 0.0,
+            y: 0.0,
+            data: ParticleData {
+                parent,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_fork_measures() {
+        // A stem of two particles forking into two tips, plus a stray root
+        let discs = [disc(None), disc(Some(0)), disc(Some(1)), disc(Some(1)), disc(None)];
+        let tree = GrowthTree::new(&discs);
+        assert_eq!((0..5).map(|i| tree.depth(i)).collect::<Vec<_>>(), [0, 1, 2, 2, 0]);
+        assert_eq!((0..5).map(|i| tree.order(i)).collect::<Vec<_>>(), [2, 2, 1, 1, 1]);
+        assert_eq!(tree.descendants(0), 3);
+        assert_eq!(tree.shade(2, ColorMode::TreeDepth), 1.0);
+        assert_eq!(tree.shade(0, ColorMode::Strahler), 1.0);
+
+        let stats = tree.stats();
+        assert_eq!((stats.roots, stats.tips, stats.branch_points), (2, 3, 1));
+        assert_eq!((stats.longest_path, stats.strahler_order), (2, 2));
+        // Order-1 streams: both tips and the stray root; one order-2 stream
+        assert_eq!(stats.branching_ratio, 3.0);
+    }
+}
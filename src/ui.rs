@@ -1,11 +1,13 @@
-use crate::app::{App, Focus, ParamPopup, PresetPopup, TextInputPopup, ViewMode};
+use crate::app::{App, Focus, ParamPopup, PresetPopup, StateAction, TextInputPopup, ViewMode};
 use crate::braille;
-use crate::theme::BackgroundMode;
+use dla_sim_tui::analysis::AnalysisSample;
+use dla_sim_tui::theme::BackgroundMode;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    widgets::{Axis, Block, BorderType, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Wrap},
     Frame,
 };
 
@@ -16,12 +18,16 @@ const POPUP_PADDING: u16 = 4;
 const TOAST_HEIGHT: u16 = 3;
 const TOAST_BOTTOM_OFFSET: u16 = 5;
 const MIN_POPUP_WIDTH: u16 = 20;
+/// Status box height (5 content lines + borders)
+const STATUS_HEIGHT: u16 = 7;
+/// Height of the dimension chart in the States panel
+const ANALYSIS_HEIGHT: u16 = 10;
 
 /// Max scroll for help content (generous to account for text wrapping on small screens)
-pub const HELP_CONTENT_LINES: u16 = 79;
+pub const HELP_CONTENT_LINES: u16 = 92;
 
-/// Number of lines in controls content (9 main + 10 non-shift + 11 Shift+letter hints)
-pub const CONTROLS_CONTENT_LINES: u16 = 30;
+/// Number of lines in controls content (9 main + 13 non-shift + 12 Shift+letter hints)
+pub const CONTROLS_CONTENT_LINES: u16 = 35;
 
 /// Number of lines in parameters content
 pub const PARAMS_CONTENT_LINES: u16 = 24;
@@ -83,6 +89,26 @@ pub fn render(frame: &mut Frame, app: &App) {
         render_export_result(frame, area, result, &app.theme);
     }
 
+    // Render snapshot popup if open (overlays everything)
+    if let Some(popup) = &app.snapshot_popup {
+        render_snapshot_popup(frame, area, popup, app.snapshot_scale, app.snapshot_tiles, &app.theme);
+    }
+
+    // Render snapshot result toast if present
+    if let Some(result) = &app.snapshot_result {
+        render_export_result(frame, area, result, &app.theme);
+    }
+
+    // Render state save/load popup if open (overlays everything)
+    if let Some(popup) = &app.state_popup {
+        render_state_popup(frame, area, popup, app.state_action, &app.theme);
+    }
+
+    // Render state result toast if present
+    if let Some(result) = &app.state_result {
+        render_state_result(frame, area, result, &app.theme);
+    }
+
     // Render recording popup if open (overlays everything)
     if let Some(popup) = &app.recording_popup {
         render_recording_popup(frame, area, popup, &app.theme);
@@ -100,13 +126,23 @@ pub fn render(frame: &mut Frame, app: &App) {
 
     // Render preset save popup if open
     if let Some(popup) = &app.preset_save_popup {
-        render_preset_save_popup(frame, area, popup, &app.theme);
+        render_name_popup(frame, area, popup, "Enter preset name:", &app.theme);
     }
 
     // Render preset result toast if present
     if let Some(result) = &app.preset_result {
         render_preset_result(frame, area, result, &app.theme);
     }
+
+    // Render custom seed save popup if open
+    if let Some(popup) = &app.seed_save_popup {
+        render_name_popup(frame, area, popup, "Enter seed name:", &app.theme);
+    }
+
+    // Render custom seed result toast if present
+    if let Some(result) = &app.paint_result {
+        render_preset_result(frame, area, result, &app.theme);
+    }
 }
 
 /// Calculate the canvas size (excluding borders)
@@ -128,6 +164,22 @@ pub fn get_canvas_size(frame_area: Rect, view_mode: ViewMode) -> (u16, u16) {
     }
 }
 
+/// Screen area of the canvas inside its border, for mapping mouse positions
+pub fn get_canvas_area(frame_area: Rect, view_mode: ViewMode) -> Rect {
+    let (width, height) = get_canvas_size(frame_area, view_mode);
+    let left = match view_mode {
+        ViewMode::Fullscreen => 0,
+        ViewMode::Default => SIDEBAR_WIDTH,
+        ViewMode::States => STATES_PANEL_WIDTH,
+    };
+    Rect {
+        x: frame_area.x + left + 1,
+        y: frame_area.y + 1,
+        width,
+        height,
+    }
+}
+
 /// Calculate the number of visible lines in the help popup based on terminal height
 pub fn get_help_visible_lines(terminal_height: u16) -> u16 {
     // Help popup height calculation (from render_help_overlay)
@@ -138,7 +190,6 @@ pub fn get_help_visible_lines(terminal_height: u16) -> u16 {
 
 /// Calculate the number of visible lines in the controls box based on terminal height
 pub fn get_controls_visible_lines(terminal_height: u16) -> u16 {
-    const STATUS_HEIGHT: u16 = 5;
     const NAV_HEIGHT: u16 = 4;
     const MIN_CONTROLS_VISIBLE: u16 = 4;
     const BORDERS: u16 = 2;
@@ -164,7 +215,6 @@ pub fn get_controls_visible_lines(terminal_height: u16) -> u16 {
 
 fn render_sidebar(frame: &mut Frame, area: Rect, app: &App) {
     // Fixed component heights
-    const STATUS_HEIGHT: u16 = 5;
     const NAV_HEIGHT: u16 = 4;
     const MIN_CONTROLS_VISIBLE: u16 = 4;
     const BORDERS: u16 = 2;
@@ -216,7 +266,10 @@ fn render_status_box(frame: &mut Frame, area: Rect, app: &App) {
     let theme = &app.theme;
     let block = styled_block(" DLA Sim TUI ", theme.border_color);
 
-    let progress = app.simulation.progress();
+    let progress = match &app.timeline {
+        Some(timeline) => timeline.cursor as f32 / timeline.end.max(1) as f32,
+        None => app.simulation.progress(),
+    };
     let progress_width = (area.width.saturating_sub(4)) as usize;
     let filled = (progress * progress_width as f32) as usize;
     let empty = progress_width.saturating_sub(filled);
@@ -224,7 +277,18 @@ fn render_status_box(frame: &mut Frame, area: Rect, app: &App) {
     // Recording indicator takes priority, then simulation status
     let (status_text, status_color) = if app.is_recording() {
         let frame_count = app.recorder.frame_count().unwrap_or(0);
-        (format!("REC {}", frame_count), theme.error_color)
+        let (queued, dropped) = app.recorder.queue_stats().unwrap_or((0, 0));
+        let text = if dropped > 0 {
+            format!("REC {} q{} drop {}", frame_count, queued, dropped)
+        } else {
+            format!("REC {} q{}", frame_count, queued)
+        };
+        (text, theme.error_color)
+    } else if let Some(timeline) = &app.timeline {
+        (format!("TIMELINE {}/{}", timeline.cursor, timeline.end), theme.highlight_color)
+    } else if app.paint_mode {
+        let tool = app.paint_tool.name().to_uppercase();
+        (format!("PAINT {} r{}", tool, app.brush_size), theme.highlight_color)
     } else if app.simulation.paused {
         ("PAUSED".to_string(), theme.highlight_color)
     } else if app.simulation.is_complete() {
@@ -241,11 +305,20 @@ fn render_status_box(frame: &mut Frame, area: Rect, app: &App) {
         "D_f: --".to_string()
     };
 
+    // RNG seed of the current run (fixed seeds reproduce the run exactly)
+    let seed_text = match app.simulation.rng_seed {
+        Some(seed) => format!("Seed: {}", seed),
+        None => format!("Seed: {} (rnd)", app.simulation.active_seed()),
+    };
+
     let content = vec![
         Line::from(Span::styled(dim_text, Style::default().fg(theme.text_color))),
         Line::from(vec![
             Span::styled(
-                format!("N: {} / {}", app.simulation.particles_stuck, app.simulation.num_particles),
+                match app.simulation.cluster_count() {
+                    Some(clusters) => format!("Clusters: {}", clusters),
+                    None => format!("N: {} / {}", app.simulation.particles_stuck, app.simulation.particle_budget()),
+                },
                 Style::default().fg(theme.text_color),
             ),
         ]),
@@ -254,6 +327,7 @@ fn render_status_box(frame: &mut Frame, area: Rect, app: &App) {
             Span::styled("░".repeat(empty), Style::default().fg(theme.dim_text_color)),
         ]),
         Line::from(Span::styled(status_text, Style::default().fg(status_color))),
+        Line::from(Span::styled(seed_text, Style::default().fg(theme.dim_text_color))),
     ];
 
     let paragraph = Paragraph::new(content).block(block);
@@ -300,7 +374,7 @@ fn render_params_box(frame: &mut Frame, area: Rect, app: &App) {
 
     // Parameters grouped by type, alphabetical within each group
     let content = vec![
-        // === Movement (alphabetical: adaptfactor, adaptive, direction, force, lattice, radial, walk) ===
+        // === Movement (alphabetical: adaptfactor, adaptive, direction, force, jumps, lattice, off-lattice, radial, walk) ===
         make_header("Movement"),
         make_line(
             "adaptive",
@@ -317,16 +391,41 @@ fn render_params_box(frame: &mut Frame, area: Rect, app: &App) {
             format!("{:.0}°", settings.walk_bias_angle),
             app.focus == Focus::Direction,
         ),
+        make_line(
+            "eta",
+            format!("{:.2}", settings.dbm_eta),
+            app.focus == Focus::DbmEta,
+        ),
         make_line(
             "force",
             format!("{:.2}", settings.walk_bias_strength),
             app.focus == Focus::Force,
         ),
+        make_line(
+            "jumps",
+            if settings.distance_jumps { "on" } else { "off" }.to_string(),
+            app.focus == Focus::Jumps,
+        ),
         make_line(
             "lattice",
             if settings.lattice_walk { "on" } else { "off" }.to_string(),
             app.focus == Focus::LatticeWalk,
         ),
+        make_line(
+            "mobility",
+            format!("{:.1}", settings.mobility_exponent),
+            app.focus == Focus::Mobility,
+        ),
+        make_line(
+            "model",
+            settings.growth_model.name().to_lowercase(),
+            app.focus == Focus::GrowthModel,
+        ),
+        make_line(
+            "off-lattice",
+            if settings.off_lattice { "on" } else { "off" }.to_string(),
+            app.focus == Focus::OffLattice,
+        ),
         make_line(
             "radial",
             format!("{:.2}", settings.radial_bias),
@@ -369,7 +468,7 @@ fn render_params_box(frame: &mut Frame, area: Rect, app: &App) {
             format!("{:.1}", settings.tip_stickiness),
             app.focus == Focus::TipSticky,
         ),
-        // === Spawn (alphabetical: bound, escape, maxsteps, minradius, spawn, spawnoff) ===
+        // === Spawn (alphabetical: bound, escape, maxsteps, minradius, spawn, spawnoff, walls) ===
         make_header("Spawn"),
         make_line(
             "bound",
@@ -401,6 +500,11 @@ fn render_params_box(frame: &mut Frame, area: Rect, app: &App) {
             format!("{:.0}", settings.spawn_radius_offset),
             app.focus == Focus::SpawnOffset,
         ),
+        make_line(
+            "walls",
+            settings.sticky_edges.name().to_lowercase(),
+            app.focus == Focus::StickyEdges,
+        ),
         // === Visual (alphabetical: age, color, highlight, invert, mode, particles, seed, speed) ===
         make_header("Visual"),
         make_line(
@@ -560,6 +664,21 @@ fn render_controls_box(frame: &mut Frame, area: Rect, app: &App) {
             Span::styled("`", key_style),
             Span::styled(" record", desc_style),
         ]),
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("F", key_style),
+            Span::styled(" potential", desc_style),
+        ]),
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("D", key_style),
+            Span::styled(" paint", desc_style),
+        ]),
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("Z", key_style),
+            Span::styled(" timeline", desc_style),
+        ]),
         // Shift+key hotkeys
         Line::from(vec![
             Span::raw(" "),
@@ -576,6 +695,16 @@ fn render_controls_box(frame: &mut Frame, area: Rect, app: &App) {
             Span::styled("Shift+X:", key_style),
             Span::styled(" export", desc_style),
         ]),
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("Shift+V:", key_style),
+            Span::styled(" png", desc_style),
+        ]),
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("F5/F9:", key_style),
+            Span::styled(" save/load", desc_style),
+        ]),
         // Shift+letter hotkeys
         Line::from(vec![
             Span::raw(" "),
@@ -697,18 +826,77 @@ fn render_states_layout(frame: &mut Frame, area: Rect, app: &App) {
     render_canvas(frame, layout[1], app);
 }
 
-/// Render the States panel with status and two-column params
+/// Render the States panel with status, dimension chart and two-column params
 fn render_states_panel(frame: &mut Frame, area: Rect, app: &App) {
     let sections = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(5),  // Status box
+            Constraint::Length(STATUS_HEIGHT),   // Status box
+            Constraint::Length(ANALYSIS_HEIGHT), // Dimension chart
             Constraint::Min(10),    // Two-column params (fills available space)
         ])
         .split(area);
 
     render_status_box(frame, sections[0], app);
-    render_two_column_params(frame, sections[1], app);
+    render_analysis_chart(frame, sections[1], app);
+    render_two_column_params(frame, sections[2], app);
+}
+
+/// Render how the dimension estimates converge as the cluster grows
+fn render_analysis_chart(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let samples = app.analysis.samples();
+
+    let title = match app.analysis.latest() {
+        Some(s) if s.mass_radius_dimension > 0.0 => format!(
+            " D  mass {:.2}  corr {:.2}  rg {:.2} ",
+            s.mass_radius_dimension, s.correlation_dimension, s.rg_dimension
+        ),
+        _ => " D: -- ".to_string(),
+    };
+
+    // (particles, estimate) points, skipping samples without enough data
+    let series = |estimate: fn(&AnalysisSample) -> f32| -> Vec<(f64, f64)> {
+        samples
+            .iter()
+            .filter(|s| estimate(s) > 0.0)
+            .map(|s| (s.particles as f64, estimate(s) as f64))
+            .collect()
+    };
+    let mass = series(|s| s.mass_radius_dimension);
+    let corr = series(|s| s.correlation_dimension);
+    let rg = series(|s| s.rg_dimension);
+
+    let dataset = |name: &'static str, data, color| {
+        Dataset::default()
+            .name(name)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data)
+    };
+    let datasets = vec![
+        dataset("rg", &rg, theme.dim_text_color),
+        dataset("corr", &corr, theme.text_color),
+        dataset("mass", &mass, theme.highlight_color),
+    ];
+
+    let axis_style = Style::default().fg(theme.dim_text_color);
+    let chart = Chart::new(datasets)
+        .block(styled_block(&title, theme.border_color))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, app.simulation.num_particles.max(1) as f64])
+                .style(axis_style),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([1.0, 2.0])
+                .labels(["1.0", "1.5", "2.0"])
+                .style(axis_style),
+        )
+        .legend_position(None);
+    frame.render_widget(chart, area);
 }
 
 /// Render parameters in two columns for States mode
@@ -760,14 +948,19 @@ fn render_two_column_params(frame: &mut Frame, area: Rect, app: &App) {
         ))
     };
 
-    // Left column content: Movement + Sticking (15 lines)
+    // Left column content: Movement + Sticking (20 lines)
     let left_content: Vec<Line<'_>> = vec![
         make_header("movement"),
         make_line("adaptive", if settings.adaptive_step { "on" } else { "off" }.to_string(), app.focus == Focus::AdaptiveStep),
         make_line("adapt fact", format!("{:.2}", settings.adaptive_step_factor), app.focus == Focus::AdaptiveFactor),
         make_line("direction", format!("{:.0}°", settings.walk_bias_angle), app.focus == Focus::Direction),
+        make_line("eta", format!("{:.2}", settings.dbm_eta), app.focus == Focus::DbmEta),
         make_line("force", format!("{:.2}", settings.walk_bias_strength), app.focus == Focus::Force),
+        make_line("jumps", if settings.distance_jumps { "on" } else { "off" }.to_string(), app.focus == Focus::Jumps),
         make_line("lattice", if settings.lattice_walk { "on" } else { "off" }.to_string(), app.focus == Focus::LatticeWalk),
+        make_line("mobility", format!("{:.1}", settings.mobility_exponent), app.focus == Focus::Mobility),
+        make_line("model", settings.growth_model.name().to_lowercase(), app.focus == Focus::GrowthModel),
+        make_line("off-lattice", if settings.off_lattice { "on" } else { "off" }.to_string(), app.focus == Focus::OffLattice),
         make_line("radial", format!("{:.2}", settings.radial_bias), app.focus == Focus::RadialBias),
         make_line("walk", format!("{:.1}", settings.walk_step_size), app.focus == Focus::WalkStep),
         make_header("sticking"),
@@ -779,7 +972,7 @@ fn render_two_column_params(frame: &mut Frame, area: Rect, app: &App) {
         make_line("tip", format!("{:.1}", settings.tip_stickiness), app.focus == Focus::TipSticky),
     ];
 
-    // Right column content: Spawn + Visual (16 lines)
+    // Right column content: Spawn + Visual (17 lines)
     let right_content: Vec<Line<'_>> = vec![
         make_header("spawn"),
         make_line("bound", settings.boundary_behavior.name().to_lowercase(), app.focus == Focus::Boundary),
@@ -788,6 +981,7 @@ fn render_two_column_params(frame: &mut Frame, area: Rect, app: &App) {
         make_line("min radius", format!("{:.0}", settings.min_spawn_radius), app.focus == Focus::MinRadius),
         make_line("spawn", settings.spawn_mode.name().to_lowercase(), app.focus == Focus::Spawn),
         make_line("spawn off", format!("{:.0}", settings.spawn_radius_offset), app.focus == Focus::SpawnOffset),
+        make_line("walls", settings.sticky_edges.name().to_lowercase(), app.focus == Focus::StickyEdges),
         make_header("visual"),
         make_line("age", if app.color_by_age { "on" } else { "off" }.to_string(), app.focus == Focus::Age),
         make_line("color", app.color_scheme.name().to_lowercase(), app.focus == Focus::ColorScheme),
@@ -804,16 +998,21 @@ fn render_two_column_params(frame: &mut Frame, area: Rect, app: &App) {
         Focus::AdaptiveStep => Some(1),
         Focus::AdaptiveFactor => Some(2),
         Focus::Direction => Some(3),
-        Focus::Force => Some(4),
-        Focus::LatticeWalk => Some(5),
-        Focus::RadialBias => Some(6),
-        Focus::WalkStep => Some(7),
-        Focus::MultiContact => Some(9),
-        Focus::StickyGradient => Some(10),
-        Focus::Neighborhood => Some(11),
-        Focus::Stickiness => Some(12),
-        Focus::SideSticky => Some(13),
-        Focus::TipSticky => Some(14),
+        Focus::DbmEta => Some(4),
+        Focus::Force => Some(5),
+        Focus::Jumps => Some(6),
+        Focus::LatticeWalk => Some(7),
+        Focus::Mobility => Some(8),
+        Focus::GrowthModel => Some(9),
+        Focus::OffLattice => Some(10),
+        Focus::RadialBias => Some(11),
+        Focus::WalkStep => Some(12),
+        Focus::MultiContact => Some(14),
+        Focus::StickyGradient => Some(15),
+        Focus::Neighborhood => Some(16),
+        Focus::Stickiness => Some(17),
+        Focus::SideSticky => Some(18),
+        Focus::TipSticky => Some(19),
         _ => None,
     };
 
@@ -825,14 +1024,15 @@ fn render_two_column_params(frame: &mut Frame, area: Rect, app: &App) {
         Focus::MinRadius => Some(4),
         Focus::Spawn => Some(5),
         Focus::SpawnOffset => Some(6),
-        Focus::Age => Some(8),
-        Focus::ColorScheme => Some(9),
-        Focus::Highlight => Some(10),
-        Focus::Invert => Some(11),
-        Focus::Mode => Some(12),
-        Focus::Particles => Some(13),
-        Focus::Seed => Some(14),
-        Focus::Speed => Some(15),
+        Focus::StickyEdges => Some(7),
+        Focus::Age => Some(9),
+        Focus::ColorScheme => Some(10),
+        Focus::Highlight => Some(11),
+        Focus::Invert => Some(12),
+        Focus::Mode => Some(13),
+        Focus::Particles => Some(14),
+        Focus::Seed => Some(15),
+        Focus::Speed => Some(16),
         _ => None,
     };
 
@@ -888,12 +1088,29 @@ fn render_canvas(frame: &mut Frame, area: Rect, app: &App) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    // Shade cell backgrounds with the potential before drawing particles over them
+    if app.show_potential {
+        if let Some(potential) = app.simulation.potential() {
+            let buf = frame.buffer_mut();
+            for (x, y, color) in braille::render_potential(&app.simulation, potential, inner.width, inner.height) {
+                buf[(inner.x + x, inner.y + y)].set_bg(color);
+            }
+        }
+    }
+
+    // Dim the walls, sinks and no-stick zones of a domain mask
+    let buf = frame.buffer_mut();
+    for (x, y, color) in braille::render_mask(&app.simulation, inner.width, inner.height) {
+        buf[(inner.x + x, inner.y + y)].set_bg(color);
+    }
+
     // Get settings for rendering
     let settings = &app.simulation.settings;
 
     // Render Braille pattern (uses LUT for fast color lookup)
     let cells = braille::render_to_braille(
         &app.simulation,
+        app.visible_age(),
         inner.width,
         inner.height,
         &app.color_lut,
@@ -971,6 +1188,8 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, app: &App) {
         Line::from(Span::styled("Esc - Close help / exit focus", Style::default().fg(text_color))),
         Line::from(Span::styled("V - Cycle view (Default/States/Fullscreen)", Style::default().fg(text_color))),
         Line::from(Span::styled("Shift+X - Export config to file", Style::default().fg(text_color))),
+        Line::from(Span::styled("Shift+V - Save PNG snapshot", Style::default().fg(text_color))),
+        Line::from(Span::styled("F5/F9 - Save/load full simulation state", Style::default().fg(text_color))),
         Line::from(Span::styled("H - Show help", Style::default().fg(text_color))),
         Line::from(Span::styled("Q - Quit", Style::default().fg(text_color))),
         Line::from(""),
@@ -988,7 +1207,16 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, app: &App) {
         Line::from(Span::styled("[/] - Adjust highlight count", Style::default().fg(text_color))),
         Line::from(Span::styled("A - Toggle color-by-age", Style::default().fg(text_color))),
         Line::from(Span::styled("L - Cycle min brightness", Style::default().fg(text_color))),
+        Line::from(Span::styled("F - Potential heat map (DBM model)", Style::default().fg(text_color))),
         Line::from(Span::styled("` - Start/stop recording", Style::default().fg(text_color))),
+        Line::from(Span::styled("D - Paint mode: left drag paints, right drag erases", Style::default().fg(text_color))),
+        Line::from(Span::styled("G - Paint tool (seed/erase/wall)", Style::default().fg(text_color))),
+        Line::from(Span::styled(",/. or wheel - Brush size", Style::default().fg(text_color))),
+        Line::from(Span::styled("U - Save drawing as a custom seed", Style::default().fg(text_color))),
+        Line::from(Span::styled("Z - Timeline: pause and scrub the growth history", Style::default().fg(text_color))),
+        Line::from(Span::styled("Left/Right, ,/. - Scrub 1% / 1 particle", Style::default().fg(text_color))),
+        Line::from(Span::styled("Home/End - First/last particle", Style::default().fg(text_color))),
+        Line::from(Span::styled("Enter - Branch: regrow from the cursor", Style::default().fg(text_color))),
         Line::from(Span::styled("Shift+L - Load preset", Style::default().fg(text_color))),
         Line::from(Span::styled("Shift+K - Save preset", Style::default().fg(text_color))),
         Line::from(Span::styled("Shift+S - Spawn popup", Style::default().fg(text_color))),
@@ -1007,6 +1235,9 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, app: &App) {
         Line::from(""),
         Line::from(Span::styled("MOVEMENT PARAMETERS:", Style::default().fg(highlight_color))),
         Line::from(""),
+        Line::from("Model - DLA/Eden/Ballistic/RLA/DBM/DLCA growth rule"),
+        Line::from("Eta (0-4) - DBM field exponent, 0 compact to 4 needles"),
+        Line::from("Mobility (0-2) - DLCA cluster speed falls as size^-mobility"),
         Line::from("Walk Step (0.5-5.0) - Distance per step"),
         Line::from("Direction (0-360) - Bias angle"),
         Line::from("Force (0-0.5) - Bias strength"),
@@ -1024,13 +1255,14 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, app: &App) {
         Line::from(""),
         Line::from("Spawn - Circle/Edges/Corners/Random/Dir"),
         Line::from("Boundary - Clamp/Wrap/Bounce/Stick/Absorb"),
+        Line::from("Walls - Edges that Stick deposits grow on"),
         Line::from("Offset/Escape/MinRadius/MaxIter"),
         Line::from(""),
         Line::from(Span::styled("VISUAL PARAMETERS:", Style::default().fg(highlight_color))),
         Line::from(""),
         Line::from("Particles (100-10000) - Total count"),
         Line::from("Speed (1-100) - Steps per frame"),
-        Line::from("Color - 16 schemes, 4 modes"),
+        Line::from("Color - 16 schemes, 10 modes"),
         Line::from("Highlight (0-50) - Recent particles in white"),
         Line::from(""),
     ];
@@ -1062,7 +1294,7 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, app: &App) {
 }
 
 /// Render parameter selection popup
-fn render_param_popup(frame: &mut Frame, area: Rect, popup: &ParamPopup, theme: &crate::theme::Theme) {
+fn render_param_popup(frame: &mut Frame, area: Rect, popup: &ParamPopup, theme: &dla_sim_tui::theme::Theme) {
     let highlight_color = theme.highlight_color;
     let text_color = theme.text_color;
 
@@ -1135,7 +1367,7 @@ fn render_param_popup(frame: &mut Frame, area: Rect, popup: &ParamPopup, theme:
 }
 
 /// Render text input popup for export filename
-fn render_export_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup, theme: &crate::theme::Theme) {
+fn render_export_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup, theme: &dla_sim_tui::theme::Theme) {
     let highlight_color = theme.highlight_color;
     let text_color = theme.text_color;
     let dim_text_color = theme.dim_text_color;
@@ -1218,7 +1450,7 @@ fn render_result_toast(frame: &mut Frame, area: Rect, message: &str, color: Colo
 }
 
 /// Render export result toast (success or error message)
-fn render_export_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &crate::theme::Theme) {
+fn render_export_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &dla_sim_tui::theme::Theme) {
     let (message, color) = match result {
         Ok(filename) => (format!("Saved: {}", filename), theme.success_color),
         Err(e) => (format!("Error: {}", e), theme.error_color),
@@ -1226,8 +1458,142 @@ fn render_export_result(frame: &mut Frame, area: Rect, result: &Result<String, S
     render_result_toast(frame, area, &message, color);
 }
 
+/// Render text input popup for PNG snapshot filename and pixel scale
+fn render_snapshot_popup(
+    frame: &mut Frame,
+    area: Rect,
+    popup: &TextInputPopup,
+    scale: u32,
+    tiles: u32,
+    theme: &dla_sim_tui::theme::Theme,
+) {
+    let highlight_color = theme.highlight_color;
+    let text_color = theme.text_color;
+    let dim_text_color = theme.dim_text_color;
+
+    let popup_width = 44.min(area.width.saturating_sub(4));
+    let popup_height = 6;
+
+    let popup_x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+
+    let popup_area = Rect {
+        x: popup_x,
+        y: popup_y,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    // Build input line with cursor
+    let (before_cursor, after_cursor) = popup.input.split_at(popup.cursor_pos);
+    let content = vec![
+        Line::from(vec![
+            Span::styled(before_cursor, Style::default().fg(text_color)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(highlight_color)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+            Span::styled(after_cursor, Style::default().fg(text_color)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Scale: ", Style::default().fg(dim_text_color)),
+            Span::styled(format!("{}x", scale), Style::default().fg(highlight_color)),
+            Span::styled(" (↑/↓)", Style::default().fg(dim_text_color)),
+            Span::styled(
+                if tiles > 1 { format!("  Tiles: {}x{}", tiles, tiles) } else { String::new() },
+                Style::default().fg(dim_text_color),
+            ),
+        ]),
+        Line::from(Span::styled(
+            "Enter: save | Esc: cancel",
+            Style::default().fg(dim_text_color),
+        )),
+    ];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(highlight_color))
+        .title(popup.title);
+
+    let paragraph = Paragraph::new(content).block(block);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Render text input popup for state save/load filename
+fn render_state_popup(
+    frame: &mut Frame,
+    area: Rect,
+    popup: &TextInputPopup,
+    action: StateAction,
+    theme: &dla_sim_tui::theme::Theme,
+) {
+    let highlight_color = theme.highlight_color;
+    let text_color = theme.text_color;
+    let dim_text_color = theme.dim_text_color;
+
+    let popup_width = 44.min(area.width.saturating_sub(4));
+    let popup_height = 5;
+
+    let popup_x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+
+    let popup_area = Rect {
+        x: popup_x,
+        y: popup_y,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let hint = match action {
+        StateAction::Save => "Enter: save | Esc: cancel",
+        StateAction::Load => "Enter: load (replaces run) | Esc: cancel",
+    };
+
+    // Build input line with cursor
+    let (before_cursor, after_cursor) = popup.input.split_at(popup.cursor_pos);
+    let content = vec![
+        Line::from(vec![
+            Span::styled(before_cursor, Style::default().fg(text_color)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(highlight_color)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+            Span::styled(after_cursor, Style::default().fg(text_color)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(hint, Style::default().fg(dim_text_color))),
+    ];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(highlight_color))
+        .title(popup.title);
+
+    let paragraph = Paragraph::new(content).block(block);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Render state save/load result toast (success or error message)
+fn render_state_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &dla_sim_tui::theme::Theme) {
+    match result {
+        Ok(msg) => render_result_toast(frame, area, msg, theme.success_color),
+        Err(e) => render_result_toast(frame, area, &format!("Error: {}", e), theme.error_color),
+    }
+}
+
 /// Render text input popup for recording filename
-fn render_recording_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup, theme: &crate::theme::Theme) {
+fn render_recording_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup, theme: &dla_sim_tui::theme::Theme) {
     let highlight_color = theme.highlight_color;
     let text_color = theme.text_color;
     let dim_text_color = theme.dim_text_color;
@@ -1262,7 +1628,7 @@ fn render_recording_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup,
         ]),
         Line::from(""),
         Line::from(Span::styled(
-            ".mp4/.webm (FFmpeg) or .gif",
+            ".mp4/.webm (FFmpeg), .gif, .png or dir/",
             Style::default().fg(dim_text_color),
         )),
         Line::from(Span::styled(
@@ -1282,7 +1648,7 @@ fn render_recording_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup,
 }
 
 /// Render recording result toast (success or error message)
-fn render_recording_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &crate::theme::Theme) {
+fn render_recording_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &dla_sim_tui::theme::Theme) {
     let (message, color) = match result {
         Ok(msg) => (msg.as_str(), theme.success_color),
         Err(e) => return render_result_toast(frame, area, &format!("Error: {}", e), theme.error_color),
@@ -1291,7 +1657,7 @@ fn render_recording_result(frame: &mut Frame, area: Rect, result: &Result<String
 }
 
 /// Render preset selection popup
-fn render_preset_popup(frame: &mut Frame, area: Rect, popup: &PresetPopup, theme: &crate::theme::Theme) {
+fn render_preset_popup(frame: &mut Frame, area: Rect, popup: &PresetPopup, theme: &dla_sim_tui::theme::Theme) {
     let highlight_color = theme.highlight_color;
     let text_color = theme.text_color;
 
@@ -1349,7 +1715,7 @@ fn render_preset_popup(frame: &mut Frame, area: Rect, popup: &PresetPopup, theme
 }
 
 /// Render preset result toast (success or error message)
-fn render_preset_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &crate::theme::Theme) {
+fn render_preset_result(frame: &mut Frame, area: Rect, result: &Result<String, String>, theme: &dla_sim_tui::theme::Theme) {
     let (message, color) = match result {
         Ok(msg) => (msg.as_str(), theme.success_color),
         Err(e) => return render_result_toast(frame, area, &format!("Error: {}", e), theme.error_color),
@@ -1357,8 +1723,14 @@ fn render_preset_result(frame: &mut Frame, area: Rect, result: &Result<String, S
     render_result_toast(frame, area, message, color);
 }
 
-/// Render preset save popup (text input for preset name)
-fn render_preset_save_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopup, theme: &crate::theme::Theme) {
+/// Render a popup asking for a name (preset or custom seed)
+fn render_name_popup(
+    frame: &mut Frame,
+    area: Rect,
+    popup: &TextInputPopup,
+    prompt: &str,
+    theme: &dla_sim_tui::theme::Theme,
+) {
     let highlight_color = theme.highlight_color;
     let text_color = theme.text_color;
 
@@ -1399,7 +1771,7 @@ fn render_preset_save_popup(frame: &mut Frame, area: Rect, popup: &TextInputPopu
 
     let content = vec![
         Line::from(vec![Span::styled(
-            "Enter preset name:",
+            prompt,
             Style::default().fg(theme.dim_text_color),
         )]),
         Line::from(input_display),